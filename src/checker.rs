@@ -2,10 +2,23 @@ use self::{
 	case::{check_ident, Case},
 	context::{check_stmt, Context},
 };
-use crate::{common::stmt::Stmt, parser::types::ParserScope};
+use crate::{
+	common::{
+		expr::Expr,
+		ident::Ident,
+		pattern::Pattern,
+		span::Spanned,
+		stmt::Stmt,
+		visit::{walk_expr, walk_scope, walk_stmt, Visit},
+	},
+	parser::types::{ParserExpr, ParserScope, ParserStmt},
+	session::CompilerSession,
+};
 
+pub mod safety;
 pub mod case;
 pub mod context;
+pub mod mutability;
 
 // Case systems:
 // variables/functions -> snake_case
@@ -13,34 +26,114 @@ pub mod context;
 // pure consts -> UPPER_SNAKE_CASE
 // never use camelCase
 
-fn check_inner(scope: &ParserScope, context: Context) {
-	for stmt in &scope.stmts {
-		check_stmt(stmt, context);
+/// Checks the case of every name declared in a scope, and that every stmt is
+/// allowed where it is.
+struct Checker<'a> {
+	session: &'a CompilerSession,
+	/// Where the stmts being checked are, which nested scopes keep except for
+	/// the bodies of loops, functions and closures.
+	context: Context,
+}
+
+impl Checker<'_> {
+	fn with_context(&mut self, context: Context, body: impl FnOnce(&mut Self)) {
+		let outer = std::mem::replace(&mut self.context, context);
+		body(self);
+		self.context = outer;
+	}
+}
+
+impl<'a> Visit<'a, ParserScope> for Checker<'_> {
+	fn scope(&mut self, scope: &'a ParserScope) {
+		walk_scope(self, scope);
+	}
+
+	fn stmt(&mut self, stmt: &'a Spanned<ParserStmt>) {
+		let session = self.session;
+		check_stmt(session, stmt, self.context);
 		match &stmt.value {
-			Stmt::Create { ty_id, .. } => check_ident(&ty_id.value.ident, Case::SnakeCase),
-			Stmt::Set { .. } | Stmt::Return { .. } => {}
+			Stmt::Create { ty_id, .. } => check_ident(session, &ty_id.value.ident, Case::SnakeCase),
+			Stmt::Destructure { ids, .. } => {
+				for id in &ids.value {
+					check_ident(session, id, Case::SnakeCase);
+				}
+			}
 			Stmt::Func {
 				id,
 				signature,
 				body,
 			} => {
-				check_ident(id, Case::SnakeCase);
+				check_ident(session, id, Case::SnakeCase);
 				for generic in &signature.generics.value {
-					check_ident(generic, Case::PascalCase);
+					check_ident(session, generic, Case::PascalCase);
 				}
 				for arg in &signature.args.value {
-					check_ident(&arg.value.ident, Case::SnakeCase);
+					check_ident(session, &arg.value.ident, Case::SnakeCase);
+				}
+				if let Some(body) = body {
+					self.with_context(Context::Func, |x| x.scope(&body.value));
+				}
+				return;
+			}
+			Stmt::Struct {
+				id,
+				generics,
+				fields,
+			} => {
+				check_ident(session, id, Case::PascalCase);
+				for generic in &generics.value {
+					check_ident(session, generic, Case::PascalCase);
+				}
+				for field in &fields.value {
+					check_ident(session, &field.value.ident, Case::SnakeCase);
 				}
-				match body {
-					Some(body) => check_inner(&body.value, Context::Func),
-					None => {}
+			}
+			Stmt::Enum { id, variants } => {
+				check_ident(session, id, Case::PascalCase);
+				for variant in &variants.value {
+					check_ident(session, &variant.value.id, Case::PascalCase);
 				}
 			}
+			Stmt::Const { id, .. } => check_ident(session, id, Case::UpperSnakeCase),
+			_ => {}
+		}
+		walk_stmt(self, stmt);
+	}
+
+	fn expr(&mut self, expr: &'a Spanned<ParserExpr>) {
+		if let Expr::Closure {
+			signature, body, ..
+		} = &expr.value
+		{
+			for arg in &signature.args.value {
+				check_ident(self.session, &arg.value.ident, Case::SnakeCase);
+			}
+			self.with_context(Context::Func, |x| x.scope(&body.value));
+		} else {
+			walk_expr(self, expr);
+		}
+	}
+
+	fn loop_body(&mut self, body: &'a Spanned<ParserExpr>) {
+		self.with_context(Context::Loop, |x| x.expr(body));
+	}
+
+	fn loop_var(&mut self, var: &'a Spanned<Ident>) {
+		check_ident(self.session, var, Case::SnakeCase);
+	}
+
+	fn pattern(&mut self, pattern: &'a Spanned<Pattern>) {
+		for binding in pattern.bindings() {
+			check_ident(self.session, &binding, Case::SnakeCase);
 		}
 	}
 }
 
 // TODO: check no yields in funcs
-pub fn check(scope: &ParserScope) {
-	check_inner(scope, Context::TopLevel);
+pub fn check(session: &CompilerSession, scope: &ParserScope) {
+	let mut checker = Checker {
+		session,
+		context: Context::TopLevel,
+	};
+	checker.scope(scope);
 }
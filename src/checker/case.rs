@@ -4,6 +4,7 @@ use crate::{
 		diagnostics::invalid_case,
 		span::{Span, Spanned},
 	},
+	session::CompilerSession,
 };
 use derive_more::Display;
 
@@ -43,7 +44,7 @@ fn begins_with_uppercase(str: &str) -> bool {
 	str.chars().next().unwrap().is_uppercase()
 }
 
-pub fn check(span: &Span, name: &str, wanted: Case) {
+pub fn check(session: &CompilerSession, span: &Span, name: &str, wanted: Case) {
 	let found = match wanted {
 		Case::PascalCase => {
 			if name.contains('_') {
@@ -80,19 +81,21 @@ pub fn check(span: &Span, name: &str, wanted: Case) {
 		Case::UpperSnakeCase => {
 			if is_uppercase(name) {
 				Case::UpperSnakeCase
-			} else if name.contains('_') {
+			} else if name.contains('_') || !has_uppercase(name) {
 				Case::SnakeCase
-			} else {
+			} else if begins_with_uppercase(name) {
 				Case::PascalCase
+			} else {
+				Case::CamelCase
 			}
 		}
 		_ => panic!("why"),
 	};
 	if found != wanted {
-		invalid_case(*span, wanted, found);
+		invalid_case(session, *span, wanted, found);
 	}
 }
 
-pub fn check_ident(ident: &Spanned<Ident>, wanted: Case) {
-	check(&ident.span, &ident.value.to_string(), wanted);
+pub fn check_ident(session: &CompilerSession, ident: &Spanned<Ident>, wanted: Case) {
+	check(session, &ident.span, &ident.value.to_string(), wanted);
 }
@@ -1,6 +1,7 @@
 use crate::{
 	common::{diagnostics::invalid_stmt, span::Spanned},
 	parser::types::ParserStmt,
+	session::CompilerSession,
 };
 use derive_more::Display;
 
@@ -10,16 +11,20 @@ pub enum Context {
 	TopLevel,
 	#[display(fmt = "function")]
 	Func,
+	/// The body of a loop (or any scope nested in it), which is also inside a
+	/// function.
+	#[display(fmt = "loop")]
+	Loop,
 }
 
 macro_rules! check_stmt {
 	($($v:ident => $($ctx:ident)*;)*) => {
-		pub fn check_stmt(stmt: &Spanned<ParserStmt>, context: Context) {
+		pub fn check_stmt(session: &CompilerSession, stmt: &Spanned<ParserStmt>, context: Context) {
 			match stmt.value {
 				$(
 					ParserStmt::$v{..} => {
 						if $(context != Context::$ctx)&&* {
-							invalid_stmt(stmt, context);
+							invalid_stmt(session, stmt, context);
 						}
 					}
 				)*
@@ -29,8 +34,16 @@ macro_rules! check_stmt {
 }
 
 check_stmt!(
-	Create => Func;
-	Set => Func;
-	Func => TopLevel Func;
-	Return => Func;
+	Create => Func Loop;
+	Destructure => Func Loop;
+	Set => Func Loop;
+	Store => Func Loop;
+	Func => TopLevel Func Loop;
+	Struct => TopLevel Func Loop;
+	Enum => TopLevel Func Loop;
+	Const => TopLevel;
+	Return => Func Loop;
+	Break => Loop;
+	Continue => Loop;
+	Import => TopLevel;
 );
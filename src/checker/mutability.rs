@@ -0,0 +1,272 @@
+use crate::{
+	common::{
+		diagnostics::{immutable_binding, immutable_capture},
+		expr::{Capture, Expr},
+		ident::{Id, Ident},
+		pattern::Pattern,
+		span::{Add, Span, Spanned},
+		stmt::Stmt,
+		typed_ident::TypedIdent,
+		visit::{walk_expr, walk_scope, walk_stmt, Visit},
+	},
+	hoister::{HoistedExpr, HoistedScope, HoistedStmt},
+	session::CompilerSession,
+};
+use std::collections::HashMap;
+
+/// Something that can be assigned to by name.
+#[derive(Clone, Copy)]
+enum Binding {
+	Var { mutable: bool },
+	Arg,
+	LoopVar,
+	PatternVar,
+	Func,
+	Const,
+}
+
+/// Since every id is unique once resolved, bindings never shadow each other and
+/// a single map is enough for the whole program.
+#[derive(Default)]
+struct Bindings {
+	/// Every binding along with its declaration span.
+	declared: HashMap<Id, Spanned<Binding>>,
+	/// The span of the outermost closure capturing each variable, while inside
+	/// of it.
+	captured: HashMap<Id, Span>,
+}
+
+/// Describes what `binding` is and why it can't be changed, or returns `None`
+/// if it can.
+fn describe(binding: Binding) -> Option<(&'static str, &'static str)> {
+	Some(match binding {
+		Binding::Var { mutable: true } => return None,
+		Binding::Var { mutable: false } => ("an immutable variable", "declared here without `mut`"),
+		Binding::Arg => ("a function argument", "arguments are always immutable"),
+		Binding::LoopVar => ("a loop variable", "loop variables are always immutable"),
+		Binding::PatternVar => (
+			"a variable bound by a pattern",
+			"variables bound by patterns are always immutable",
+		),
+		Binding::Func => ("a function", "declared here as a function"),
+		Binding::Const => ("a const", "consts are always immutable"),
+	})
+}
+
+/// Reports `id` being changed (as described by `action`) if it's immutable.
+fn check_binding(
+	session: &CompilerSession,
+	id: &Spanned<Ident>,
+	action: &str,
+	bindings: &Bindings,
+) {
+	let Ident::Resolved(x) = id.value else {
+		return;
+	};
+	// captured variables are checked once, where the closure is
+	if bindings.captured.contains_key(&x) {
+		return;
+	}
+	let Some(binding) = bindings.declared.get(&x) else {
+		return;
+	};
+	if let Some((what, why)) = describe(binding.value) {
+		immutable_binding(session, id.span, binding.span, action, what, why);
+	}
+}
+
+/// Reports `capture` being mutated by the closure at `closure` if it's
+/// immutable.
+fn check_capture(
+	session: &CompilerSession,
+	capture: &Spanned<Capture>,
+	closure: Span,
+	bindings: &Bindings,
+) {
+	let Some(binding) = bindings.declared.get(&capture.value.id) else {
+		return;
+	};
+	if let (true, Some((what, why))) = (capture.value.mutated, describe(binding.value)) {
+		immutable_capture(session, capture.span, binding.span, closure, what, why);
+	}
+}
+
+fn declare_args(args: &[Spanned<TypedIdent>], bindings: &mut Bindings) {
+	for arg in args {
+		if let Ident::Resolved(x) = arg.ident() {
+			bindings.declared.insert(*x, Binding::Arg.add_span(arg.span));
+		}
+	}
+}
+
+struct Checker<'a> {
+	session: &'a CompilerSession,
+	bindings: Bindings,
+	/// The immutable variables declared without a value that haven't been
+	/// assigned to yet, along with the amount of loops and closures their
+	/// declaration is in. Their first assignment is where they get their value,
+	/// as long as it doesn't run more than once.
+	unassigned: HashMap<Id, usize>,
+	/// The amount of loops and closures the code being checked is in.
+	depth: usize,
+}
+
+impl<'a> Checker<'a> {
+	/// Checks the code of every branch of a conditional in turn, after which
+	/// a variable is only still unassigned if no branch assigned to it.
+	fn branches(&mut self, branches: &[&'a Spanned<HoistedExpr>]) {
+		let before = self.unassigned.clone();
+		let mut after = before.clone();
+		for branch in branches {
+			self.unassigned.clone_from(&before);
+			self.expr(branch);
+			after.retain(|id, _| self.unassigned.contains_key(id));
+		}
+		self.unassigned = after;
+	}
+}
+
+impl<'a> Visit<'a, HoistedScope> for Checker<'a> {
+	fn scope(&mut self, scope: &'a HoistedScope) {
+		for (ident, var) in &scope.data.vars {
+			let binding = Binding::Var {
+				mutable: var.value.mutable,
+			};
+			self.bindings.declared.insert(ident.id(), binding.add_span(var.span));
+		}
+		for (ident, func) in &scope.data.funcs {
+			self.bindings.declared.insert(ident.id(), Binding::Func.add_span(func.span));
+		}
+		for (ident, r#const) in &scope.data.consts {
+			self.bindings.declared.insert(ident.id(), Binding::Const.add_span(r#const.span));
+		}
+		for func in scope.data.funcs.values() {
+			declare_args(&func.value.signature.args.value, &mut self.bindings);
+			if let Some(body) = &func.value.body {
+				self.scope(&body.value);
+			}
+		}
+		walk_scope(self, scope);
+	}
+
+	fn stmt(&mut self, stmt: &'a Spanned<HoistedStmt>) {
+		match &stmt.value {
+			Stmt::Create {
+				ty_id,
+				mutable: false,
+				value: None,
+			} => {
+				if let Ident::Resolved(x) = ty_id.ident() {
+					self.unassigned.insert(*x, self.depth);
+				}
+			}
+			Stmt::Set { id, .. } => {
+				let first = match id.value {
+					Ident::Resolved(x) => self.unassigned.get(&x) == Some(&self.depth),
+					_ => false,
+				};
+				if first {
+					self.unassigned.remove(&id.value.id());
+				} else {
+					check_binding(self.session, id, "assign to", &self.bindings);
+				}
+			}
+			// whether a pointer allows changing its pointee is part of its type, so
+			// stores are up to inference
+			_ => {}
+		}
+		walk_stmt(self, stmt);
+	}
+
+	fn expr(&mut self, expr: &'a Spanned<HoistedExpr>) {
+		match &expr.value {
+			Expr::Ref {
+				value,
+				mutable: true,
+			} => {
+				if let Some(place) = value.place() {
+					check_binding(self.session, &place, "mutably reference", &self.bindings);
+				}
+				walk_expr(self, expr);
+			}
+			Expr::If {
+				cond,
+				then,
+				otherwise,
+			} => {
+				self.expr(cond);
+				// without `otherwise`, the variables stay as they were if `then` doesn't
+				// run, which doesn't change which ones are unassigned after it
+				match otherwise {
+					Some(otherwise) => self.branches(&[then, otherwise]),
+					None => self.branches(&[then]),
+				}
+			}
+			Expr::Match { value, arms } => {
+				self.expr(value);
+				for (pattern, _) in arms {
+					self.pattern(pattern);
+				}
+				let arms: Vec<_> = arms.iter().map(|(_, value)| value).collect();
+				self.branches(&arms);
+			}
+			Expr::Closure {
+				signature,
+				body,
+				captures,
+			} => {
+				declare_args(&signature.args.value, &mut self.bindings);
+				// closures nested in this one capture a subset of what this one does
+				let mut new = Vec::new();
+				for capture in captures {
+					if !self.bindings.captured.contains_key(&capture.value.id) {
+						check_capture(self.session, capture, expr.span, &self.bindings);
+						self.bindings.captured.insert(capture.value.id, expr.span);
+						new.push(capture.value.id);
+					}
+				}
+				// the closure may be called any number of times
+				self.depth += 1;
+				self.scope(&body.value);
+				self.depth -= 1;
+				for id in new {
+					self.bindings.captured.remove(&id);
+				}
+			}
+			_ => walk_expr(self, expr),
+		}
+	}
+
+	fn loop_body(&mut self, body: &'a Spanned<HoistedExpr>) {
+		self.depth += 1;
+		self.expr(body);
+		self.depth -= 1;
+	}
+
+	fn loop_var(&mut self, var: &'a Spanned<Ident>) {
+		if let Ident::Resolved(x) = var.value {
+			self.bindings.declared.insert(x, Binding::LoopVar.add_span(var.span));
+		}
+	}
+
+	fn pattern(&mut self, pattern: &'a Spanned<Pattern>) {
+		for binding in pattern.bindings() {
+			let declared = Binding::PatternVar.add_span(binding.span);
+			self.bindings.declared.insert(binding.value.id(), declared);
+		}
+	}
+}
+
+/// Reports every assignment to (or mutable reference of) something that can't
+/// be changed: immutable variables past their first assignment, function
+/// arguments, loop variables, functions and consts, including ones captured
+/// and changed by closures.
+pub fn check_mutability(session: &CompilerSession, scope: &HoistedScope) {
+	let mut checker = Checker {
+		session,
+		bindings: Bindings::default(),
+		unassigned: HashMap::new(),
+		depth: 0,
+	};
+	checker.scope(scope);
+}
@@ -0,0 +1,203 @@
+use crate::{
+	common::{
+		diagnostics::{
+			impure_assignment, impure_call, impure_store, unknown_purity, unsafe_call,
+			unsafe_deref, unsafe_value_call,
+		},
+		expr::Expr,
+		func::Signature,
+		ident::{Id, Ident},
+		pattern::Pattern,
+		r#type::Type,
+		span::{Span, Spanned},
+		stmt::Stmt,
+		visit::{walk_expr, walk_scope, walk_stmt, Visit},
+	},
+	hoister::{HoistedExpr, HoistedFunc, HoistedScope, HoistedStmt},
+	infer::typed::Typed,
+	session::CompilerSession,
+};
+use std::collections::HashMap;
+
+/// What the code being checked is allowed to do.
+#[derive(Clone, Copy, Default)]
+struct Context {
+	/// The id of the innermost function.
+	func: Option<Id>,
+	/// The span of the attributes of the innermost function, if it's pure.
+	pure: Option<Span>,
+	is_unsafe: bool,
+}
+
+/// Since every id is unique once resolved, nothing shadows anything else and a
+/// single set of maps is enough for the whole program.
+struct Items<'a> {
+	session: &'a CompilerSession,
+	typed: &'a Typed<'a>,
+	funcs: HashMap<Id, &'a Spanned<HoistedFunc>>,
+	/// The function every variable was declared in, along with its declaration
+	/// span.
+	owners: HashMap<Id, (Option<Id>, Span)>,
+	/// What the code being checked is allowed to do.
+	context: Context,
+}
+
+impl Items<'_> {
+	/// Registers the args of a function or closure as belonging to the
+	/// function being checked.
+	fn args(&mut self, signature: &Signature) {
+		for arg in &signature.args.value {
+			if let Ident::Resolved(x) = arg.ident() {
+				self.owners.insert(*x, (self.context.func, arg.span));
+			}
+		}
+	}
+}
+
+/// Reports going through the raw pointer `ptr` outside of unsafe code. `span`
+/// is the span of the whole dereference or store.
+fn check_deref(ptr: &Spanned<HoistedExpr>, span: Span, items: &Items) {
+	if let (Type::Ptr(..), false) = (&items.typed.expr_ty(ptr).value, items.context.is_unsafe) {
+		unsafe_deref(items.session, span);
+	}
+}
+
+fn check_call(callee: &Spanned<HoistedExpr>, items: &Items) {
+	let context = items.context;
+	let func = match &callee.value {
+		Expr::Identifier(Ident::Resolved(id)) => items.funcs.get(id),
+		// building a variant has no effects at all
+		Expr::Variant { .. } => return,
+		_ => None,
+	};
+	let Some(func) = func else {
+		if let Some(pure) = context.pure {
+			unknown_purity(items.session, callee.span, pure);
+		}
+		// calling anything but a function by name, only its type tells whether
+		// it's unsafe
+		let is_unsafe = matches!(
+			items.typed.expr_ty(callee).value,
+			Type::Func { is_unsafe: true, .. }
+		);
+		if is_unsafe && !context.is_unsafe {
+			unsafe_value_call(items.session, callee.span);
+		}
+		return;
+	};
+	let attribs = &func.value.signature.attribs.value;
+	if let (Some(pure), false) = (context.pure, attribs.is_pure) {
+		impure_call(items.session, callee.span, &func.value.id.value, func.span, pure);
+	}
+	if attribs.is_unsafe && !context.is_unsafe {
+		unsafe_call(items.session, callee.span, &func.value.id.value, func.span);
+	}
+}
+
+impl<'a> Visit<'a, HoistedScope> for Items<'a> {
+	fn scope(&mut self, scope: &'a HoistedScope) {
+		for (ident, var) in &scope.data.vars {
+			self.owners.insert(ident.id(), (self.context.func, var.span));
+		}
+		for (ident, func) in &scope.data.funcs {
+			self.funcs.insert(ident.id(), func);
+		}
+		let outer = self.context;
+		for (ident, func) in &scope.data.funcs {
+			let attribs = &func.value.signature.attribs;
+			// a function inside an unsafe one is still checked on its own
+			self.context = Context {
+				func: Some(ident.id()),
+				pure: attribs.value.is_pure.then_some(attribs.span),
+				is_unsafe: attribs.value.is_unsafe,
+			};
+			self.args(&func.value.signature);
+			if let Some(body) = &func.value.body {
+				self.scope(&body.value);
+			}
+		}
+		self.context = outer;
+		walk_scope(self, scope);
+	}
+
+	fn stmt(&mut self, stmt: &'a Spanned<HoistedStmt>) {
+		match &stmt.value {
+			Stmt::Set { id, .. } => {
+				let owner = match id.value {
+					Ident::Resolved(x) => self.owners.get(&x),
+					_ => None,
+				};
+				// assigning to anything that isn't a var is reported by the mutability pass
+				if let (Some(pure), Some((owner, declaration))) = (self.context.pure, owner) {
+					if *owner != self.context.func {
+						impure_assignment(self.session, id.span, *declaration, pure);
+					}
+				}
+			}
+			Stmt::Store { ptr, .. } => {
+				check_deref(ptr, stmt.span, self);
+				if let Some(pure) = self.context.pure {
+					impure_store(self.session, ptr.span, pure);
+				}
+			}
+			_ => {}
+		}
+		walk_stmt(self, stmt);
+	}
+
+	fn expr(&mut self, expr: &'a Spanned<HoistedExpr>) {
+		match &expr.value {
+			Expr::Deref(value) => check_deref(value, expr.span, self),
+			Expr::Call { callee, .. } => check_call(callee, self),
+			Expr::Unsafe(scope) => {
+				let outer = self.context;
+				self.context.is_unsafe = true;
+				self.scope(scope);
+				self.context = outer;
+				return;
+			}
+			Expr::Closure {
+				signature, body, ..
+			} => {
+				self.args(signature);
+				// the closure runs wherever it's called, which might not be unsafe, but
+				// what it changes still belongs to the function it's in
+				let outer = self.context;
+				self.context.is_unsafe = false;
+				self.scope(&body.value);
+				self.context = outer;
+				return;
+			}
+			_ => {}
+		}
+		walk_expr(self, expr);
+	}
+
+	fn loop_var(&mut self, var: &'a Spanned<Ident>) {
+		if let Ident::Resolved(x) = var.value {
+			self.owners.insert(x, (self.context.func, var.span));
+		}
+	}
+
+	fn pattern(&mut self, pattern: &'a Spanned<Pattern>) {
+		for binding in pattern.bindings() {
+			self.owners.insert(binding.value.id(), (self.context.func, binding.span));
+		}
+	}
+}
+
+/// Makes sure function attributes hold: pure functions only call other pure
+/// functions, never assign to variables declared outside of them and never
+/// write through pointers, and unsafe functions are only called from unsafe
+/// functions or `unsafe` blocks, just like raw pointers are only gone through
+/// there.
+pub fn check_safety(session: &CompilerSession, typed: &Typed) {
+	let mut items = Items {
+		session,
+		typed,
+		funcs: HashMap::new(),
+		owners: HashMap::new(),
+		context: Context::default(),
+	};
+	items.scope(&typed.program.value);
+}
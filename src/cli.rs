@@ -0,0 +1,103 @@
+//! Command-line interface of the compiler.
+//!
+//! The process exits with code 0 when no errors were emitted, 1 when the
+//! compilation produced errors (or warnings with `--deny-warnings`) and 2 when
+//! the invocation itself was wrong, e.g. an unknown flag or an unreadable
+//! input file.
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use codespan_reporting::term::termcolor::ColorChoice;
+use lang::Step;
+use std::{io::IsTerminal, path::PathBuf};
+
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+	#[command(subcommand)]
+	pub command: Command,
+	/// When to color the rendered diagnostics.
+	#[arg(long, value_enum, default_value_t = Color::Auto, global = true)]
+	pub color: Color,
+	/// Treat every warning as an error.
+	#[arg(short = 'W', long, global = true)]
+	pub deny_warnings: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+	/// Run every analysis pass and only report diagnostics.
+	Check(Inputs),
+	/// Print the parsed syntax tree.
+	DumpAst(Inputs),
+	/// Print the syntax tree after hoisting.
+	DumpHoisted(Inputs),
+	/// Print the syntax tree after name resolution.
+	DumpResolved(Inputs),
+	/// Print the type table produced by inference.
+	DumpTypes(Inputs),
+	/// Interpret the inputs, printing what their `main` function returns.
+	Run(Inputs),
+	/// Compile the inputs into LLVM IR.
+	Build {
+		#[command(flatten)]
+		inputs: Inputs,
+		/// Where to write the generated LLVM IR, `-` meaning stdout. Defaults
+		/// to the first input path with an `.ll` extension.
+		#[arg(short, long)]
+		output: Option<PathBuf>,
+	},
+}
+
+#[derive(Args, Debug)]
+pub struct Inputs {
+	/// Source files to compile. Each one is a module of the same program,
+	/// whose root is the first one.
+	#[arg(required = true)]
+	pub paths: Vec<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Color {
+	/// Color only when writing to a terminal.
+	Auto,
+	Always,
+	Never,
+}
+
+impl Command {
+	#[must_use]
+	pub fn inputs(&self) -> &Inputs {
+		match self {
+			Self::Check(inputs)
+			| Self::DumpAst(inputs)
+			| Self::DumpHoisted(inputs)
+			| Self::DumpResolved(inputs)
+			| Self::DumpTypes(inputs)
+			| Self::Run(inputs)
+			| Self::Build { inputs, .. } => inputs,
+		}
+	}
+
+	/// The last compilation step the command needs run before it can do what
+	/// it does with the program.
+	#[must_use]
+	pub fn step(&self) -> Step {
+		match self {
+			Self::DumpAst(_) => Step::Parse,
+			Self::DumpHoisted(_) => Step::Hoist,
+			Self::DumpResolved(_) => Step::Resolve,
+			Self::Check(_) | Self::DumpTypes(_) | Self::Run(_) | Self::Build { .. } => Step::Infer,
+		}
+	}
+}
+
+impl Color {
+	#[must_use]
+	pub fn choice(self) -> ColorChoice {
+		match self {
+			Self::Auto if std::io::stderr().is_terminal() => ColorChoice::Auto,
+			Self::Auto | Self::Never => ColorChoice::Never,
+			Self::Always => ColorChoice::Always,
+		}
+	}
+}
@@ -0,0 +1,1603 @@
+//! Generates textual LLVM IR (`.ll`) out of a resolved and type-inferred
+//! program. We don't link against LLVM, the output is meant to be fed to
+//! `llc`, `clang` or any other tool that understands LLVM IR.
+
+use self::ty::{Ty, POINTER_WIDTH};
+use crate::{
+	common::{
+		diagnostics::{uninferred_type, unsupported_in_codegen},
+		expr::{Capture, Expr},
+		func::{Linkage, Signature},
+		ident::{Id, Ident},
+		pattern::Pattern,
+		r#type::Type,
+		span::{Add, Span, Spanned},
+		stmt::Stmt,
+		typed_ident::TypedIdent,
+		visit::{walk_expr, walk_scope, Visit},
+	},
+	hoister::{HoistedExpr, HoistedFunc, HoistedScope},
+	infer::typed::Typed,
+	lexer::{NumberLiteral, Operator},
+	session::CompilerSession,
+};
+use itertools::Itertools;
+use std::{
+	collections::{HashMap, HashSet},
+	fmt::Write,
+	iter::once,
+};
+
+pub mod constant;
+pub mod ty;
+
+/// Everything we know about a function before generating its body.
+struct FuncInfo<'a> {
+	symbol: String,
+	func: &'a Spanned<HoistedFunc>,
+	return_ty: Ty,
+	args: Vec<Ty>,
+}
+
+struct Module<'a> {
+	session: &'a CompilerSession,
+	typed: &'a Typed<'a>,
+	/// Every function in the program, indexed by its resolved id.
+	funcs: HashMap<Id, FuncInfo<'a>>,
+	/// The ids of everything used as a value rather than called directly, so
+	/// that the functions among them get a thunk to be called through.
+	values: HashSet<Id>,
+	/// The contents of every distinct string literal in the program, each of
+	/// which becomes a global named after its index.
+	strings: Vec<String>,
+	/// The ids of every variable captured by a closure.
+	captured: HashSet<Id>,
+	/// The symbol of the function whose body functions are being collected
+	/// from, empty at the top level.
+	prefix: String,
+	/// The type every generic of every generic function is instantiated with,
+	/// or `None` if nothing determined it. Inference gives all calls of a
+	/// generic function the same types, so there's a single instance of each.
+	generics: HashMap<Id, Option<Type>>,
+}
+
+impl Module<'_> {
+	/// Lowers `ty`, reporting it if it has no LLVM representation. `span` is
+	/// the span of what has that type.
+	fn lower(&self, ty: &Type, span: Span) -> Option<Ty> {
+		let lowered = self.try_lower(ty);
+		if lowered.is_some() {
+			return lowered;
+		}
+		// structs and generics nothing determined are the only types without a
+		// representation
+		let what = match ty {
+			Type::Inferred => {
+				uninferred_type(self.session, span);
+				return None;
+			}
+			Type::User(x) if self.typed.mappings.get_struct(x.id()).is_some() => "a struct",
+			Type::Generic(..) => "a struct",
+			Type::User(x) if self.typed.enum_payloads(x.id()).is_some() => {
+				"an enum holding a struct"
+			}
+			Type::User(_) => "a generic value",
+			_ => "a value holding a struct",
+		};
+		unsupported_in_codegen(self.session, span, what);
+		None
+	}
+
+	/// Lowers `ty` with the generics in it replaced by what they were
+	/// instantiated with, without reporting anything.
+	fn try_lower(&self, ty: &Type) -> Option<Ty> {
+		Ty::from_type(&self.instantiate(ty, &mut Vec::new())?, self.typed)
+	}
+
+	/// Replaces every generic in `ty` with the type it was instantiated with,
+	/// which may be a generic of the calling function in turn. Returns `None` if
+	/// one of them was never instantiated with a concrete type. `seen` holds the
+	/// generics being replaced already, which only generic functions calling
+	/// each other without anything else calling them can lead back to.
+	fn instantiate(&self, ty: &Type, seen: &mut Vec<Id>) -> Option<Type> {
+		let mut instantiate = |x: &Spanned<Type>| {
+			let ty = self.instantiate(&x.value, seen)?;
+			Some(ty.add_span(x.span))
+		};
+		Some(match ty {
+			Type::User(x) => match self.generics.get(&x.id()) {
+				Some(Some(concrete)) if !seen.contains(&x.id()) => {
+					seen.push(x.id());
+					let concrete = self.instantiate(concrete, seen);
+					seen.pop();
+					return concrete;
+				}
+				Some(_) => return None,
+				None => ty.clone(),
+			},
+			Type::Generic(base, args) => Type::Generic(
+				base.clone(),
+				args.iter().map(&mut instantiate).collect::<Option<_>>()?,
+			),
+			Type::Ref(x, mutable) => Type::Ref(Box::new(instantiate(x)?), *mutable),
+			Type::Ptr(x, mutable) => Type::Ptr(Box::new(instantiate(x)?), *mutable),
+			Type::Optional(x) => Type::Optional(Box::new(instantiate(x)?)),
+			Type::Func {
+				args,
+				return_ty,
+				is_unsafe,
+			} => Type::Func {
+				args: args.iter().map(&mut instantiate).collect::<Option<_>>()?,
+				return_ty: Box::new(instantiate(return_ty)?),
+				is_unsafe: *is_unsafe,
+			},
+			Type::Tuple(xs) => Type::Tuple(xs.iter().map(&mut instantiate).collect::<Option<_>>()?),
+			Type::Array(x, len) => Type::Array(Box::new(instantiate(x)?), len.clone()),
+			Type::BuiltIn(_) | Type::Inferred => ty.clone(),
+		})
+	}
+
+	/// Learns what the generics in `declared`, a type in the signature of a
+	/// generic function, were instantiated with out of `concrete`, the type
+	/// inference found for it.
+	fn bind_generics(&mut self, declared: &Type, concrete: &Type) {
+		match (declared, concrete) {
+			// a generic nothing determined is still itself, and stays unbound
+			(Type::User(x), _)
+				if self.generics.contains_key(&x.id())
+					&& !matches!(concrete, Type::User(y) if y.id() == x.id()) =>
+			{
+				self.generics.insert(x.id(), Some(concrete.clone()));
+			}
+			(Type::Generic(_, xs), Type::Generic(_, ys))
+			| (Type::Tuple(xs), Type::Tuple(ys)) => {
+				for (x, y) in xs.iter().zip(ys) {
+					self.bind_generics(&x.value, &y.value);
+				}
+			}
+			(Type::Ref(x, _), Type::Ref(y, _))
+			| (Type::Ptr(x, _), Type::Ptr(y, _))
+			| (Type::Optional(x), Type::Optional(y))
+			| (Type::Array(x, _), Type::Array(y, _)) => self.bind_generics(&x.value, &y.value),
+			(
+				Type::Func {
+					args: xs,
+					return_ty: x,
+					..
+				},
+				Type::Func {
+					args: ys,
+					return_ty: y,
+					..
+				},
+			) => {
+				for (x, y) in xs.iter().zip(ys) {
+					self.bind_generics(&x.value, &y.value);
+				}
+				self.bind_generics(&x.value, &y.value);
+			}
+			_ => {}
+		}
+	}
+
+	/// Settles on the instance of every generic function once every generic is
+	/// bound, since a function may be instantiated with the generics of one
+	/// collected after it. The ones never instantiated with concrete types are
+	/// left out, as nothing could call them.
+	fn instantiate_funcs(&mut self) {
+		let generic_funcs: Vec<Id> = (self.funcs.iter())
+			.filter(|(_, info)| !info.func.value.signature.generics.value.is_empty())
+			.map(|(id, _)| *id)
+			.collect();
+		for id in generic_funcs {
+			let ty = self.instantiate(&self.typed.var_ty(id).value, &mut Vec::new());
+			match ty.and_then(|ty| self.signature(&ty)) {
+				Some((return_ty, args)) => {
+					let info = self.funcs.get_mut(&id).unwrap();
+					info.return_ty = return_ty;
+					info.args = args;
+				}
+				None => {
+					self.funcs.remove(&id);
+				}
+			}
+		}
+	}
+
+	fn lower_ty(&self, id: Id, span: Span) -> Ty {
+		self.lower(&self.typed.var_ty(id).value, span).unwrap_or(Ty::Void)
+	}
+
+	/// Lowers the return and argument types of the function type `ty`, if it
+	/// is one.
+	fn signature(&self, ty: &Type) -> Option<(Ty, Vec<Ty>)> {
+		let Type::Func { args, return_ty, .. } = ty else {
+			return None;
+		};
+		let lower = |x: &Spanned<Type>| self.try_lower(&x.value).unwrap_or(Ty::Void);
+		Some((lower(return_ty), args.iter().map(lower).collect()))
+	}
+
+	/// Lowers the type `ptr` points to, reporting it if it has no LLVM
+	/// representation. `span` is the span of the pointer.
+	fn pointee_ty(&self, ptr: &Type, span: Span) -> Option<Ty> {
+		match ptr {
+			Type::Ref(x, _) | Type::Ptr(x, _) => self.lower(&x.value, span),
+			_ => None,
+		}
+	}
+
+	/// Returns the tag of the variant `variant` of the enum `r#enum`, which is
+	/// its position in the declaration.
+	fn tag(&self, r#enum: &Ident, variant: &Ident) -> usize {
+		// inference made sure the variant exists
+		let (tag, _) = self.typed.mappings.get_enum(r#enum.id()).unwrap().variant(variant).unwrap();
+		tag
+	}
+
+	fn emit_func(&self, id: Id, out: &mut String) {
+		let info = &self.funcs[&id];
+		let signature = &info.func.value.signature;
+		out.push('\n');
+		if let Some(body) = &info.func.value.body {
+			let mut builder = FuncBuilder::new(self, info.symbol.clone(), info.return_ty.clone());
+			let params = builder.args(&signature.args.value, &info.args);
+			builder.define(&params, &body.value, out);
+		} else {
+			let args = info
+				.args
+				.iter()
+				.map(ToString::to_string)
+				.collect::<Vec<_>>()
+				.join(", ");
+			writeln!(out, "declare {} @{}({args})", info.return_ty, info.symbol).unwrap();
+		}
+		if self.values.contains(&id) {
+			Self::emit_thunk(info, out);
+		}
+	}
+
+	/// Emits the function a function used as a value is called through, which
+	/// takes the environment every function value is called with and ignores
+	/// it.
+	fn emit_thunk(info: &FuncInfo, out: &mut String) {
+		let args: Vec<_> = (info.args.iter().enumerate())
+			.map(|(i, ty)| format!("{ty} %arg{i}"))
+			.collect();
+		let call = format!("call {} @{}({})", info.return_ty, info.symbol, args.join(", "));
+		let body = if info.return_ty.is_void() {
+			format!("\t{call}\n\tret void\n")
+		} else {
+			format!("\t%result = {call}\n\tret {} %result\n", info.return_ty)
+		};
+		writeln!(
+			out,
+			"\ndefine {} @{}.value({}) {{\nentry:\n{body}}}",
+			info.return_ty,
+			info.symbol,
+			once("ptr %env".to_string()).chain(args).join(", ")
+		)
+		.unwrap();
+	}
+}
+
+impl<'a> Visit<'a, HoistedScope> for Module<'a> {
+	/// Registers every function declared in `scope` and in the scopes nested
+	/// within it. Nested functions get their parent's symbol as a prefix so
+	/// they don't clash with functions of the same name elsewhere.
+	fn scope(&mut self, scope: &'a HoistedScope) {
+		for (ident, func) in &scope.data.funcs {
+			// funcs from imported modules are named after their module, which also
+			// makes for a prefix
+			let path = func.value.id.value.segments();
+			let name = path.iter().join(".");
+			let symbol = match func.value.signature.linkage.value {
+				Linkage::External => path.last().unwrap().to_string(),
+				Linkage::Default if self.prefix.is_empty() => name,
+				Linkage::Default => format!("{}.{name}", self.prefix),
+			};
+			let signature = &func.value.signature;
+			if !signature.generics.value.is_empty() {
+				self.generics.extend(signature.generics.value.iter().map(|x| (x.value.id(), None)));
+				let Type::Func { args, return_ty, .. } = &self.typed.var_ty(ident.id()).value else {
+					unreachable!("function without a signature");
+				};
+				for (declared, concrete) in signature.args.value.iter().zip(args) {
+					self.bind_generics(&declared.value.ty.value, &concrete.value);
+				}
+				self.bind_generics(&signature.return_ty.value, &return_ty.value);
+			}
+			// the signatures of generic functions are lowered again once every
+			// generic is bound
+			let Some((return_ty, args)) = self.signature(&self.typed.var_ty(ident.id()).value)
+			else {
+				unreachable!("function without a signature");
+			};
+			if let Some(body) = &func.value.body {
+				let outer = std::mem::replace(&mut self.prefix, symbol.clone());
+				self.scope(&body.value);
+				self.prefix = outer;
+			}
+			self.funcs.insert(
+				ident.id(),
+				FuncInfo {
+					symbol,
+					func,
+					return_ty,
+					args,
+				},
+			);
+		}
+		walk_scope(self, scope);
+	}
+
+	fn expr(&mut self, expr: &'a Spanned<HoistedExpr>) {
+		match &expr.value {
+			Expr::StringLiteral(x) => {
+				if !self.strings.contains(x) {
+					self.strings.push(x.clone());
+				}
+			}
+			Expr::Identifier(Ident::Resolved(id)) => {
+				self.values.insert(*id);
+			}
+			Expr::Closure { captures, .. } => {
+				self.captured.extend(captures.iter().map(|x| x.value.id));
+				walk_expr(self, expr);
+			}
+			Expr::Call { callee, args, .. } => {
+				// calling a function by name doesn't use it as a value
+				if !matches!(callee.value, Expr::Identifier(_)) {
+					self.expr(callee);
+				}
+				for arg in args {
+					self.expr(arg);
+				}
+			}
+			_ => walk_expr(self, expr),
+		}
+	}
+}
+
+fn literal(literal: &NumberLiteral, ty: &Ty) -> Option<String> {
+	match ty {
+		Ty::Int { bits, .. } => Some(
+			constant::int(literal, *bits)
+				.expect("literals out of range of their type are reported during inference"),
+		),
+		Ty::Float { bits } => Some(constant::float(literal.as_f64(), *bits)),
+		Ty::Ptr | Ty::Optional(_) | Ty::Func | Ty::Tuple(_) | Ty::Array(..) | Ty::Void => None,
+	}
+}
+
+/// Where `break` and `continue` jump to from inside a loop.
+struct Loop {
+	continue_label: String,
+	break_label: String,
+	ty: Ty,
+	/// The values the loop was broken out of with, as incoming pairs of the phi
+	/// node that merges them.
+	incoming: Vec<String>,
+}
+
+struct FuncBuilder<'m, 'a> {
+	module: &'m Module<'a>,
+	/// The symbol of the function, which closures defined in it are named
+	/// after.
+	symbol: String,
+	/// Definitions of the closures defined in the function so far, which are
+	/// emitted right after it.
+	closures: String,
+	next_closure: usize,
+	/// Stack slots are all allocated in the entry block, so that they can be
+	/// promoted to registers by LLVM.
+	allocas: String,
+	body: String,
+	next_tmp: usize,
+	/// Stack slot of every variable and argument seen so far, along with the
+	/// type it holds.
+	locals: HashMap<Id, (String, Ty)>,
+	return_ty: Ty,
+	/// Label of the block instructions are currently emitted into.
+	block: String,
+	/// Whether the current block already ended with a terminator instruction.
+	terminated: bool,
+	/// The loops the current block is nested in, innermost last.
+	loops: Vec<Loop>,
+}
+
+impl<'m, 'a> FuncBuilder<'m, 'a> {
+	fn new(module: &'m Module<'a>, symbol: String, return_ty: Ty) -> Self {
+		Self {
+			module,
+			symbol,
+			closures: String::new(),
+			next_closure: 0,
+			allocas: String::new(),
+			body: String::new(),
+			next_tmp: 0,
+			locals: HashMap::new(),
+			return_ty,
+			block: "entry".into(),
+			terminated: false,
+			loops: Vec::new(),
+		}
+	}
+
+	/// Stores every argument into a stack slot of its own, returning the
+	/// parameters of the function.
+	fn args(&mut self, args: &[Spanned<TypedIdent>], tys: &[Ty]) -> Vec<String> {
+		let mut params = Vec::new();
+		for (i, (arg, ty)) in args.iter().zip(tys).enumerate() {
+			let register = format!("%arg{i}");
+			params.push(format!("{ty} {register}"));
+			if let Ident::Resolved(arg_id) = arg.ident() {
+				let slot = self.slot(*arg_id, ty.clone());
+				self.emit(&format!("store {ty} {register}, ptr {slot}"));
+			}
+		}
+		params
+	}
+
+	/// Generates the code for `body`, appending the definition of the function
+	/// to `out` followed by the closures defined in it.
+	fn define(mut self, params: &[String], body: &HoistedScope, out: &mut String) {
+		let return_ty = self.return_ty.clone();
+		let result = self.scope(body, &return_ty);
+		if !self.terminated {
+			match (&return_ty, result) {
+				(Ty::Void, _) => self.emit("ret void"),
+				(ty, Some(result)) => self.emit(&format!("ret {ty} {result}")),
+				// inference has made sure that every path returns a value
+				(_, None) => self.emit("unreachable"),
+			}
+		}
+		writeln!(
+			out,
+			"define {return_ty} @{}({}) {{\nentry:\n{}{}}}",
+			self.symbol,
+			params.join(", "),
+			self.allocas,
+			self.body
+		)
+		.unwrap();
+		out.push_str(&self.closures);
+	}
+
+	fn tmp(&mut self) -> String {
+		self.next_tmp += 1;
+		format!("%t{}", self.next_tmp)
+	}
+
+	/// Creates a new label for a block, without the leading `%`.
+	fn label(&mut self) -> String {
+		self.tmp()[1..].to_string()
+	}
+
+	/// Starts emitting instructions into the block `label`. The current block
+	/// must have been terminated already.
+	fn start_block(&mut self, label: String) {
+		writeln!(self.body, "{label}:").unwrap();
+		self.block = label;
+		self.terminated = false;
+	}
+
+	fn emit(&mut self, instruction: &str) {
+		if self.terminated {
+			// anything after a terminator is dead code, but it still has to live in a
+			// block of its own
+			let label = self.label();
+			self.start_block(label);
+		}
+		writeln!(self.body, "\t{instruction}").unwrap();
+	}
+
+	fn terminate(&mut self, instruction: &str) {
+		self.emit(instruction);
+		self.terminated = true;
+	}
+
+	/// Allocates a slot for the variable `id`. Variables captured by a closure
+	/// live on the heap instead of the stack, since the closure may be called
+	/// after this function returned.
+	fn slot(&mut self, id: Id, ty: Ty) -> String {
+		let slot = format!("%v{id}");
+		if !ty.is_void() && self.module.captured.contains(&id) {
+			let size = format!("ptrtoint (ptr getelementptr ({ty}, ptr null, i32 1) to i64)");
+			writeln!(self.allocas, "\t{slot} = call ptr @malloc(i64 {size})").unwrap();
+		} else if !ty.is_void() {
+			writeln!(self.allocas, "\t{slot} = alloca {ty}").unwrap();
+		}
+		self.locals.insert(id, (slot.clone(), ty));
+		slot
+	}
+
+	/// Generates the code for every stmt in `scope`, returning the yielded
+	/// value, if any.
+	fn scope(&mut self, scope: &HoistedScope, ty: &Ty) -> Option<String> {
+		for stmt in &scope.stmts {
+			match &stmt.value {
+				Stmt::Create { ty_id, value, .. } => {
+					let id = ty_id.ident().id();
+					let var_ty = self.module.lower_ty(id, ty_id.span);
+					let slot = self.slot(id, var_ty.clone());
+					if let Some(value) = value {
+						if let Some(value) = self.expr(value, &var_ty) {
+							self.emit(&format!("store {var_ty} {value}, ptr {slot}"));
+						}
+					}
+				}
+				Stmt::Set { id, value } => {
+					let (slot, var_ty) = self.locals[&id.value.id()].clone();
+					if let Some(value) = self.expr(value, &var_ty) {
+						self.emit(&format!("store {var_ty} {value}, ptr {slot}"));
+					}
+				}
+				Stmt::Destructure { ids, value, .. } => self.destructure(&ids.value, value),
+				Stmt::Store { ptr, value } => {
+					let ptr_ty = &self.module.typed.expr_ty(ptr).value;
+					let Some(pointee) = self.module.pointee_ty(ptr_ty, ptr.span) else {
+						continue;
+					};
+					let Some(ptr) = self.expr(ptr, &Ty::Ptr) else {
+						continue;
+					};
+					if let Some(value) = self.expr(value, &pointee) {
+						self.emit(&format!("store {pointee} {value}, ptr {ptr}"));
+					}
+				}
+				// imports can only be at the top level, where there's no code to run
+				Stmt::Func { .. }
+				| Stmt::Struct { .. }
+				| Stmt::Enum { .. }
+				| Stmt::Const { .. }
+				| Stmt::Import { .. } => unreachable!(),
+				Stmt::Return {
+					value,
+					is_yield: true,
+				} => return self.expr(value, ty),
+				Stmt::Return {
+					value,
+					is_yield: false,
+				} => {
+					let return_ty = self.return_ty.clone();
+					match self.expr(value, &return_ty) {
+						Some(value) => self.terminate(&format!("ret {return_ty} {value}")),
+						None => self.terminate("ret void"),
+					}
+					return None;
+				}
+				Stmt::Break { value } => {
+					// the checker makes sure we can only break from inside a loop
+					let ty = self.loops.last().expect("break outside of a loop").ty.clone();
+					let value = value.as_ref().and_then(|x| self.expr(x, &ty));
+					// the value may have returned, in which case we never get to break
+					if !self.terminated {
+						let block = self.block.clone();
+						let r#loop = self.loops.last_mut().unwrap();
+						if let Some(value) = value {
+							r#loop.incoming.push(format!("[ {value}, %{block} ]"));
+						}
+						let branch = format!("br label %{}", r#loop.break_label);
+						self.terminate(&branch);
+					}
+					return None;
+				}
+				Stmt::Continue => {
+					let r#loop = self.loops.last().expect("continue outside of a loop");
+					let branch = format!("br label %{}", r#loop.continue_label);
+					self.terminate(&branch);
+					return None;
+				}
+			}
+		}
+		None
+	}
+
+	/// Generates the code for destructuring `value` into a new stack slot for
+	/// every one of `ids`.
+	fn destructure(&mut self, ids: &[Spanned<Ident>], value: &Spanned<HoistedExpr>) {
+		let Some(tuple_ty @ Ty::Tuple(_)) = self.expr_ty(value) else {
+			return;
+		};
+		let tuple = self.expr(value, &tuple_ty);
+		for (i, ident) in ids.iter().enumerate() {
+			let Ident::Resolved(id) = ident.value else {
+				continue;
+			};
+			let var_ty = self.module.lower_ty(id, ident.span);
+			let slot = self.slot(id, var_ty.clone());
+			if let Some(component) = tuple.as_ref().and_then(|x| self.component(x, &tuple_ty, i)) {
+				self.emit(&format!("store {var_ty} {component}, ptr {slot}"));
+			}
+		}
+	}
+
+	/// Gets the component `index` out of `tuple`, of type `tuple_ty`.
+	fn component(&mut self, tuple: &str, tuple_ty: &Ty, index: usize) -> Option<String> {
+		let Ty::Tuple(components) = tuple_ty else {
+			unreachable!("{tuple_ty} is not a tuple");
+		};
+		if components[index].is_void() {
+			return None;
+		}
+		let tmp = self.tmp();
+		self.emit(&format!("{tmp} = extractvalue {tuple_ty} {tuple}, {index}"));
+		Some(tmp)
+	}
+
+	/// Generates the code for a tuple or array literal of type `ty`, which is
+	/// built one component at a time.
+	fn aggregate(&mut self, values: &[Spanned<HoistedExpr>], ty: &Ty) -> String {
+		let mut aggregate = "undef".to_string();
+		for (i, value) in values.iter().enumerate() {
+			let component = match ty {
+				Ty::Tuple(components) => &components[i],
+				Ty::Array(element, _) => element,
+				_ => unreachable!("{ty} is not a tuple or an array"),
+			};
+			let Some(value) = self.expr(value, component) else {
+				continue;
+			};
+			let tmp = self.tmp();
+			self.emit(&format!(
+				"{tmp} = insertvalue {ty} {aggregate}, {component} {value}, {i}"
+			));
+			aggregate = tmp;
+		}
+		aggregate
+	}
+
+	/// Generates the code for `value[index]`, which reads the element through
+	/// a pointer since the index isn't known at compile time.
+	fn index(
+		&mut self,
+		value: &Spanned<HoistedExpr>,
+		index: &Spanned<HoistedExpr>,
+	) -> Option<String> {
+		let (element, element_ty) = self.element_ptr(value, index)?;
+		if element_ty.is_void() {
+			return None;
+		}
+		let tmp = self.tmp();
+		self.emit(&format!("{tmp} = load {element_ty}, ptr {element}"));
+		Some(tmp)
+	}
+
+	/// Generates the code for the address of `value[index]`, along with the
+	/// type of the element. Arrays in variables are indexed in place, anything
+	/// else is first stored into a temporary slot.
+	fn element_ptr(
+		&mut self,
+		value: &Spanned<HoistedExpr>,
+		index: &Spanned<HoistedExpr>,
+	) -> Option<(String, Ty)> {
+		let Some(Ty::Array(element_ty, len)) = self.expr_ty(value) else {
+			return None;
+		};
+		let array_ty = Ty::Array(element_ty.clone(), len);
+		let array = match &value.value {
+			Expr::Identifier(x) if self.locals.contains_key(&x.id()) => self.locals[&x.id()].0.clone(),
+			_ => self.temporary(value, &array_ty),
+		};
+		let index_ty = self.expr_ty(index)?;
+		let index = self.expr(index, &index_ty)?;
+		// `getelementptr` sign extends narrower indices, which is only right for
+		// signed ones
+		let (index, index_ty) = match index_ty {
+			Ty::Int {
+				bits: bits @ ..POINTER_WIDTH,
+				signed: false,
+			} => {
+				let extended = self.tmp();
+				self.emit(&format!("{extended} = zext i{bits} {index} to {}", Ty::USIZE));
+				(extended, Ty::USIZE)
+			}
+			ty => (index, ty),
+		};
+		let element = self.tmp();
+		self.emit(&format!(
+			"{element} = getelementptr {array_ty}, ptr {array}, i64 0, {index_ty} {index}"
+		));
+		Some((element, *element_ty))
+	}
+
+	/// Stores `value`, of type `ty`, into a new stack slot, returning its
+	/// address.
+	fn temporary(&mut self, value: &Spanned<HoistedExpr>, ty: &Ty) -> String {
+		let slot = self.tmp();
+		// a void value still needs an address, any one will do
+		let alloca_ty = if ty.is_void() { &Ty::BOOL } else { ty };
+		writeln!(self.allocas, "\t{slot} = alloca {alloca_ty}").unwrap();
+		if let Some(value) = self.expr(value, ty) {
+			self.emit(&format!("store {ty} {value}, ptr {slot}"));
+		}
+		slot
+	}
+
+	/// Generates the code for an if expression of type `ty`, merging the values
+	/// of both branches with a phi node.
+	fn if_expr(
+		&mut self,
+		cond: &Spanned<HoistedExpr>,
+		then: &Spanned<HoistedExpr>,
+		otherwise: Option<&Spanned<HoistedExpr>>,
+		ty: &Ty,
+	) -> Option<String> {
+		let cond = self.expr(cond, &Ty::BOOL)?;
+		let then_label = self.label();
+		let else_label = self.label();
+		let end_label = self.label();
+		self.terminate(&format!(
+			"br i1 {cond}, label %{then_label}, label %{else_label}"
+		));
+		let mut incoming = Vec::new();
+		for (label, branch) in [(then_label, Some(then)), (else_label, otherwise)] {
+			self.start_block(label);
+			let value = branch.and_then(|x| self.expr(x, ty));
+			// branches that returned don't flow into the end block
+			if !self.terminated {
+				if let Some(value) = value {
+					incoming.push(format!("[ {value}, %{} ]", self.block));
+				}
+				self.terminate(&format!("br label %{end_label}"));
+			}
+		}
+		self.start_block(end_label);
+		if ty.is_void() || incoming.is_empty() {
+			return None;
+		}
+		let tmp = self.tmp();
+		self.emit(&format!("{tmp} = phi {ty} {}", incoming.join(", ")));
+		Some(tmp)
+	}
+
+	/// Generates the code for the body of `r#loop`, jumping to its continue
+	/// label once it's done. Returns the loop, along with the values it was
+	/// broken out of with.
+	fn loop_body(&mut self, body: &Spanned<HoistedExpr>, r#loop: Loop) -> Loop {
+		self.loops.push(r#loop);
+		self.expr(body, &Ty::Void);
+		let r#loop = self.loops.pop().unwrap();
+		if !self.terminated {
+			self.terminate(&format!("br label %{}", r#loop.continue_label));
+		}
+		r#loop
+	}
+
+	/// Generates the code for the else branch of `r#loop`, starting at
+	/// `else_label`, and merges its value with the ones the loop was broken out
+	/// of with.
+	fn loop_end(
+		&mut self,
+		r#loop: Loop,
+		else_label: String,
+		otherwise: Option<&Spanned<HoistedExpr>>,
+	) -> Option<String> {
+		let Loop {
+			break_label,
+			ty,
+			mut incoming,
+			..
+		} = r#loop;
+		self.start_block(else_label);
+		let value = otherwise.and_then(|x| self.expr(x, &ty));
+		if !self.terminated {
+			if let Some(value) = value {
+				incoming.push(format!("[ {value}, %{} ]", self.block));
+			}
+			self.terminate(&format!("br label %{break_label}"));
+		}
+		self.start_block(break_label);
+		if ty.is_void() || incoming.is_empty() {
+			return None;
+		}
+		let tmp = self.tmp();
+		self.emit(&format!("{tmp} = phi {ty} {}", incoming.join(", ")));
+		Some(tmp)
+	}
+
+	/// Generates the code for a while loop of type `ty`, which checks its
+	/// condition before every iteration.
+	fn while_expr(
+		&mut self,
+		cond: &Spanned<HoistedExpr>,
+		body: &Spanned<HoistedExpr>,
+		otherwise: Option<&Spanned<HoistedExpr>>,
+		ty: &Ty,
+	) -> Option<String> {
+		let cond_label = self.label();
+		let body_label = self.label();
+		let else_label = self.label();
+		let end_label = self.label();
+		self.terminate(&format!("br label %{cond_label}"));
+		self.start_block(cond_label.clone());
+		let cond = self.expr(cond, &Ty::BOOL)?;
+		self.terminate(&format!(
+			"br i1 {cond}, label %{body_label}, label %{else_label}"
+		));
+		self.start_block(body_label);
+		let r#loop = self.loop_body(
+			body,
+			Loop {
+				continue_label: cond_label,
+				break_label: end_label,
+				ty: ty.clone(),
+				incoming: Vec::new(),
+			},
+		);
+		self.loop_end(r#loop, else_label, otherwise)
+	}
+
+	/// Generates the code for a for loop of type `ty`. The var lives in a stack
+	/// slot like any other, and is incremented after every iteration.
+	fn for_expr(
+		&mut self,
+		var: &Spanned<Ident>,
+		(start, end): (&Spanned<HoistedExpr>, &Spanned<HoistedExpr>),
+		body: &Spanned<HoistedExpr>,
+		otherwise: Option<&Spanned<HoistedExpr>>,
+		ty: &Ty,
+	) -> Option<String> {
+		let bound_ty = self.module.try_lower(&self.module.typed.expr_ty(start).value);
+		let Some(bound_ty @ Ty::Int { signed, .. }) = bound_ty else {
+			unsupported_in_codegen(self.module.session, var.span, "a range over non-integers");
+			return None;
+		};
+		let start = self.expr(start, &bound_ty)?;
+		let end = self.expr(end, &bound_ty)?;
+		let slot = if let Ident::Resolved(id) = var.value {
+			self.slot(id, bound_ty.clone())
+		} else {
+			// a discarded var still has to be counted
+			let slot = self.tmp();
+			writeln!(self.allocas, "\t{slot} = alloca {bound_ty}").unwrap();
+			slot
+		};
+		self.emit(&format!("store {bound_ty} {start}, ptr {slot}"));
+		let cond_label = self.label();
+		let body_label = self.label();
+		let step_label = self.label();
+		let else_label = self.label();
+		let end_label = self.label();
+		self.terminate(&format!("br label %{cond_label}"));
+		self.start_block(cond_label.clone());
+		let current = self.tmp();
+		self.emit(&format!("{current} = load {bound_ty}, ptr {slot}"));
+		let cond = self.tmp();
+		let cmp = if signed { "slt" } else { "ult" };
+		self.emit(&format!("{cond} = icmp {cmp} {bound_ty} {current}, {end}"));
+		self.terminate(&format!(
+			"br i1 {cond}, label %{body_label}, label %{else_label}"
+		));
+		self.start_block(body_label);
+		let r#loop = self.loop_body(
+			body,
+			Loop {
+				continue_label: step_label.clone(),
+				break_label: end_label,
+				ty: ty.clone(),
+				incoming: Vec::new(),
+			},
+		);
+		self.start_block(step_label);
+		let current = self.tmp();
+		self.emit(&format!("{current} = load {bound_ty}, ptr {slot}"));
+		let next = self.tmp();
+		self.emit(&format!("{next} = add {bound_ty} {current}, 1"));
+		self.emit(&format!("store {bound_ty} {next}, ptr {slot}"));
+		self.terminate(&format!("br label %{cond_label}"));
+		self.loop_end(r#loop, else_label, otherwise)
+	}
+
+	/// Generates the code for `&&` or `||`, which only evaluate their rhs if the
+	/// lhs didn't already determine the result.
+	fn logical(
+		&mut self,
+		lhs: &Spanned<HoistedExpr>,
+		op: Operator,
+		rhs: &Spanned<HoistedExpr>,
+	) -> Option<String> {
+		let lhs = self.expr(lhs, &Ty::BOOL)?;
+		let lhs_block = self.block.clone();
+		let rhs_label = self.label();
+		let end_label = self.label();
+		// the value the whole expression takes if the rhs is skipped
+		let (short_circuit, branch) = if op == Operator::And {
+			("0", format!("br i1 {lhs}, label %{rhs_label}, label %{end_label}"))
+		} else {
+			("1", format!("br i1 {lhs}, label %{end_label}, label %{rhs_label}"))
+		};
+		self.terminate(&branch);
+		self.start_block(rhs_label);
+		let mut incoming = vec![format!("[ {short_circuit}, %{lhs_block} ]")];
+		let rhs = self.expr(rhs, &Ty::BOOL);
+		// the rhs may have returned, in which case it doesn't flow into the end block
+		if !self.terminated {
+			if let Some(rhs) = rhs {
+				incoming.push(format!("[ {rhs}, %{} ]", self.block));
+			}
+			self.terminate(&format!("br label %{end_label}"));
+		}
+		self.start_block(end_label);
+		let tmp = self.tmp();
+		self.emit(&format!("{tmp} = phi i1 {}", incoming.join(", ")));
+		Some(tmp)
+	}
+
+	/// Generates the code for the comparison `lhs op rhs`, which yields a bool.
+	fn comparison(
+		&mut self,
+		lhs: &Spanned<HoistedExpr>,
+		op: Spanned<Operator>,
+		rhs: &Spanned<HoistedExpr>,
+	) -> Option<String> {
+		let operand_ty = self.expr_ty(lhs)?;
+		let lhs = self.expr(lhs, &operand_ty)?;
+		let rhs = self.expr(rhs, &operand_ty)?;
+		let instruction = match (op.value, &operand_ty) {
+			(Operator::Eq, Ty::Int { .. } | Ty::Ptr) => "icmp eq",
+			(Operator::Ne, Ty::Int { .. } | Ty::Ptr) => "icmp ne",
+			(Operator::Lt, Ty::Int { signed: true, .. }) => "icmp slt",
+			(Operator::Gt, Ty::Int { signed: true, .. }) => "icmp sgt",
+			(Operator::Le, Ty::Int { signed: true, .. }) => "icmp sle",
+			(Operator::Ge, Ty::Int { signed: true, .. }) => "icmp sge",
+			(Operator::Lt, Ty::Int { signed: false, .. }) => "icmp ult",
+			(Operator::Gt, Ty::Int { signed: false, .. }) => "icmp ugt",
+			(Operator::Le, Ty::Int { signed: false, .. }) => "icmp ule",
+			(Operator::Ge, Ty::Int { signed: false, .. }) => "icmp uge",
+			(Operator::Eq, Ty::Float { .. }) => "fcmp oeq",
+			// NaN is different from everything, including itself
+			(Operator::Ne, Ty::Float { .. }) => "fcmp une",
+			(Operator::Lt, Ty::Float { .. }) => "fcmp olt",
+			(Operator::Gt, Ty::Float { .. }) => "fcmp ogt",
+			(Operator::Le, Ty::Float { .. }) => "fcmp ole",
+			(Operator::Ge, Ty::Float { .. }) => "fcmp oge",
+			_ => {
+				unsupported_in_codegen(
+					self.module.session,
+					op.span,
+					&format!("the '{}' operator", op.value),
+				);
+				return None;
+			}
+		};
+		let tmp = self.tmp();
+		self.emit(&format!("{tmp} = {instruction} {operand_ty} {lhs}, {rhs}"));
+		Some(tmp)
+	}
+
+	/// Generates the code for `&value`. Variables are referenced through their
+	/// own stack slot, `&*x` is just `x`, and anything else is first stored into
+	/// a temporary slot.
+	fn address_of(&mut self, value: &Spanned<HoistedExpr>) -> Option<String> {
+		match &value.value {
+			// consts have no slot of their own, they're just values
+			Expr::Identifier(x) if self.module.typed.mappings.get_const(x.id()).is_none() => {
+				let Some((slot, _)) = self.locals.get(&x.id()) else {
+					unsupported_in_codegen(
+						self.module.session,
+						value.span,
+						"referencing a function",
+					);
+					return None;
+				};
+				Some(slot.clone())
+			}
+			Expr::Deref(ptr) => self.expr(ptr, &Ty::Ptr),
+			// the element has to be changed in place
+			Expr::Index {
+				value: array,
+				index,
+			} => self.element_ptr(array, index).map(|(x, _)| x),
+			_ => {
+				let value_ty = self.expr_ty(value)?;
+				Some(self.temporary(value, &value_ty))
+			}
+		}
+	}
+
+	/// Generates the code for `value?`, returning `none` from the function if
+	/// `value` holds nothing.
+	fn try_expr(&mut self, value: &Spanned<HoistedExpr>) -> Option<String> {
+		let Some(Ty::Optional(inner)) = self.expr_ty(value) else {
+			return None;
+		};
+		let optional_ty = Ty::Optional(inner.clone());
+		let optional = self.expr(value, &optional_ty)?;
+		let flag = self.tmp();
+		self.emit(&format!("{flag} = extractvalue {optional_ty} {optional}, 0"));
+		let some_label = self.label();
+		let none_label = self.label();
+		self.terminate(&format!(
+			"br i1 {flag}, label %{some_label}, label %{none_label}"
+		));
+		self.start_block(none_label);
+		// inference made sure the function returns an optional
+		let return_ty = self.return_ty.clone();
+		self.terminate(&format!("ret {return_ty} zeroinitializer"));
+		self.start_block(some_label);
+		self.unwrap(&optional, &optional_ty, &inner)
+	}
+
+	/// Generates the code for casting `value` to the numeric type `ty`.
+	fn cast(&mut self, value: &Spanned<HoistedExpr>, ty: &Ty) -> Option<String> {
+		let from = self.expr_ty(value)?;
+		let value = self.expr(value, &from)?;
+		let instruction = match (&from, ty) {
+			(Ty::Int { bits: a, signed }, Ty::Int { bits: b, .. }) if a < b => {
+				if *signed {
+					"sext"
+				} else {
+					"zext"
+				}
+			}
+			(Ty::Int { bits: a, .. }, Ty::Int { bits: b, .. }) if a > b => "trunc",
+			(Ty::Float { bits: a }, Ty::Float { bits: b }) if a < b => "fpext",
+			(Ty::Float { bits: a }, Ty::Float { bits: b }) if a > b => "fptrunc",
+			(Ty::Int { signed: true, .. }, Ty::Float { .. }) => "sitofp",
+			(Ty::Int { .. }, Ty::Float { .. }) => "uitofp",
+			(Ty::Float { .. }, Ty::Int { signed: true, .. }) => "fptosi",
+			(Ty::Float { .. }, Ty::Int { .. }) => "fptoui",
+			// the bits stay the same, only the signedness (if anything) changes
+			_ => return Some(value),
+		};
+		let tmp = self.tmp();
+		self.emit(&format!("{tmp} = {instruction} {from} {value} to {ty}"));
+		Some(tmp)
+	}
+
+	/// Generates the code for `lhs ?? rhs`, which only evaluates `rhs` if `lhs`
+	/// holds nothing.
+	fn unwrap_or(
+		&mut self,
+		lhs: &Spanned<HoistedExpr>,
+		rhs: &Spanned<HoistedExpr>,
+		ty: &Ty,
+	) -> Option<String> {
+		let Some(optional_ty @ Ty::Optional(_)) = self.expr_ty(lhs) else {
+			return None;
+		};
+		let optional = self.expr(lhs, &optional_ty)?;
+		let flag = self.tmp();
+		self.emit(&format!("{flag} = extractvalue {optional_ty} {optional}, 0"));
+		let some_label = self.label();
+		let none_label = self.label();
+		let end_label = self.label();
+		self.terminate(&format!(
+			"br i1 {flag}, label %{some_label}, label %{none_label}"
+		));
+		let mut incoming = Vec::new();
+		self.start_block(some_label);
+		if let Some(value) = self.unwrap(&optional, &optional_ty, ty) {
+			incoming.push(format!("[ {value}, %{} ]", self.block));
+		}
+		self.terminate(&format!("br label %{end_label}"));
+		self.start_block(none_label);
+		let rhs = self.expr(rhs, ty);
+		// the rhs may have returned, in which case it doesn't flow into the end block
+		if !self.terminated {
+			if let Some(rhs) = rhs {
+				incoming.push(format!("[ {rhs}, %{} ]", self.block));
+			}
+			self.terminate(&format!("br label %{end_label}"));
+		}
+		self.start_block(end_label);
+		if ty.is_void() {
+			return None;
+		}
+		let tmp = self.tmp();
+		self.emit(&format!("{tmp} = phi {ty} {}", incoming.join(", ")));
+		Some(tmp)
+	}
+
+	/// Gets the value out of `optional`, of type `optional_ty`, which must hold
+	/// a value of type `inner`.
+	fn unwrap(&mut self, optional: &str, optional_ty: &Ty, inner: &Ty) -> Option<String> {
+		if inner.is_void() {
+			return None;
+		}
+		let tmp = self.tmp();
+		self.emit(&format!("{tmp} = extractvalue {optional_ty} {optional}, 1"));
+		Some(tmp)
+	}
+
+	/// Generates the code for building the variant `variant` of the enum
+	/// `r#enum` out of `args`. Every part of the value other than the tag and
+	/// the payload of the variant is left zeroed. `span` is the span of the
+	/// variant expr.
+	fn variant(
+		&mut self,
+		(r#enum, variant): (&Ident, &Ident),
+		args: &[Spanned<HoistedExpr>],
+		span: Span,
+	) -> Option<String> {
+		let Some(Ty::Tuple(parts)) = self.module.lower(&Type::User(r#enum.clone()), span) else {
+			return None;
+		};
+		let enum_ty = Ty::Tuple(parts.clone());
+		let tag = self.module.tag(r#enum, variant);
+		let Ty::Tuple(payload) = &parts[tag + 1] else {
+			unreachable!("the payload of a variant is always a tuple");
+		};
+		let mut value = self.tmp();
+		self.emit(&format!(
+			"{value} = insertvalue {enum_ty} zeroinitializer, {} {tag}, 0",
+			Ty::TAG
+		));
+		for (i, (arg, arg_ty)) in args.iter().zip(payload).enumerate() {
+			let Some(arg) = self.expr(arg, arg_ty) else {
+				continue;
+			};
+			let tmp = self.tmp();
+			self.emit(&format!(
+				"{tmp} = insertvalue {enum_ty} {value}, {arg_ty} {arg}, {}, {i}",
+				tag + 1
+			));
+			value = tmp;
+		}
+		Some(value)
+	}
+
+	/// Generates the code for a match expression of type `ty`, testing the value
+	/// against the pattern of every arm in order and merging the values of the
+	/// arms with a phi node.
+	fn match_expr(
+		&mut self,
+		value: &Spanned<HoistedExpr>,
+		arms: &[(Spanned<Pattern>, Spanned<HoistedExpr>)],
+		ty: &Ty,
+	) -> Option<String> {
+		let value_ty = self.expr_ty(value)?;
+		// void values can only be matched by bindings, which never look at them
+		let value = self
+			.expr(value, &value_ty)
+			.unwrap_or_else(|| "undef".into());
+		let end_label = self.label();
+		let mut incoming = Vec::new();
+		for (pattern, arm) in arms {
+			let next_label = self.label();
+			// patterns that match anything need no branch, which leaves the following
+			// arms unreachable
+			if let Some(cond) = self.test(pattern, &value, &value_ty) {
+				let arm_label = self.label();
+				self.terminate(&format!(
+					"br i1 {cond}, label %{arm_label}, label %{next_label}"
+				));
+				self.start_block(arm_label);
+			}
+			self.bind(pattern, &value, &value_ty);
+			let result = self.expr(arm, ty);
+			// arms that returned don't flow into the end block
+			if !self.terminated {
+				if let Some(result) = result {
+					incoming.push(format!("[ {result}, %{} ]", self.block));
+				}
+				self.terminate(&format!("br label %{end_label}"));
+			}
+			self.start_block(next_label);
+		}
+		// inference made sure every value is matched by some arm
+		self.terminate("unreachable");
+		self.start_block(end_label);
+		if ty.is_void() || incoming.is_empty() {
+			return None;
+		}
+		let tmp = self.tmp();
+		self.emit(&format!("{tmp} = phi {ty} {}", incoming.join(", ")));
+		Some(tmp)
+	}
+
+	/// Generates the code for testing whether `value`, of type `ty`, matches
+	/// `pattern`. Returns the bool holding the result, or `None` if the pattern
+	/// matches anything.
+	fn test(&mut self, pattern: &Spanned<Pattern>, value: &str, ty: &Ty) -> Option<String> {
+		let cond = self.tmp();
+		match &pattern.value {
+			Pattern::Binding(_) => return None,
+			Pattern::NumberLiteral(x) => {
+				let literal = literal(x, ty)?;
+				let instruction = if let Ty::Float { .. } = ty { "fcmp oeq" } else { "icmp eq" };
+				self.emit(&format!("{cond} = {instruction} {ty} {value}, {literal}"));
+			}
+			Pattern::CharLiteral(x) => {
+				self.emit(&format!("{cond} = icmp eq {ty} {value}, {}", u32::from(*x)));
+			}
+			Pattern::Variant {
+				r#enum,
+				variant,
+				fields,
+			} => {
+				let tag = self.module.tag(&r#enum.value, &variant.value);
+				let actual = self.tmp();
+				self.emit(&format!("{actual} = extractvalue {ty} {value}, 0"));
+				self.emit(&format!("{cond} = icmp eq {} {actual}, {tag}", Ty::TAG));
+				let mut cond = cond;
+				// the fields are tested whatever the tag is, there's no harm in looking at
+				// the zeroes of another variant
+				for (i, field) in fields.iter().enumerate() {
+					let Some((part, part_ty)) = self.payload(value, ty, tag, i) else {
+						continue;
+					};
+					let Some(field_cond) = self.test(field, &part, &part_ty) else {
+						continue;
+					};
+					let tmp = self.tmp();
+					self.emit(&format!("{tmp} = and i1 {cond}, {field_cond}"));
+					cond = tmp;
+				}
+				return Some(cond);
+			}
+		}
+		Some(cond)
+	}
+
+	/// Stores the part of `value`, of type `ty`, that every var bound by
+	/// `pattern` stands for into a new stack slot.
+	fn bind(&mut self, pattern: &Spanned<Pattern>, value: &str, ty: &Ty) {
+		match &pattern.value {
+			Pattern::Binding(Ident::Resolved(id)) => {
+				let slot = self.slot(*id, ty.clone());
+				if !ty.is_void() {
+					self.emit(&format!("store {ty} {value}, ptr {slot}"));
+				}
+			}
+			Pattern::Variant {
+				r#enum,
+				variant,
+				fields,
+			} => {
+				let tag = self.module.tag(&r#enum.value, &variant.value);
+				for (i, field) in fields.iter().enumerate() {
+					if field.bindings().is_empty() {
+						continue;
+					}
+					match self.payload(value, ty, tag, i) {
+						Some((part, part_ty)) => self.bind(field, &part, &part_ty),
+						None => self.bind(field, "undef", &Ty::Void),
+					}
+				}
+			}
+			Pattern::Binding(_) | Pattern::NumberLiteral(_) | Pattern::CharLiteral(_) => {}
+		}
+	}
+
+	/// Gets the value `index` of the payload of the variant `tag` out of
+	/// `value`, of the enum type `ty`, along with its type. Returns `None` if
+	/// the value is void.
+	fn payload(&mut self, value: &str, ty: &Ty, tag: usize, index: usize) -> Option<(String, Ty)> {
+		let Ty::Tuple(parts) = ty else {
+			unreachable!("{ty} is not an enum");
+		};
+		let Ty::Tuple(payload) = &parts[tag + 1] else {
+			unreachable!("the payload of a variant is always a tuple");
+		};
+		let part_ty = payload[index].clone();
+		if part_ty.is_void() {
+			return None;
+		}
+		let tmp = self.tmp();
+		self.emit(&format!("{tmp} = extractvalue {ty} {value}, {}, {index}", tag + 1));
+		Some((tmp, part_ty))
+	}
+
+	/// Lowers the type of `expr`, reporting it if it has no LLVM representation.
+	fn expr_ty(&self, expr: &Spanned<HoistedExpr>) -> Option<Ty> {
+		self.module.lower(&self.module.typed.expr_ty(expr).value, expr.span)
+	}
+
+	/// Generates the code for `expr`, whose type is `ty`. Returns the register
+	/// or constant holding its value, or `None` if it has no value. Values that
+	/// inference coerced into optionals get wrapped here.
+	fn expr(&mut self, expr: &Spanned<HoistedExpr>, ty: &Ty) -> Option<String> {
+		let Ty::Optional(inner) = ty else {
+			return self.expr_value(expr, ty);
+		};
+		if !self.module.typed.mappings.is_coerced(expr.span) {
+			return self.expr_value(expr, ty);
+		}
+		let value = self.expr_value(expr, inner);
+		if inner.is_void() {
+			return Some("{ i1 1 }".into());
+		}
+		let value = value?;
+		let tmp = self.tmp();
+		self.emit(&format!(
+			"{tmp} = insertvalue {ty} {{ i1 1, {inner} undef }}, {inner} {value}, 1"
+		));
+		Some(tmp)
+	}
+
+	/// Generates the code for `expr` like `FuncBuilder::expr`, without
+	/// coercing it.
+	// exprs needing more than a few instructions already have methods of their
+	// own, what's left is the dispatch between them
+	#[allow(clippy::too_many_lines)]
+	fn expr_value(&mut self, expr: &Spanned<HoistedExpr>, ty: &Ty) -> Option<String> {
+		match &expr.value {
+			Expr::NumberLiteral(x) => literal(x, ty),
+			Expr::CharLiteral(x) => Some(u32::from(*x).to_string()),
+			Expr::StringLiteral(x) => {
+				let index = self.module.strings.iter().position(|y| y == x).unwrap();
+				Some(format!("{{ ptr @str.{index}, {} {} }}", Ty::USIZE, x.len()))
+			}
+			// nothing is all zeroes, starting with the flag
+			Expr::None => Some("zeroinitializer".into()),
+			Expr::Identifier(x) if self.module.typed.mappings.get_const(x.id()).is_some() => {
+				self.module.typed.mappings.get_const(x.id()).map(constant::value)
+			}
+			Expr::Identifier(x) => {
+				let Some((slot, var_ty)) = self.locals.get(&x.id()).cloned() else {
+					// functions are values too, called through their thunk
+					let Some(info) = self.module.funcs.get(&x.id()) else {
+						unsupported_in_codegen(
+							self.module.session,
+							expr.span,
+							"using a function as a value",
+						);
+						return None;
+					};
+					// nothing is captured, so the environment is never looked at
+					return Some(format!("{{ ptr @{}.value, ptr null }}", info.symbol));
+				};
+				if var_ty.is_void() {
+					return None;
+				}
+				let tmp = self.tmp();
+				self.emit(&format!("{tmp} = load {var_ty}, ptr {slot}"));
+				Some(tmp)
+			}
+			Expr::BinaryOp(lhs, op, rhs) if op.value.is_logical() => self.logical(lhs, op.value, rhs),
+			Expr::BinaryOp(lhs, op, rhs) if op.value == Operator::UnwrapOr => {
+				self.unwrap_or(lhs, rhs, ty)
+			}
+			Expr::BinaryOp(lhs, op, rhs) if op.value.is_comparison() => self.comparison(lhs, *op, rhs),
+			Expr::BinaryOp(lhs, op, rhs) => {
+				let lhs = self.expr(lhs, ty)?;
+				let rhs = self.expr(rhs, ty)?;
+				let instruction = match (op.value, ty) {
+					(Operator::Plus, Ty::Int { .. }) => "add",
+					(Operator::Neg, Ty::Int { .. }) => "sub",
+					(Operator::Star, Ty::Int { .. }) => "mul",
+					(Operator::Div, Ty::Int { signed: true, .. }) => "sdiv",
+					(Operator::Div, Ty::Int { signed: false, .. }) => "udiv",
+					(Operator::Plus, Ty::Float { .. }) => "fadd",
+					(Operator::Neg, Ty::Float { .. }) => "fsub",
+					(Operator::Star, Ty::Float { .. }) => "fmul",
+					(Operator::Div, Ty::Float { .. }) => "fdiv",
+					_ => {
+						unsupported_in_codegen(
+							self.module.session,
+							op.span,
+							&format!("the '{}' operator", op.value),
+						);
+						return None;
+					}
+				};
+				let tmp = self.tmp();
+				self.emit(&format!("{tmp} = {instruction} {ty} {lhs}, {rhs}"));
+				Some(tmp)
+			}
+			Expr::UnaryOp(op, value) => {
+				let value = self.expr(value, ty)?;
+				let tmp = self.tmp();
+				match (op.value, ty) {
+					(Operator::Neg, Ty::Int { .. }) => self.emit(&format!("{tmp} = sub {ty} 0, {value}")),
+					(Operator::Neg, Ty::Float { .. }) => self.emit(&format!("{tmp} = fneg {ty} {value}")),
+					_ => {
+						unsupported_in_codegen(
+							self.module.session,
+							op.span,
+							&format!("the '{}' operator", op.value),
+						);
+						return None;
+					}
+				}
+				Some(tmp)
+			}
+			Expr::Ref { value, .. } => self.address_of(value),
+			Expr::Deref(value) => {
+				let ptr = self.expr(value, &Ty::Ptr)?;
+				if ty.is_void() {
+					return None;
+				}
+				let tmp = self.tmp();
+				self.emit(&format!("{tmp} = load {ty}, ptr {ptr}"));
+				Some(tmp)
+			}
+			Expr::Try(value) => self.try_expr(value),
+			Expr::Cast { value, .. } => self.cast(value, ty),
+			Expr::Scope(scope) | Expr::Unsafe(scope) => self.scope(scope, ty),
+			Expr::Call { callee, args, .. } if matches!(callee.value, Expr::Variant { .. }) => {
+				let Expr::Variant { r#enum, variant } = &callee.value else {
+					unreachable!();
+				};
+				self.variant((&r#enum.value, &variant.value), args, callee.span)
+			}
+			Expr::Call { callee, args, .. } => {
+				let mut env_arg = None;
+				let named = match callee.value {
+					Expr::Identifier(Ident::Resolved(id)) => self.module.funcs.get(&id),
+					_ => None,
+				};
+				let (target, return_ty, arg_tys) = if let Some(info) = named {
+					let symbol = format!("@{}", info.symbol);
+					(symbol, info.return_ty.clone(), info.args.clone())
+				} else {
+					// anything else evaluates to a function value
+					let callee_ty = &self.module.typed.expr_ty(callee).value;
+					let Some((return_ty, arg_tys)) = self.module.signature(callee_ty) else {
+						uninferred_type(self.module.session, callee.span);
+						return None;
+					};
+					let value = self.expr(callee, &Ty::Func)?;
+					let (target, env) = (self.tmp(), self.tmp());
+					self.emit(&format!("{target} = extractvalue {} {value}, 0", Ty::Func));
+					self.emit(&format!("{env} = extractvalue {} {value}, 1", Ty::Func));
+					env_arg = Some(format!("ptr {env}"));
+					(target, return_ty, arg_tys)
+				};
+				let mut values: Vec<_> = env_arg.into_iter().collect();
+				for (arg, arg_ty) in args.iter().zip(arg_tys) {
+					if let Some(value) = self.expr(arg, &arg_ty) {
+						values.push(format!("{arg_ty} {value}"));
+					}
+				}
+				let call = format!("call {return_ty} {target}({})", values.join(", "));
+				if return_ty.is_void() {
+					self.emit(&call);
+					None
+				} else {
+					let tmp = self.tmp();
+					self.emit(&format!("{tmp} = {call}"));
+					Some(tmp)
+				}
+			}
+			Expr::If {
+				cond,
+				then,
+				otherwise,
+			} => self.if_expr(cond, then, otherwise.as_deref(), ty),
+			Expr::While {
+				cond,
+				body,
+				otherwise,
+			} => self.while_expr(cond, body, otherwise.as_deref(), ty),
+			Expr::For {
+				var,
+				start,
+				end,
+				body,
+				otherwise,
+			} => self.for_expr(var, (start, end), body, otherwise.as_deref(), ty),
+			Expr::Construct { .. } | Expr::Field { .. } => {
+				unsupported_in_codegen(self.module.session, expr.span, "a struct");
+				None
+			}
+			Expr::Tuple(values) | Expr::Array(values) => Some(self.aggregate(values, ty)),
+			Expr::Index { value, index } => self.index(value, index),
+			Expr::TupleField { value, index } => {
+				let Some(tuple_ty @ Ty::Tuple(_)) = self.expr_ty(value) else {
+					return None;
+				};
+				let tuple = self.expr(value, &tuple_ty)?;
+				self.component(&tuple, &tuple_ty, index.value)
+			}
+			Expr::Closure {
+				signature,
+				body,
+				captures,
+			} => self.closure(signature, &body.value, captures, expr),
+			Expr::Variant { r#enum, variant } => {
+				let declaration = self.module.typed.mappings.get_enum(r#enum.value.id()).unwrap();
+				let (_, declared) = declaration.variant(&variant.value).unwrap();
+				// variants holding values are functions building them
+				if !declared.value.payload.is_empty() {
+					unsupported_in_codegen(
+						self.module.session,
+						expr.span,
+						"using a variant as a function",
+					);
+					return None;
+				}
+				self.variant((&r#enum.value, &variant.value), &[], expr.span)
+			}
+			Expr::Match { value, arms } => self.match_expr(value, arms, ty),
+		}
+	}
+
+	/// Generates the code for a closure, which becomes a function of its own
+	/// named after this one. Its environment is an array with the address of
+	/// every variable it captured, all of which are on the heap so that the
+	/// closure can outlive this function. `closure` is the whole expression.
+	fn closure(
+		&mut self,
+		signature: &Signature,
+		body: &HoistedScope,
+		captures: &[Spanned<Capture>],
+		closure: &Spanned<HoistedExpr>,
+	) -> Option<String> {
+		let closure_ty = &self.module.typed.expr_ty(closure).value;
+		let Some((return_ty, arg_tys)) = self.module.signature(closure_ty) else {
+			uninferred_type(self.module.session, closure.span);
+			return None;
+		};
+		self.next_closure += 1;
+		let symbol = format!("{}.closure{}", self.symbol, self.next_closure);
+		let mut builder = FuncBuilder::new(self.module, symbol.clone(), return_ty);
+		for (i, capture) in captures.iter().enumerate() {
+			let ty = self.module.lower_ty(capture.value.id, capture.span);
+			let field = builder.tmp();
+			builder.emit(&format!("{field} = getelementptr ptr, ptr %env, i64 {i}"));
+			let slot = builder.tmp();
+			builder.emit(&format!("{slot} = load ptr, ptr {field}"));
+			builder.locals.insert(capture.value.id, (slot, ty));
+		}
+		let params = builder.args(&signature.args.value, &arg_tys);
+		let params: Vec<_> = once("ptr %env".to_string()).chain(params).collect();
+		self.closures.push('\n');
+		builder.define(&params, body, &mut self.closures);
+
+		if captures.is_empty() {
+			return Some(format!("{{ ptr @{symbol}, ptr null }}"));
+		}
+		// like the captured variables, the env has to outlive this function
+		let env = self.tmp();
+		self.emit(&format!("{env} = call ptr @malloc(i64 {})", captures.len() * 8));
+		for (i, capture) in captures.iter().enumerate() {
+			let (slot, ty) = &self.locals[&capture.value.id];
+			// void variables have no slot, but they're never loaded from either
+			let slot = if ty.is_void() { "null".into() } else { slot.clone() };
+			let field = self.tmp();
+			self.emit(&format!("{field} = getelementptr ptr, ptr {env}, i64 {i}"));
+			self.emit(&format!("store ptr {slot}, ptr {field}"));
+		}
+		let tmp = self.tmp();
+		self.emit(&format!(
+			"{tmp} = insertvalue {} {{ ptr @{symbol}, ptr undef }}, ptr {env}, 1",
+			Ty::Func
+		));
+		Some(tmp)
+	}
+}
+
+/// Generates an LLVM IR module named `name` out of `typed`.
+#[must_use]
+pub fn codegen(session: &CompilerSession, name: &str, typed: &Typed) -> String {
+	let scope = &typed.program.value;
+	let mut module = Module {
+		session,
+		typed,
+		funcs: HashMap::new(),
+		values: HashSet::new(),
+		strings: Vec::new(),
+		captured: HashSet::new(),
+		prefix: String::new(),
+		generics: HashMap::new(),
+	};
+	module.scope(scope);
+	module.instantiate_funcs();
+
+	let mut out = format!("; ModuleID = '{name}'\nsource_filename = \"{name}\"\n");
+	if !module.strings.is_empty() {
+		out.push('\n');
+	}
+	for (i, value) in module.strings.iter().enumerate() {
+		writeln!(
+			out,
+			"@str.{i} = private unnamed_addr constant [{} x i8] {}",
+			value.len() + 1,
+			constant::string(value)
+		)
+		.unwrap();
+	}
+	if !module.captured.is_empty() {
+		out.push_str("\ndeclare ptr @malloc(i64)\n");
+	}
+	let mut ids: Vec<Id> = module.funcs.keys().copied().collect();
+	ids.sort_unstable();
+	for id in ids {
+		module.emit_func(id, &mut out);
+	}
+	out
+}
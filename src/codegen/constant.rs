@@ -0,0 +1,187 @@
+//! Formatting of number and string literals as LLVM constants.
+
+use crate::{interp::value::Value, lexer::NumberLiteral};
+use std::fmt::Write;
+
+/// Converts the digits of a literal into its binary digits, most significant
+/// first and without leading zeros. Works for arbitrarily wide literals.
+fn binary_digits(radix: u32, digits: &str) -> Vec<u8> {
+	// the number is stored as little-endian base 2^32 limbs
+	let mut limbs: Vec<u32> = vec![];
+	for digit in digits.chars() {
+		let mut carry = u64::from(digit.to_digit(radix).unwrap());
+		for limb in &mut limbs {
+			let value = u64::from(*limb) * u64::from(radix) + carry;
+			// the lower 32 bits of the value are kept, the rest is carried over
+			#[allow(clippy::cast_possible_truncation)]
+			let value_low = value as u32;
+			*limb = value_low;
+			carry = value >> 32;
+		}
+		if carry != 0 {
+			// carry < radix <= 16 here, so no truncation can happen
+			#[allow(clippy::cast_possible_truncation)]
+			limbs.push(carry as u32);
+		}
+	}
+	let mut bits: Vec<u8> = limbs
+		.iter()
+		.rev()
+		.flat_map(|limb| (0..32).rev().map(move |i| u8::from(limb >> i & 1 == 1)))
+		.skip_while(|x| *x == 0)
+		.collect();
+	if bits.is_empty() {
+		bits.push(0);
+	}
+	bits
+}
+
+/// Formats an integer literal as a constant of an integer type `bits` wide.
+/// Returns `None` if the literal doesn't fit in that many bits.
+#[must_use]
+pub fn int(literal: &NumberLiteral, bits: u32) -> Option<String> {
+	let (radix, digits) = literal.digits();
+	let binary = binary_digits(radix, &digits);
+	if binary.len() > bits as usize {
+		return None;
+	}
+	if binary.len() <= 128 {
+		let value = binary
+			.iter()
+			.fold(0u128, |acc, bit| acc << 1 | u128::from(*bit));
+		return Some(value.to_string());
+	}
+	// wider than any native integer, so write it in hex digit by digit
+	let padding = (4 - binary.len() % 4) % 4;
+	let hex: String = std::iter::repeat_n(0, padding)
+		.chain(binary)
+		.collect::<Vec<u8>>()
+		.chunks(4)
+		.map(|chunk| {
+			let digit = chunk.iter().fold(0, |acc, bit| acc << 1 | u32::from(*bit));
+			char::from_digit(digit, 16).unwrap().to_ascii_uppercase()
+		})
+		.collect();
+	Some(format!("u0x{hex}"))
+}
+
+/// Converts a `f32` into the bits of the closest IEEE 754 half precision float,
+/// rounding to nearest, ties to even.
+// all of the casts below happen on values we know fit in the target type
+#[allow(
+	clippy::cast_possible_truncation,
+	clippy::cast_possible_wrap,
+	clippy::cast_sign_loss
+)]
+fn half_bits(value: f32) -> u16 {
+	let bits = value.to_bits();
+	let sign = (bits >> 16 & 0x8000) as u16;
+	let exponent = (bits >> 23 & 0xff) as i32;
+	let mantissa = bits & 0x7f_ffff;
+	if exponent == 0xff {
+		// inf or nan
+		return sign | 0x7c00 | if mantissa == 0 { 0 } else { 0x200 };
+	}
+	let exponent = exponent - 127 + 15;
+	if exponent >= 0x1f {
+		return sign | 0x7c00;
+	}
+	let round = |value: u32, rem: u32, halfway: u32| {
+		if rem > halfway || (rem == halfway && value & 1 == 1) {
+			value + 1
+		} else {
+			value
+		}
+	};
+	if exponent <= 0 {
+		// too small to be a normal half, so it becomes subnormal (or zero)
+		if exponent < -10 {
+			return sign;
+		}
+		let mantissa = mantissa | 0x80_0000;
+		let shift = (14 - exponent) as u32;
+		let value = round(
+			mantissa >> shift,
+			mantissa & ((1 << shift) - 1),
+			1 << (shift - 1),
+		);
+		sign | value as u16
+	} else {
+		// rounding may carry into the exponent, which is exactly what we want
+		let value = round(
+			(exponent as u32) << 10 | mantissa >> 13,
+			mantissa & 0x1fff,
+			0x1000,
+		);
+		sign | value as u16
+	}
+}
+
+/// Converts a `f64` into the bits of the equivalent IEEE 754 quadruple
+/// precision float. This is always exact.
+fn quad_bits(value: f64) -> u128 {
+	let bits = value.to_bits();
+	let sign = u128::from(bits >> 63) << 127;
+	let exponent = bits >> 52 & 0x7ff;
+	let mantissa = bits & 0xf_ffff_ffff_ffff;
+	match exponent {
+		0 if mantissa == 0 => sign,
+		0 => {
+			// subnormal doubles are normal quads
+			let top = mantissa.ilog2();
+			let exponent = u128::from(top + 16383 - 1074);
+			let mantissa = u128::from(mantissa ^ 1 << top) << (112 - top);
+			sign | exponent << 112 | mantissa
+		}
+		0x7ff => sign | 0x7fff << 112 | u128::from(mantissa) << 60,
+		_ => sign | u128::from(exponent + 16383 - 1023) << 112 | u128::from(mantissa) << 60,
+	}
+}
+
+/// Formats a value as a constant of a float type `bits` wide. LLVM only
+/// accepts decimal float constants that are exactly representable, so we
+/// always write out the bits in hex.
+// the casts are lossy on purpose, we want the closest value of the target type
+#[allow(clippy::cast_possible_truncation)]
+#[must_use]
+pub fn float(value: f64, bits: u8) -> String {
+	match bits {
+		16 => format!("0xH{:04X}", half_bits(value as f32)),
+		32 => format!("0x{:016X}", f64::from(value as f32).to_bits()),
+		64 => format!("0x{:016X}", value.to_bits()),
+		128 => {
+			let bits = quad_bits(value);
+			// LLVM wants the low 64 bits first
+			let (low, high) = (bits as u64, (bits >> 64) as u64);
+			format!("0xL{low:016X}{high:016X}")
+		}
+		_ => unreachable!("invalid float width {bits}"),
+	}
+}
+
+/// Formats a value computed at compile time, like the value of a const, as a
+/// constant of its own type.
+#[must_use]
+pub fn value(value: Value) -> String {
+	match value {
+		Value::Float { bits, value } => float(value, bits),
+		// LLVM accepts decimal integers whether they're meant as signed or not
+		_ => value.to_string(),
+	}
+}
+
+/// Formats the bytes of `value` as the initializer of a global byte array,
+/// followed by a NUL byte so that C functions can read it too.
+#[must_use]
+pub fn string(value: &str) -> String {
+	let mut out = String::from("c\"");
+	for byte in value.bytes().chain(std::iter::once(0)) {
+		if byte.is_ascii_graphic() && byte != b'"' && byte != b'\\' || byte == b' ' {
+			out.push(char::from(byte));
+		} else {
+			write!(out, "\\{byte:02X}").unwrap();
+		}
+	}
+	out.push('"');
+	out
+}
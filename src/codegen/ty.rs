@@ -0,0 +1,137 @@
+use crate::{
+	common::r#type::{ArrayLen, BuiltIn, Type},
+	infer::typed::Typed,
+};
+use itertools::Itertools;
+use std::iter::once;
+
+/// Width of `isize` and `usize`. We only target 64-bit platforms for now.
+pub const POINTER_WIDTH: u32 = 64;
+
+/// A `Ty` is a type as LLVM sees it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ty {
+	Int { bits: u32, signed: bool },
+	Float { bits: u8 },
+	/// Pointers are opaque, references and raw pointers to anything all lower
+	/// to the same type.
+	Ptr,
+	/// A flag telling whether there's a value, followed by the value itself.
+	Optional(Box<Self>),
+	/// The address of a function, followed by the environment it's called with
+	/// as its first argument.
+	Func,
+	/// A struct with one field per component. Void components are kept as
+	/// empty structs so that indices line up.
+	Tuple(Vec<Self>),
+	/// A fixed amount of elements of the inner type.
+	Array(Box<Self>, usize),
+	Void,
+}
+
+impl Ty {
+	pub const BOOL: Self = Self::Int {
+		bits: 1,
+		signed: false,
+	};
+	pub const USIZE: Self = Self::Int {
+		bits: POINTER_WIDTH,
+		signed: false,
+	};
+	/// The type of the tag telling which variant an enum holds.
+	pub const TAG: Self = Self::Int {
+		bits: 32,
+		signed: false,
+	};
+
+	#[must_use]
+	pub fn from_builtin(builtin: &BuiltIn) -> Self {
+		match builtin {
+			BuiltIn::Integer { bits, signed } => Self::Int {
+				bits: bits.unwrap_or(POINTER_WIDTH),
+				signed: *signed,
+			},
+			BuiltIn::Float { bits } => Self::Float { bits: *bits },
+			BuiltIn::Char => Self::Int {
+				bits: 32,
+				signed: false,
+			},
+			BuiltIn::Str => Self::Tuple(vec![Self::Ptr, Self::USIZE]),
+			BuiltIn::Void => Self::Void,
+		}
+	}
+
+	/// Lowers `ty`, a type of `typed`. Returns `None` for types that have no
+	/// LLVM representation (yet).
+	#[must_use]
+	pub fn from_type(ty: &Type, typed: &Typed) -> Option<Self> {
+		match ty {
+			Type::BuiltIn(x) => Some(Self::from_builtin(x)),
+			Type::Ref(..) | Type::Ptr(..) => Some(Self::Ptr),
+			Type::Func { .. } => Some(Self::Func),
+			Type::Optional(x) => {
+				Self::from_type(&x.value, typed).map(|x| Self::Optional(Box::new(x)))
+			}
+			Type::Tuple(xs) => xs
+				.iter()
+				.map(|x| Self::from_type(&x.value, typed))
+				.collect::<Option<_>>()
+				.map(Self::Tuple),
+			Type::Array(x, len) => {
+				// inference replaced every const length with its value
+				let ArrayLen::Literal(len) = len.value else {
+					return None;
+				};
+				Self::from_type(&x.value, typed).map(|x| Self::Array(Box::new(x), len))
+			}
+			// the tag is followed by a tuple for the payload of every variant. they
+			// don't overlap, which wastes space but spares us from bitcasting
+			Type::User(x) => typed
+				.enum_payloads(x.id())?
+				.iter()
+				.map(|payload| {
+					payload
+						.iter()
+						.map(|x| Self::from_type(&x.value, typed))
+						.collect::<Option<_>>()
+						.map(Self::Tuple)
+				})
+				.collect::<Option<Vec<_>>>()
+				.map(|variants| Self::Tuple(once(Self::TAG).chain(variants).collect())),
+			Type::Generic(..) | Type::Inferred => None,
+		}
+	}
+
+	#[must_use]
+	pub fn is_void(&self) -> bool {
+		matches!(self, Self::Void)
+	}
+}
+
+impl std::fmt::Display for Ty {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Int { bits, .. } => f.write_fmt(format_args!("i{bits}")),
+			Self::Float { bits: 16 } => f.write_str("half"),
+			Self::Float { bits: 32 } => f.write_str("float"),
+			Self::Float { bits: 64 } => f.write_str("double"),
+			Self::Float { bits: 128 } => f.write_str("fp128"),
+			Self::Float { bits } => unreachable!("invalid float width {bits}"),
+			Self::Ptr => f.write_str("ptr"),
+			// there's no point in storing nothing
+			Self::Optional(x) if x.is_void() => f.write_str("{ i1 }"),
+			Self::Optional(x) => f.write_fmt(format_args!("{{ i1, {x} }}")),
+			Self::Func => f.write_str("{ ptr, ptr }"),
+			Self::Tuple(xs) => {
+				let fields = xs
+					.iter()
+					.map(|x| if x.is_void() { "{}".into() } else { x.to_string() })
+					.join(", ");
+				f.write_fmt(format_args!("{{ {fields} }}"))
+			}
+			Self::Array(x, len) if x.is_void() => f.write_fmt(format_args!("[{len} x {{}}]")),
+			Self::Array(x, len) => f.write_fmt(format_args!("[{len} x {x}]")),
+			Self::Void => f.write_str("void"),
+		}
+	}
+}
@@ -1,12 +1,17 @@
 //! Contains common types that can be used across "passes" of the compilation.
 
+pub mod r#const;
 pub mod diagnostics;
+pub mod r#enum;
 pub mod expr;
 pub mod func;
 pub mod ident;
 pub mod join;
+pub mod pattern;
 pub mod scope;
 pub mod span;
 pub mod stmt;
+pub mod r#struct;
 pub mod r#type;
 pub mod typed_ident;
+pub mod visit;
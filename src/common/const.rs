@@ -0,0 +1,21 @@
+use super::{expr::Expr, ident::Ident, r#type::Type, scope::Scope, span::Spanned};
+
+#[derive(Debug, Clone)]
+pub struct Const<Sc: Scope> {
+	/// The name of the const as written in code. Just like with `Func`, this
+	/// stays unresolved, the resolved id is the key the const is stored under.
+	pub id: Spanned<Ident>,
+	/// The type of the const, which must be numeric so that its value can be
+	/// computed at compile time.
+	pub ty: Spanned<Type>,
+	pub value: Spanned<Expr<Sc>>,
+}
+
+impl<Sc: Scope> std::fmt::Display for Const<Sc>
+where
+	Expr<Sc>: std::fmt::Display,
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_fmt(format_args!("const {}: {} = {}", self.id, self.ty, self.value))
+	}
+}
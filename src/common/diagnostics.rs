@@ -4,33 +4,11 @@ use super::{
 	span::{Span, Spanned},
 	stmt::Stmt,
 };
-use crate::{checker::context::Context, resolver::mappings::MapRepr};
+use crate::{checker::context::Context, resolver::mappings::MapRepr, session::CompilerSession};
 use codespan_reporting::diagnostic::{Diagnostic, Label};
-use lazy_static::lazy_static;
-use std::sync::Mutex;
 
-lazy_static! {
-	static ref DIAGNOSTICS: Mutex<Vec<Diagnostic<usize>>> = Mutex::new(vec![]);
-}
-
-pub fn add_diagnostic(diagnostic: Diagnostic<usize>) {
-	DIAGNOSTICS.lock().unwrap().push(diagnostic);
-}
-
-// allow because it contrasts with add_diagnostic as this one is plural and
-// takes in a vec
-#[allow(clippy::module_name_repetitions)]
-pub fn add_diagnostics(diagnostics: &mut Vec<Diagnostic<usize>>) {
-	DIAGNOSTICS.lock().unwrap().append(diagnostics);
-}
-
-#[must_use]
-pub fn own() -> Vec<Diagnostic<usize>> {
-	DIAGNOSTICS.lock().unwrap().clone()
-}
-
-pub fn type_mismatch(span: Span, used: MapRepr, desired: MapRepr) {
-	add_diagnostic(
+pub fn type_mismatch(session: &CompilerSession, span: Span, used: MapRepr, desired: MapRepr) {
+	session.add_diagnostic(
 		Diagnostic::error()
 			.with_message("type mismatch")
 			.with_labels(vec![Label::primary(span.file_id, span.range())
@@ -38,8 +16,8 @@ pub fn type_mismatch(span: Span, used: MapRepr, desired: MapRepr) {
 	);
 }
 
-pub fn nonexistent_item(span: Span, ident: &Ident) {
-	add_diagnostic(
+pub fn nonexistent_item(session: &CompilerSession, span: Span, ident: &Ident) {
+	session.add_diagnostic(
 		Diagnostic::error()
 			.with_message("referenced nonexistent item")
 			.with_labels(vec![Label::primary(span.file_id, span.range())
@@ -49,8 +27,8 @@ pub fn nonexistent_item(span: Span, ident: &Ident) {
 	);
 }
 
-pub fn discarded_ident(span: Span) {
-	add_diagnostic(
+pub fn discarded_ident(session: &CompilerSession, span: Span) {
+	session.add_diagnostic(
 		Diagnostic::error()
 			.with_message("referenced discarded item where value is required")
 			.with_labels(vec![Label::primary(span.file_id, span.range())
@@ -59,8 +37,12 @@ pub fn discarded_ident(span: Span) {
 	);
 }
 
-pub fn invalid_stmt<T: Scope>(stmt: &Spanned<Stmt<T>>, context: Context) {
-	add_diagnostic(
+pub fn invalid_stmt<T: Scope>(
+	session: &CompilerSession,
+	stmt: &Spanned<Stmt<T>>,
+	context: Context,
+) {
+	session.add_diagnostic(
 		Diagnostic::error()
 			.with_message(format!(
 				"invalid {} statement in {context} context",
@@ -70,11 +52,589 @@ pub fn invalid_stmt<T: Scope>(stmt: &Spanned<Stmt<T>>, context: Context) {
 	);
 }
 
-pub fn invalid_case<W: std::fmt::Display, F: std::fmt::Display>(span: Span, wanted: W, found: F) {
-	add_diagnostic(
+pub fn invalid_case<W: std::fmt::Display, F: std::fmt::Display>(
+	session: &CompilerSession,
+	span: Span,
+	wanted: W,
+	found: F,
+) {
+	session.add_diagnostic(
 		Diagnostic::warning()
 			.with_message("wrong case system used")
 			.with_labels(vec![Label::primary(span.file_id, span.range())
-				.with_message(format!("expected {wanted}, found {found}",))]),
+				.with_message(format!("expected {wanted}, found {found}"))]),
+	);
+}
+
+pub fn invalid_cast(session: &CompilerSession, span: Span, from: &str, to: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("invalid cast")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("can't cast {from} to {to}"))])
+			.with_notes(vec!["only integers and floats can be cast with `as`".into()]),
+	);
+}
+
+pub fn lossy_cast(session: &CompilerSession, span: Span, from: &str, to: &str, loss: &str) {
+	session.add_diagnostic(
+		Diagnostic::warning()
+			.with_message("lossy cast")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("casting {from} to {to} may lose information"))])
+			.with_notes(vec![loss.into()]),
+	);
+}
+
+pub fn uninferred_type(session: &CompilerSession, span: Span) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("could not infer type")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message("consider giving this an explicit type")]),
+	);
+}
+
+pub fn unsupported_in_codegen(session: &CompilerSession, span: Span, what: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message(format!("{what} cannot be compiled yet"))
+			.with_labels(vec![Label::primary(span.file_id, span.range())]),
+	);
+}
+
+pub fn number_out_of_range<T: std::fmt::Display>(session: &CompilerSession, span: Span, ty: T) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("number literal out of range")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("this does not fit in {ty}"))]),
+	);
+}
+
+pub fn invalid_const_ty<T: std::fmt::Display>(session: &CompilerSession, span: Span, ty: T) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("invalid const type")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("consts can't be of type {ty}"))])
+			.with_notes(vec!["only integers and floats can be computed at compile time".into()]),
+	);
+}
+
+pub fn not_const(session: &CompilerSession, span: Span, what: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("not a constant expression")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("{what} can't be evaluated at compile time"))])
+			.with_notes(vec![
+				"consts may only use number literals, arithmetic, other consts and calls to `pure` \
+				 functions"
+					.into(),
+			]),
+	);
+}
+
+pub fn const_overflow<T: std::fmt::Display>(session: &CompilerSession, span: Span, ty: T) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("overflow in const")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("the result does not fit in {ty}"))]),
+	);
+}
+
+pub fn const_eval_failed(session: &CompilerSession, span: Span, reason: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("could not evaluate const")
+			.with_labels(vec![Label::primary(span.file_id, span.range()).with_message(reason)]),
+	);
+}
+
+pub fn recursive_const(session: &CompilerSession, span: Span, ident: &Ident) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("const depends on itself")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("the value of '{ident}' is needed to compute itself"))]),
+	);
+}
+
+pub fn invalid_array_len(session: &CompilerSession, span: Span, len: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("invalid array length")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("{len} is not a valid array length"))])
+			.with_notes(vec!["array lengths must be integers no smaller than zero".into()]),
+	);
+}
+
+pub fn not_a_struct(session: &CompilerSession, span: Span, ty: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("expected a struct")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("this is of type {ty}, which has no fields"))]),
+	);
+}
+
+pub fn nonexistent_field(session: &CompilerSession, span: Span, field: &Ident, ty: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("referenced nonexistent field")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("{ty} has no field named '{field}'"))]),
+	);
+}
+
+pub fn not_an_enum(session: &CompilerSession, span: Span, ident: &Ident) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("expected an enum")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("'{ident}' is not an enum, so it has no variants"))]),
+	);
+}
+
+pub fn nonexistent_variant(session: &CompilerSession, span: Span, variant: &Ident, ty: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("referenced nonexistent variant")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("{ty} has no variant named '{variant}'"))]),
+	);
+}
+
+pub fn wrong_payload_count(
+	session: &CompilerSession,
+	span: Span,
+	variant: &str,
+	expected: usize,
+	found: usize,
+) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("wrong number of values in pattern")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!(
+					"{variant} holds {expected} value{}, found {found}",
+					if expected == 1 { "" } else { "s" }
+				))]),
+	);
+}
+
+pub fn non_exhaustive_match(session: &CompilerSession, span: Span, missing: &[String]) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("non-exhaustive match")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("missing {}", missing.join(", ")))])
+			.with_notes(vec![
+				"add an arm for each of them, or a `_` arm matching everything else".into(),
+			]),
+	);
+}
+
+pub fn recursive_enum(session: &CompilerSession, span: Span, ident: &Ident) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("recursive enum has infinite size")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("'{ident}' holds a value of its own type"))])
+			.with_notes(vec![
+				"put the value behind a reference or a pointer to give the enum a size".into(),
+			]),
+	);
+}
+
+pub fn recursive_struct(session: &CompilerSession, span: Span, ident: &Ident) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("recursive struct has infinite size")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("'{ident}' holds a value of its own type"))])
+			.with_notes(vec![
+				"put the value behind a reference or a pointer to give the struct a size".into(),
+			]),
+	);
+}
+
+pub fn not_a_tuple(session: &CompilerSession, span: Span, ty: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("expected a tuple")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("this is of type {ty}, which has no components"))]),
+	);
+}
+
+pub fn nonexistent_tuple_field(
+	session: &CompilerSession,
+	span: Span,
+	index: usize,
+	ty: &str,
+	len: usize,
+) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("referenced nonexistent tuple component")
+			.with_labels(vec![Label::primary(span.file_id, span.range()).with_message(
+				format!("{ty} has {len} components, so there's no component {index}"),
+			)]),
+	);
+}
+
+pub fn not_an_array(session: &CompilerSession, span: Span, ty: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("expected an array")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("this is of type {ty}, which can't be indexed"))]),
+	);
+}
+
+pub fn index_out_of_bounds(session: &CompilerSession, span: Span, index: &str, len: usize) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("index out of bounds")
+			.with_labels(vec![Label::primary(span.file_id, span.range()).with_message(
+				format!("the array has {len} elements, so there's no element {index}"),
+			)]),
+	);
+}
+
+pub fn non_integer_index(session: &CompilerSession, span: Span, ty: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("expected an integer index")
+			.with_labels(vec![Label::primary(span.file_id, span.range()).with_message(
+				format!("this is of type {ty}, but arrays can only be indexed with integers"),
+			)]),
+	);
+}
+
+pub fn duplicate_field(session: &CompilerSession, span: Span, first: Span) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("field specified more than once")
+			.with_labels(vec![
+				Label::primary(span.file_id, span.range()),
+				Label::secondary(first.file_id, first.range())
+					.with_message("first specified here"),
+			]),
+	);
+}
+
+pub fn missing_fields(session: &CompilerSession, span: Span, ty: &str, fields: &[String]) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message(format!("missing fields in construction of {ty}"))
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("missing {}", fields.join(", ")))]),
+	);
+}
+
+pub fn wrong_generic_count(session: &CompilerSession, span: Span, expected: usize, found: usize) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("wrong number of generics")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!(
+					"expected {expected} generic{}, found {found}",
+					if expected == 1 { "" } else { "s" }
+				))]),
+	);
+}
+
+pub fn wrong_arg_count(session: &CompilerSession, span: Span, expected: usize, found: usize) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("wrong number of arguments")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!(
+					"expected {expected} argument{}, found {found}",
+					if expected == 1 { "" } else { "s" }
+				))]),
+	);
+}
+
+pub fn not_callable(session: &CompilerSession, span: Span, ty: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("called a value that isn't a function")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("this is of type {ty}, which can't be called"))]),
+	);
+}
+
+pub fn non_integer_range(session: &CompilerSession, span: Span, ty: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("expected integer range bounds")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("this is of type {ty}, but ranges can only go over integers"))]),
+	);
+}
+
+pub fn not_a_pointer(session: &CompilerSession, span: Span, ty: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("dereferenced a value that isn't a pointer")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("this is of type {ty}, which isn't a reference or pointer"))]),
+	);
+}
+
+pub fn not_an_optional(session: &CompilerSession, span: Span, ty: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("expected an optional value")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("this is of type {ty}, which isn't optional"))]),
+	);
+}
+
+/// `return_ty` is the return type of the enclosing function, if there's one.
+pub fn try_outside_optional(session: &CompilerSession, span: Span, return_ty: Option<&str>) {
+	let message = match return_ty {
+		Some(ty) => format!("the enclosing function returns {ty}"),
+		None => "this isn't inside of a function".into(),
+	};
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("the `?` operator can only be used in functions returning an optional")
+			.with_labels(vec![Label::primary(span.file_id, span.range()).with_message(message)])
+			.with_notes(vec![
+				"`?` returns `none` from the function when there's no value to unwrap".into(),
+			]),
+	);
+}
+
+pub fn immutable_pointee(session: &CompilerSession, span: Span, ty: &str) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("cannot assign through an immutable pointer")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!("this is of type {ty}"))])
+			.with_notes(vec![
+				"only `&mut` references and `*mut` pointers allow changing what they point to"
+					.into(),
+			]),
+	);
+}
+
+pub fn nonexistent_module(session: &CompilerSession, span: Span, path: &Ident) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("imported nonexistent module")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message(format!(
+					"'{path}' is neither a module nor an item of one"
+				))])
+			.with_notes(vec![
+				"every input file is a module, named after its path".into()
+			]),
+	);
+}
+
+pub fn import_conflict(session: &CompilerSession, span: Span, local: Span, ident: &Ident) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("imported item conflicts with a local one")
+			.with_labels(vec![
+				Label::primary(span.file_id, span.range())
+					.with_message(format!("'{ident}' is imported here")),
+				Label::secondary(local.file_id, local.range())
+					.with_message("but it is also declared here"),
+			]),
+	);
+}
+
+/// `action` is how `what` was going to be changed (like "assign to"), and
+/// `why` explains at its declaration why it can't be.
+pub fn immutable_binding(
+	session: &CompilerSession,
+	span: Span,
+	declaration: Span,
+	action: &str,
+	what: &str,
+	why: &str,
+) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message(format!("cannot {action} {what}"))
+			.with_labels(vec![
+				Label::primary(span.file_id, span.range()).with_message("changed here"),
+				Label::secondary(declaration.file_id, declaration.range()).with_message(why),
+			]),
+	);
+}
+
+/// `closure` is the span of the closure that captures what it changes.
+pub fn immutable_capture(
+	session: &CompilerSession,
+	span: Span,
+	declaration: Span,
+	closure: Span,
+	what: &str,
+	why: &str,
+) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message(format!("closure changes {what} it captures"))
+			.with_labels(vec![
+				Label::primary(span.file_id, span.range()).with_message("changed here"),
+				Label::secondary(declaration.file_id, declaration.range()).with_message(why),
+				Label::secondary(closure.file_id, closure.range())
+					.with_message("captured by this closure"),
+			])
+			.with_notes(vec![
+				"closures capture by reference, so this would change the original".into(),
+			]),
+	);
+}
+
+pub fn captured_by_func(session: &CompilerSession, span: Span, var: &Ident) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message(format!("function uses `{var}` from outside of it"))
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message("captured here")])
+			.with_notes(vec!["only closures (`func (...) => ...`) can capture variables".into()]),
+	);
+}
+
+/// `pure` is the span of the attributes of the pure function the call is in.
+pub fn impure_call(
+	session: &CompilerSession,
+	span: Span,
+	callee: &Ident,
+	declaration: Span,
+	pure: Span,
+) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("pure function calls an impure function")
+			.with_labels(vec![
+				Label::primary(span.file_id, span.range())
+					.with_message(format!("'{callee}' isn't pure")),
+				Label::secondary(declaration.file_id, declaration.range())
+					.with_message("declared here without `pure`"),
+				Label::secondary(pure.file_id, pure.range())
+					.with_message("the caller is pure because of this"),
+			]),
+	);
+}
+
+pub fn unknown_purity(session: &CompilerSession, span: Span, pure: Span) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("pure function calls something that might not be pure")
+			.with_labels(vec![
+				Label::primary(span.file_id, span.range())
+					.with_message("only functions called by name can be checked for purity"),
+				Label::secondary(pure.file_id, pure.range())
+					.with_message("the caller is pure because of this"),
+			]),
+	);
+}
+
+pub fn impure_assignment(session: &CompilerSession, span: Span, declaration: Span, pure: Span) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("pure function assigns to a variable declared outside of it")
+			.with_labels(vec![
+				Label::primary(span.file_id, span.range()).with_message("assigned to here"),
+				Label::secondary(declaration.file_id, declaration.range())
+					.with_message("declared outside of the function here"),
+				Label::secondary(pure.file_id, pure.range())
+					.with_message("the function is pure because of this"),
+			]),
+	);
+}
+
+pub fn impure_store(session: &CompilerSession, span: Span, pure: Span) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("pure function writes through a pointer")
+			.with_labels(vec![
+				Label::primary(span.file_id, span.range())
+					.with_message("what this points to may live outside of the function"),
+				Label::secondary(pure.file_id, pure.range())
+					.with_message("the function is pure because of this"),
+			]),
+	);
+}
+
+pub fn unsafe_call(session: &CompilerSession, span: Span, callee: &Ident, declaration: Span) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("call to an unsafe function outside of an unsafe context")
+			.with_labels(vec![
+				Label::primary(span.file_id, span.range())
+					.with_message(format!("'{callee}' is unsafe")),
+				Label::secondary(declaration.file_id, declaration.range())
+					.with_message("declared `unsafe` here"),
+			])
+			.with_notes(vec![
+				"call it inside an `unsafe { }` block, or mark the caller `unsafe`".into(),
+			]),
+	);
+}
+
+pub fn unsafe_value_call(session: &CompilerSession, span: Span) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("call to an unsafe function outside of an unsafe context")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message("this has the type of an unsafe function")])
+			.with_notes(vec![
+				"call it inside an `unsafe { }` block, or mark the caller `unsafe`".into(),
+			]),
+	);
+}
+
+pub fn unsafe_deref(session: &CompilerSession, span: Span) {
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("dereference of a raw pointer outside of an unsafe context")
+			.with_labels(vec![Label::primary(span.file_id, span.range())
+				.with_message("this goes through a raw pointer")])
+			.with_notes(vec![
+				"do it inside an `unsafe { }` block, or mark the function `unsafe`".into()
+			]),
+	);
+}
+
+/// `imports` holds the span of every import in the cycle, along with the
+/// module it is in and the module it imports.
+pub fn import_cycle(session: &CompilerSession, imports: &[(Span, &Ident, &Ident)]) {
+	let cycle = imports
+		.iter()
+		.map(|(_, from, _)| from.to_string())
+		.chain(imports.first().map(|(_, from, _)| from.to_string()))
+		.collect::<Vec<_>>()
+		.join(" -> ");
+	session.add_diagnostic(
+		Diagnostic::error()
+			.with_message("import cycle detected")
+			.with_labels(
+				imports
+					.iter()
+					.enumerate()
+					.map(|(i, (span, from, to))| {
+						if i == 0 {
+							Label::primary(span.file_id, span.range())
+						} else {
+							Label::secondary(span.file_id, span.range())
+						}
+						.with_message(format!("'{from}' imports '{to}'"))
+					})
+					.collect(),
+			)
+			.with_notes(vec![format!("modules can't depend on themselves: {cycle}")]),
 	);
 }
@@ -0,0 +1,52 @@
+use super::{join::Join, span::Spanned};
+use crate::common::{ident::Ident, r#type::Type};
+
+#[derive(Debug, Clone)]
+pub struct Variant {
+	pub id: Spanned<Ident>,
+	/// The types of the values the variant holds, if any.
+	pub payload: Vec<Spanned<Type>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Enum {
+	/// The name of the enum as written in code. Just like with `Struct`, this
+	/// stays unresolved, the resolved id is the key the enum is stored under.
+	pub id: Spanned<Ident>,
+	/// The variants of the enum, whose idents are never resolved since they can
+	/// only be referred to through the enum.
+	pub variants: Spanned<Vec<Spanned<Variant>>>,
+}
+
+impl Enum {
+	/// Gets the variant named `name` along with its position in the enum,
+	/// which backends use as its tag.
+	#[must_use]
+	pub fn variant(&self, name: &Ident) -> Option<(usize, &Spanned<Variant>)> {
+		self.variants
+			.value
+			.iter()
+			.enumerate()
+			.find(|(_, x)| x.value.id.value == *name)
+	}
+}
+
+impl std::fmt::Display for Variant {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_fmt(format_args!(
+			"{}{}",
+			self.id,
+			(&self.payload).join_comma_wrapped("(", ")")
+		))
+	}
+}
+
+impl std::fmt::Display for Enum {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_fmt(format_args!(
+			"enum {} {{{}}}",
+			self.id,
+			(&self.variants.value).join_comma_wrapped(" ", " ")
+		))
+	}
+}
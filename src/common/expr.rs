@@ -1,37 +1,178 @@
-use super::{join::Join, r#type::Type, scope::Scope, span::Spanned};
+use super::{
+	func::Signature,
+	join::Join,
+	pattern::Pattern,
+	r#type::Type,
+	scope::Scope,
+	span::{Add, Spanned},
+};
 use crate::{
-	common::ident::Ident,
+	common::ident::{Id, Ident},
 	lexer::{NumberLiteral, Operator},
 };
 
-// TODO: dot access (a.b), deref, ref, construct (Struct {a: 3, b: 5}), array
-// literal?, tuple??
+/// A variable used by a closure that was declared outside of it. The span is
+/// where it's first mutated, or where it's first read if it never is.
+#[derive(Debug, Clone, Copy)]
+pub struct Capture {
+	pub id: Id,
+	pub mutated: bool,
+}
+
 #[derive(Debug, Clone)]
 pub enum Expr<Sc: Scope> {
 	NumberLiteral(NumberLiteral),
+	/// A string with its escape sequences already decoded, of type `str`.
+	StringLiteral(String),
+	CharLiteral(char),
+	/// The absence of a value, for any optional type.
+	None,
 	Identifier(Ident),
+	/// A variant of an enum, which is a function building it if it holds any
+	/// values. The parser sees these as qualified identifiers, they're only
+	/// told apart by the resolver.
+	Variant {
+		r#enum: Spanned<Ident>,
+		variant: Spanned<Ident>,
+	},
 	BinaryOp(
 		Box<Spanned<Expr<Sc>>>,
 		Spanned<Operator>,
 		Box<Spanned<Expr<Sc>>>,
 	),
 	UnaryOp(Spanned<Operator>, Box<Spanned<Expr<Sc>>>),
+	/// Takes a reference to `value`, through which it can be changed if the
+	/// reference is mutable.
+	Ref {
+		value: Box<Spanned<Expr<Sc>>>,
+		mutable: bool,
+	},
+	/// Reads the value behind a reference or a raw pointer.
+	Deref(Box<Spanned<Expr<Sc>>>),
+	/// Evaluates to the value inside an optional, returning `none` from the
+	/// current function if there's nothing inside.
+	Try(Box<Spanned<Expr<Sc>>>),
+	/// Converts a number to another numeric type, which may lose information.
+	Cast {
+		value: Box<Spanned<Expr<Sc>>>,
+		ty: Spanned<Type>,
+	},
 	Scope(Sc),
+	/// A scope in which unsafe functions can be called.
+	Unsafe(Sc),
 	Call {
 		callee: Box<Spanned<Expr<Sc>>>,
 		generics: Option<Vec<Spanned<Type>>>,
 		args: Vec<Spanned<Expr<Sc>>>,
 	},
+	/// Creates a value of a struct by giving a value to each of its fields.
+	Construct {
+		ty: Spanned<Type>,
+		fields: Vec<(Spanned<Ident>, Spanned<Expr<Sc>>)>,
+	},
+	/// Evaluates to `then` if `cond` is true, or to `otherwise` if it isn't. A
+	/// missing `otherwise` makes the whole expression void.
+	If {
+		cond: Box<Spanned<Expr<Sc>>>,
+		then: Box<Spanned<Expr<Sc>>>,
+		otherwise: Option<Box<Spanned<Expr<Sc>>>>,
+	},
+	/// Runs `body` for as long as `cond` is true. The loop evaluates to the
+	/// value it was broken out of with, or to `otherwise` once `cond` is false;
+	/// a missing `otherwise` makes the whole expression void.
+	While {
+		cond: Box<Spanned<Expr<Sc>>>,
+		body: Box<Spanned<Expr<Sc>>>,
+		otherwise: Option<Box<Spanned<Expr<Sc>>>>,
+	},
+	/// Runs `body` once for every integer from `start` up to (but excluding)
+	/// `end`, binding it to `var`. Evaluates to a value just like `While`.
+	For {
+		var: Spanned<Ident>,
+		start: Box<Spanned<Expr<Sc>>>,
+		end: Box<Spanned<Expr<Sc>>>,
+		body: Box<Spanned<Expr<Sc>>>,
+		otherwise: Option<Box<Spanned<Expr<Sc>>>>,
+	},
+	/// Evaluates to the expression of the first arm whose pattern matches
+	/// `value`. Every possible value must be matched by some arm.
+	Match {
+		value: Box<Spanned<Expr<Sc>>>,
+		arms: Vec<(Spanned<Pattern>, Spanned<Expr<Sc>>)>,
+	},
+	/// Accesses a field of a struct value.
+	Field {
+		value: Box<Spanned<Expr<Sc>>>,
+		field: Spanned<Ident>,
+	},
+	/// Groups at least two values into a tuple.
+	Tuple(Vec<Spanned<Expr<Sc>>>),
+	/// Accesses a component of a tuple value by its position.
+	TupleField {
+		value: Box<Spanned<Expr<Sc>>>,
+		index: Spanned<usize>,
+	},
+	/// Groups any amount of values of the same type into an array.
+	Array(Vec<Spanned<Expr<Sc>>>),
+	/// Accesses an element of an array value by its position, which must be a
+	/// `usize`.
+	Index {
+		value: Box<Spanned<Expr<Sc>>>,
+		index: Box<Spanned<Expr<Sc>>>,
+	},
+	/// An anonymous function. It captures the variables it uses from its
+	/// surroundings by reference, which are only known after resolution.
+	Closure {
+		signature: Signature,
+		body: Box<Spanned<Sc>>,
+		captures: Vec<Spanned<Capture>>,
+	},
+}
+
+impl<Sc: Scope> Spanned<Expr<Sc>> {
+	/// Returns the variable holding the value `self` refers to, if it refers to
+	/// a place in memory rather than to a temporary. Array elements live in the
+	/// array they're taken from.
+	#[must_use]
+	pub fn place(&self) -> Option<Spanned<Ident>> {
+		match &self.value {
+			Expr::Identifier(ident) => Some(ident.clone().add_span(self.span)),
+			Expr::Index { value, .. } => value.place(),
+			_ => None,
+		}
+	}
+}
+
+impl std::fmt::Display for Capture {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_fmt(format_args!(
+			"{}@{}",
+			if self.mutated { "mut " } else { "" },
+			self.id
+		))
+	}
 }
 
 impl<Sc: Scope + std::fmt::Display> std::fmt::Display for Expr<Sc> {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
 			Expr::NumberLiteral(num) => f.write_fmt(format_args!("{num}")),
+			Expr::StringLiteral(x) => f.write_fmt(format_args!("{x:?}")),
+			Expr::CharLiteral(x) => f.write_fmt(format_args!("{x:?}")),
+			Expr::None => f.write_str("none"),
 			Expr::Identifier(ident) => f.write_fmt(format_args!("{ident}")),
+			Expr::Variant { r#enum, variant } => f.write_fmt(format_args!("{enum}::{variant}")),
 			Expr::BinaryOp(lhs, op, rhs) => f.write_fmt(format_args!("({lhs} {op} {rhs})")),
 			Expr::UnaryOp(op, value) => f.write_fmt(format_args!("({op}{value})")),
+			Expr::Ref { value, mutable } => f.write_fmt(format_args!(
+				"(&{}{value})",
+				if *mutable { "mut " } else { "" }
+			)),
+			Expr::Deref(value) => f.write_fmt(format_args!("(*{value})")),
+			Expr::Try(value) => f.write_fmt(format_args!("({value}?)")),
+			Expr::Cast { value, ty } => f.write_fmt(format_args!("({value} as {ty})")),
 			Expr::Scope(scope) => f.write_fmt(format_args!("{scope}")),
+			Expr::Unsafe(scope) => f.write_fmt(format_args!("unsafe {scope}")),
 			Expr::Call {
 				callee,
 				generics,
@@ -43,6 +184,67 @@ impl<Sc: Scope + std::fmt::Display> std::fmt::Display for Expr<Sc> {
 					.map_or(String::new(), |x| (&x).join_comma_wrapped("<", ">")),
 				args.join_comma_wrapped("(", ")")
 			)),
+			Expr::Construct { ty, fields } => f.write_fmt(format_args!(
+				"{ty} {{{}}}",
+				(&fields
+					.iter()
+					.map(|(field, value)| format!("{field}: {value}"))
+					.collect::<Vec<_>>())
+					.join_comma_wrapped(" ", " ")
+			)),
+			Expr::If {
+				cond,
+				then,
+				otherwise,
+			} => f.write_fmt(format_args!(
+				"if ({cond}) {then}{}",
+				otherwise
+					.as_ref()
+					.map_or(String::new(), |x| format!(" else {x}"))
+			)),
+			Expr::While {
+				cond,
+				body,
+				otherwise,
+			} => f.write_fmt(format_args!(
+				"while ({cond}) {body}{}",
+				otherwise
+					.as_ref()
+					.map_or(String::new(), |x| format!(" else {x}"))
+			)),
+			Expr::For {
+				var,
+				start,
+				end,
+				body,
+				otherwise,
+			} => f.write_fmt(format_args!(
+				"for {var} in {start}..{end} {body}{}",
+				otherwise
+					.as_ref()
+					.map_or(String::new(), |x| format!(" else {x}"))
+			)),
+			Expr::Match { value, arms } => f.write_fmt(format_args!(
+				"match ({value}) {{{}}}",
+				(&arms
+					.iter()
+					.map(|(pattern, value)| format!("{pattern} => {value}"))
+					.collect::<Vec<_>>())
+					.join_comma_wrapped(" ", " ")
+			)),
+			Expr::Field { value, field } => f.write_fmt(format_args!("({value}).{field}")),
+			Expr::Tuple(values) => f.write_fmt(format_args!("({})", values.join_comma_or_empty())),
+			Expr::TupleField { value, index } => f.write_fmt(format_args!("({value}).{index}")),
+			Expr::Array(values) => f.write_fmt(format_args!("[{}]", values.join_comma_or_empty())),
+			Expr::Index { value, index } => f.write_fmt(format_args!("({value})[{index}]")),
+			Expr::Closure {
+				signature,
+				body,
+				captures,
+			} => f.write_fmt(format_args!(
+				"func [{signature}]{} {body}",
+				captures.join_comma_wrapped(" [captures ", "]")
+			)),
 		}
 	}
 }
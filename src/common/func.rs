@@ -28,6 +28,9 @@ pub struct Signature {
 
 #[derive(Debug, Clone)]
 pub struct Func<Sc: Scope> {
+	/// The name of the function as written in code. This stays unresolved even
+	/// after resolution, the resolved id is the key the function is stored
+	/// under instead.
 	pub id: Spanned<Ident>,
 	pub signature: Signature,
 	pub body: Option<Spanned<Sc>>,
@@ -64,7 +67,7 @@ impl<Sc: Scope + std::fmt::Display> std::fmt::Display for Func<Sc> {
 			"{} [{}] {}",
 			self.id,
 			self.signature,
-			self.body.as_ref().map_or("".into(), |x| format!("{x}"))
+			self.body.as_ref().map_or(String::new(), |x| format!("{x}"))
 		))
 	}
 }
@@ -44,6 +44,32 @@ impl Ident {
 		matches!(self, Self::Discarded)
 	}
 
+	/// Builds the ident for a path, which is only qualified if it has more than
+	/// one segment.
+	///
+	/// # Panics
+	///
+	/// The function will panic if `segments` is empty.
+	#[must_use]
+	pub fn qualify(mut segments: Vec<Self>) -> Self {
+		if segments.len() == 1 {
+			segments.pop().unwrap()
+		} else {
+			assert!(!segments.is_empty(), "tried to qualify an empty path");
+			Self::Qualified(segments)
+		}
+	}
+
+	/// Returns the segments of the path this ident represents, which is just
+	/// the ident itself unless it's qualified.
+	#[must_use]
+	pub fn segments(&self) -> Vec<Self> {
+		match self {
+			Self::Qualified(x) => x.clone(),
+			x => vec![x.clone()],
+		}
+	}
+
 	#[must_use]
 	pub fn id(&self) -> Id {
 		match self {
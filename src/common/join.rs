@@ -6,10 +6,7 @@ pub trait Join<T: std::fmt::Display>: IntoIterator<Item = T> + Copy {
 	}
 
 	fn join_comma_or_empty(&self) -> String {
-		match self.join_comma() {
-			Some(x) => x,
-			None => String::new(),
-		}
+		self.join_comma().unwrap_or_default()
 	}
 
 	fn join_comma_wrapped(&self, wrap_l: &str, wrap_r: &str) -> String {
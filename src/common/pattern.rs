@@ -0,0 +1,55 @@
+use super::{
+	ident::Ident,
+	join::Join,
+	span::{Add, Spanned},
+};
+use crate::lexer::NumberLiteral;
+
+/// The shape of the values an arm of a `match` applies to.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+	/// Matches anything, binding it to a new variable unless the ident is
+	/// discarded (`_`).
+	Binding(Ident),
+	NumberLiteral(NumberLiteral),
+	CharLiteral(char),
+	/// Matches the variant `variant` of the enum `enum`, as long as every value
+	/// it holds matches the pattern at the same position in `fields`.
+	Variant {
+		r#enum: Spanned<Ident>,
+		variant: Spanned<Ident>,
+		fields: Vec<Spanned<Pattern>>,
+	},
+}
+
+impl Spanned<Pattern> {
+	/// Returns every variable the pattern binds, in order of appearance.
+	#[must_use]
+	pub fn bindings(&self) -> Vec<Spanned<Ident>> {
+		match &self.value {
+			Pattern::Binding(Ident::Discarded)
+			| Pattern::NumberLiteral(_)
+			| Pattern::CharLiteral(_) => Vec::new(),
+			Pattern::Binding(ident) => vec![ident.clone().add_span(self.span)],
+			Pattern::Variant { fields, .. } => fields.iter().flat_map(Self::bindings).collect(),
+		}
+	}
+}
+
+impl std::fmt::Display for Pattern {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Pattern::Binding(ident) => f.write_fmt(format_args!("{ident}")),
+			Pattern::NumberLiteral(x) => f.write_fmt(format_args!("{x}")),
+			Pattern::CharLiteral(x) => f.write_fmt(format_args!("{x:?}")),
+			Pattern::Variant {
+				r#enum,
+				variant,
+				fields,
+			} => f.write_fmt(format_args!(
+				"{enum}::{variant}{}",
+				fields.join_comma_wrapped("(", ")")
+			)),
+		}
+	}
+}
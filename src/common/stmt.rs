@@ -1,6 +1,6 @@
 use crate::common::{
-	expr::Expr, func::Signature, ident::Ident, scope::Scope, span::Spanned,
-	typed_ident::TypedIdent,
+	expr::Expr, func::Signature, ident::Ident, join::Join, r#enum::Variant, r#type::Type,
+	scope::Scope, span::Spanned, typed_ident::TypedIdent,
 };
 
 #[derive(Debug, Clone)]
@@ -10,27 +10,67 @@ pub enum Stmt<Sc: Scope> {
 		mutable: bool,
 		value: Option<Spanned<Expr<Sc>>>,
 	},
+	/// Creates a variable out of every component of the tuple `value`, except
+	/// for the discarded ones.
+	Destructure {
+		ids: Spanned<Vec<Spanned<Ident>>>,
+		mutable: bool,
+		value: Spanned<Expr<Sc>>,
+	},
 	Set {
 		id: Spanned<Ident>,
 		value: Spanned<Expr<Sc>>,
 	},
+	/// Assigns `value` to whatever `ptr` points to.
+	Store {
+		ptr: Spanned<Expr<Sc>>,
+		value: Spanned<Expr<Sc>>,
+	},
 	Func {
 		id: Spanned<Ident>,
 		signature: Signature,
 		body: Option<Spanned<Sc>>,
 	},
+	Struct {
+		id: Spanned<Ident>,
+		generics: Spanned<Vec<Spanned<Ident>>>,
+		fields: Spanned<Vec<Spanned<TypedIdent>>>,
+	},
+	Enum {
+		id: Spanned<Ident>,
+		variants: Spanned<Vec<Spanned<Variant>>>,
+	},
+	/// Declares a value computed at compile time, which may only be numeric.
+	Const {
+		id: Spanned<Ident>,
+		ty: Spanned<Type>,
+		value: Spanned<Expr<Sc>>,
+	},
 	Return {
 		value: Spanned<Expr<Sc>>,
 		is_yield: bool,
 	},
+	/// Leaves the innermost loop, which evaluates to `value` (or to void).
+	Break {
+		value: Option<Spanned<Expr<Sc>>>,
+	},
+	/// Skips to the next iteration of the innermost loop.
+	Continue,
+	/// Makes a module (or a single item of one) available in the current one.
+	Import { path: Spanned<Ident> },
 }
 
 impl<Sc: Scope> Stmt<Sc> {
 	pub fn variant(&self) -> &str {
 		match self {
 			Self::Create { value, .. } => value.as_ref().map_or("declare", |_| "create"),
+			Self::Destructure { .. } => "destructure",
 			Self::Set { .. } => "set",
+			Self::Store { .. } => "store",
 			Self::Func { .. } => "function",
+			Self::Struct { .. } => "struct",
+			Self::Enum { .. } => "enum",
+			Self::Const { .. } => "const",
 			Self::Return { is_yield, .. } => {
 				if *is_yield {
 					"yield"
@@ -38,6 +78,9 @@ impl<Sc: Scope> Stmt<Sc> {
 					"return"
 				}
 			}
+			Self::Break { .. } => "break",
+			Self::Continue => "continue",
+			Self::Import { .. } => "import",
 		}
 	}
 }
@@ -60,7 +103,17 @@ where
 					None => String::new(),
 				}
 			)),
+			Stmt::Destructure {
+				ids,
+				mutable,
+				value,
+			} => f.write_fmt(format_args!(
+				"{}({}) = {value}",
+				if *mutable { "mut " } else { "let " },
+				(&ids.value).join_comma_or_empty()
+			)),
 			Stmt::Set { id, value } => f.write_fmt(format_args!("{id} = {value}")),
+			Stmt::Store { ptr, value } => f.write_fmt(format_args!("*{ptr} = {value}")),
 			Stmt::Func {
 				id,
 				signature,
@@ -72,10 +125,32 @@ where
 					None => ";".into(),
 				}
 			)),
+			Stmt::Struct {
+				id,
+				generics,
+				fields,
+			} => f.write_fmt(format_args!(
+				"struct {id}{} {{{}}}",
+				(&generics.value).join_comma_wrapped("<", ">"),
+				(&fields.value).join_comma_wrapped(" ", " ")
+			)),
+			Stmt::Enum { id, variants } => f.write_fmt(format_args!(
+				"enum {id} {{{}}}",
+				(&variants.value).join_comma_wrapped(" ", " ")
+			)),
+			Stmt::Const { id, ty, value } => {
+				f.write_fmt(format_args!("const {id}: {ty} = {value}"))
+			}
 			Stmt::Return { value, is_yield } => f.write_fmt(format_args!(
 				"{} {value}",
 				if *is_yield { "yield" } else { "return" }
 			)),
+			Stmt::Break { value } => f.write_fmt(format_args!(
+				"break{}",
+				value.as_ref().map_or(String::new(), |x| format!(" {x}"))
+			)),
+			Stmt::Continue => f.write_str("continue"),
+			Stmt::Import { path } => f.write_fmt(format_args!("import {path}")),
 		}
 	}
 }
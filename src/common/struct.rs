@@ -0,0 +1,31 @@
+use super::{join::Join, span::Spanned};
+use crate::common::{ident::Ident, typed_ident::TypedIdent};
+
+#[derive(Debug, Clone)]
+pub struct Struct {
+	/// The name of the struct as written in code. Just like with `Func`, this
+	/// stays unresolved, the resolved id is the key the struct is stored under.
+	pub id: Spanned<Ident>,
+	pub generics: Spanned<Vec<Spanned<Ident>>>,
+	/// The fields of the struct, whose idents are never resolved since they
+	/// can only be accessed through a value of the struct.
+	pub fields: Spanned<Vec<Spanned<TypedIdent>>>,
+}
+
+impl Struct {
+	#[must_use]
+	pub fn field(&self, name: &Ident) -> Option<&Spanned<TypedIdent>> {
+		self.fields.value.iter().find(|x| x.ident() == name)
+	}
+}
+
+impl std::fmt::Display for Struct {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_fmt(format_args!(
+			"struct {}{} {{{}}}",
+			self.id,
+			(&self.generics.value).join_comma_wrapped("<", ">"),
+			(&self.fields.value).join_comma_wrapped(" ", " ")
+		))
+	}
+}
@@ -1,38 +1,57 @@
 use super::span::{Add, Spanned};
 use crate::common::{ident::Ident, join::Join, typed_ident::TypedIdent};
 
-// TODO: figure out Refs and Ptrs, eg:
-//     Ref(Box<Self>, bool), // bool = mut
-//     Ptr(Box<Self>, bool), // bool = mut
-// Ref and Ptr are really similar so they might be merged into a Ptr type
-
-// TODO: figure out optional types (i32?), eg:
-//     Optional(Box<Self>),
-// Optional would have to interop directly with the std, also would be cool to
-// have Result too in some kind of operator
-
-// TODO: function signature types (i32 -> i32), eg:
-//     Function(Box<FuncSignature>),
-// C-style function signature types also work ( void(int, int) ), but are quite
-// bulky and something like i32 -> i32 would be clearer, although it would break
-// the type-then-name rule of the language.
-
-// TODO: we will also have tuples and structs in here at some point
-
 /// A `Type` is the representation of a type in code.
 #[derive(Debug, Clone)]
 pub enum Type {
-	/// A `Type` created by the user, identified with an Ident.
+	/// A `Type` created by the user, such as a struct or a generic, identified
+	/// with an Ident.
 	User(Ident),
 	/// A `Type` that comes inherently with the language.
 	BuiltIn(BuiltIn),
 	/// A `Type` with generics filled in, such as Vec<i32>.
-	Generic(Box<Self>, Vec<Self>),
+	Generic(Box<Spanned<Self>>, Vec<Spanned<Self>>),
+	/// A reference to a value of the inner `Type`, represented by `&<ty>` or,
+	/// if the value can be changed through it, by `&mut <ty>`.
+	Ref(Box<Spanned<Self>>, bool),
+	/// A raw pointer to a value of the inner `Type`, represented by `*<ty>` or
+	/// `*mut <ty>`. Unlike references, these may only be dereferenced in
+	/// unsafe code.
+	Ptr(Box<Spanned<Self>>, bool),
+	/// Either a value of the inner `Type` or nothing at all, represented by
+	/// `<ty>?`.
+	Optional(Box<Spanned<Self>>),
+	/// A function taking `args` and returning `return_ty`, represented by
+	/// `(<ty>, ...) -> <ty>`. The args are always parenthesized so that the type
+	/// still comes before the name, like in `(i32) -> i32 f`. Unsafe functions
+	/// are only ever called through `unsafe (<ty>, ...) -> <ty>`, so that their
+	/// callers know to be unsafe.
+	Func {
+		args: Vec<Spanned<Self>>,
+		return_ty: Box<Spanned<Self>>,
+		is_unsafe: bool,
+	},
+	/// A fixed amount of values of possibly different `Type`s, represented by
+	/// `(<ty>, <ty>, ...)`. Tuples always have at least two components.
+	Tuple(Vec<Spanned<Self>>),
+	/// A fixed amount of values of the inner `Type`, represented by
+	/// `[<ty>; <len>]`.
+	Array(Box<Spanned<Self>>, Spanned<ArrayLen>),
 	/// A `Type` not specified by the user which the inferring algorithm must
 	/// turn into a proper Type.
 	Inferred,
 }
 
+/// The length of an array `Type`, which must be known at compile time.
+#[derive(Debug, Clone)]
+pub enum ArrayLen {
+	/// A length written out as a number literal.
+	Literal(usize),
+	/// A length given by a const, whose value is only known once every const
+	/// has been evaluated.
+	Const(Ident),
+}
+
 impl Spanned<Type> {
 	#[must_use]
 	pub fn add_discarded_ident(self) -> TypedIdent {
@@ -44,8 +63,6 @@ impl Spanned<Type> {
 	}
 }
 
-// TODO: figure out strings and chars
-
 /// A `BuiltIn` is a kind of `Type` that comes with the language itself.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BuiltIn {
@@ -62,6 +79,11 @@ pub enum BuiltIn {
 	/// width of the float, which may only be 16, 32, 64 or 128 bits. The type
 	/// can hold any numeric value.
 	Float { bits: u8 },
+	/// A unicode scalar value, represented by `char`.
+	Char,
+	/// A read-only view of UTF-8 encoded text, represented by `str`. Made out of
+	/// a pointer to the first byte and the amount of bytes.
+	Str,
 	/// A type whose purpose is to denote the absence of value, represented by
 	/// `void`.
 	Void,
@@ -72,18 +94,28 @@ impl BuiltIn {
 	pub fn is_valid(&self) -> bool {
 		match self {
 			Self::Integer { bits, signed: _ } => {
-				bits.map(|x| x < 2u32.pow(23) && x > 0).unwrap_or(true)
+				bits.is_none_or(|x| x < 2u32.pow(23) && x > 0)
 			}
 			Self::Float { bits } => *bits == 16 || *bits == 32 || *bits == 64 || *bits == 128,
-			Self::Void => true,
+			Self::Char | Self::Str | Self::Void => true,
 		}
 	}
 
+	/// Returns whether the type is an integer or a float, which number
+	/// literals can become.
+	#[must_use]
+	pub fn is_numeric(&self) -> bool {
+		matches!(self, Self::Integer { .. } | Self::Float { .. })
+	}
+
 	#[must_use]
 	pub fn width(&self) -> Option<u32> {
 		match self {
 			Self::Integer { bits, signed: _ } => *bits,
 			Self::Float { bits } => Some(u32::from(*bits)),
+			Self::Char => Some(32),
+			// twice the pointer width, which depends on the target
+			Self::Str => None,
 			Self::Void => Some(0),
 		}
 	}
@@ -92,6 +124,8 @@ impl BuiltIn {
 	pub fn from_name(name: &str) -> Option<BuiltIn> {
 		let result = match name {
 			"void" => Some(BuiltIn::Void),
+			"char" => Some(BuiltIn::Char),
+			"str" => Some(BuiltIn::Str),
 			"bool" => Some(BuiltIn::Integer {
 				bits: Some(1),
 				signed: false,
@@ -153,20 +187,49 @@ impl std::fmt::Display for Type {
 			Self::Generic(x, g) => {
 				f.write_fmt(format_args!("{x}{}", g.join_comma_wrapped("<", ">")))
 			}
+			Self::Ref(x, mutable) => {
+				f.write_fmt(format_args!("&{}{x}", if *mutable { "mut " } else { "" }))
+			}
+			Self::Ptr(x, mutable) => {
+				f.write_fmt(format_args!("*{}{x}", if *mutable { "mut " } else { "" }))
+			}
+			Self::Optional(x) => f.write_fmt(format_args!("{x}?")),
+			Self::Func {
+				args,
+				return_ty,
+				is_unsafe,
+			} => f.write_fmt(format_args!(
+				"{}({}) -> {return_ty}",
+				if *is_unsafe { "unsafe " } else { "" },
+				args.join_comma_or_empty()
+			)),
+			Self::Tuple(x) => f.write_fmt(format_args!("({})", x.join_comma_or_empty())),
+			Self::Array(x, len) => f.write_fmt(format_args!("[{x}; {len}]")),
 			Self::Inferred => f.write_str("_"),
 		}
 	}
 }
 
+impl std::fmt::Display for ArrayLen {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Literal(x) => f.write_fmt(format_args!("{x}")),
+			Self::Const(x) => f.write_fmt(format_args!("{x}")),
+		}
+	}
+}
+
 impl std::fmt::Display for BuiltIn {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
 			Self::Integer { bits, signed } => f.write_fmt(format_args!(
 				"{}{}",
 				if *signed { "i" } else { "u" },
-				bits.map(|x| x.to_string()).unwrap_or("size".to_string())
+				bits.map_or("size".to_string(), |x| x.to_string())
 			)),
 			Self::Float { bits } => f.write_fmt(format_args!("f{bits}")),
+			Self::Char => f.write_str("char"),
+			Self::Str => f.write_str("str"),
 			Self::Void => f.write_str("void"),
 		}
 	}
@@ -0,0 +1,160 @@
+//! Walks through syntax trees, so that passes which only look at a few kinds
+//! of exprs and stmts don't have to spell out how to get through the others.
+
+use super::{
+	expr::Expr, ident::Ident, pattern::Pattern, scope::Scope, span::Spanned, stmt::Stmt,
+};
+
+/// A pass over a syntax tree made of scopes of type `Sc`. Every method goes
+/// through what its node holds by default; a pass overriding one to handle
+/// some nodes itself calls the matching `walk_*` function for the rest.
+pub trait Visit<'a, Sc: Scope + 'a> {
+	/// Visits a scope nested in an expr or a stmt. Only the pass knows whether
+	/// the kind of scope it walks holds items besides its stmts, which
+	/// `walk_scope` goes through.
+	fn scope(&mut self, scope: &'a Sc);
+
+	fn stmt(&mut self, stmt: &'a Spanned<Stmt<Sc>>) {
+		walk_stmt(self, stmt);
+	}
+
+	fn expr(&mut self, expr: &'a Spanned<Expr<Sc>>) {
+		walk_expr(self, expr);
+	}
+
+	/// Visits the body of a `while` or `for` loop, which may run any number of
+	/// times.
+	fn loop_body(&mut self, body: &'a Spanned<Expr<Sc>>) {
+		self.expr(body);
+	}
+
+	/// Visits the var a `for` loop binds, before anything else in the loop.
+	fn loop_var(&mut self, _var: &'a Spanned<Ident>) {}
+
+	/// Visits the pattern of a match arm, before the value of the arm.
+	fn pattern(&mut self, _pattern: &'a Spanned<Pattern>) {}
+}
+
+pub fn walk_scope<'a, Sc: Scope + 'a, V: Visit<'a, Sc> + ?Sized>(visitor: &mut V, scope: &'a Sc) {
+	for stmt in scope.stmts() {
+		visitor.stmt(stmt);
+	}
+}
+
+pub fn walk_stmt<'a, Sc: Scope + 'a, V: Visit<'a, Sc> + ?Sized>(
+	visitor: &mut V,
+	stmt: &'a Spanned<Stmt<Sc>>,
+) {
+	match &stmt.value {
+		Stmt::Create { value, .. } | Stmt::Break { value } => {
+			if let Some(value) = value {
+				visitor.expr(value);
+			}
+		}
+		Stmt::Destructure { value, .. }
+		| Stmt::Set { value, .. }
+		| Stmt::Const { value, .. }
+		| Stmt::Return { value, .. } => visitor.expr(value),
+		Stmt::Store { ptr, value } => {
+			visitor.expr(ptr);
+			visitor.expr(value);
+		}
+		Stmt::Func { body, .. } => {
+			if let Some(body) = body {
+				visitor.scope(&body.value);
+			}
+		}
+		Stmt::Struct { .. } | Stmt::Enum { .. } | Stmt::Continue | Stmt::Import { .. } => {}
+	}
+}
+
+pub fn walk_expr<'a, Sc: Scope + 'a, V: Visit<'a, Sc> + ?Sized>(
+	visitor: &mut V,
+	expr: &'a Spanned<Expr<Sc>>,
+) {
+	match &expr.value {
+		Expr::NumberLiteral(_)
+		| Expr::StringLiteral(_)
+		| Expr::CharLiteral(_)
+		| Expr::None
+		| Expr::Identifier(_)
+		| Expr::Variant { .. } => {}
+		Expr::BinaryOp(lhs, _, rhs) => {
+			visitor.expr(lhs);
+			visitor.expr(rhs);
+		}
+		Expr::UnaryOp(_, value)
+		| Expr::Field { value, .. }
+		| Expr::TupleField { value, .. }
+		| Expr::Ref { value, .. }
+		| Expr::Deref(value)
+		| Expr::Try(value)
+		| Expr::Cast { value, .. } => visitor.expr(value),
+		Expr::Tuple(values) | Expr::Array(values) => {
+			for value in values {
+				visitor.expr(value);
+			}
+		}
+		Expr::Index { value, index } => {
+			visitor.expr(value);
+			visitor.expr(index);
+		}
+		Expr::Scope(scope) | Expr::Unsafe(scope) => visitor.scope(scope),
+		Expr::Closure { body, .. } => visitor.scope(&body.value),
+		Expr::Call { callee, args, .. } => {
+			visitor.expr(callee);
+			for arg in args {
+				visitor.expr(arg);
+			}
+		}
+		Expr::Construct { fields, .. } => {
+			for (_, value) in fields {
+				visitor.expr(value);
+			}
+		}
+		Expr::If {
+			cond,
+			then,
+			otherwise,
+		} => {
+			visitor.expr(cond);
+			visitor.expr(then);
+			if let Some(otherwise) = otherwise {
+				visitor.expr(otherwise);
+			}
+		}
+		Expr::While {
+			cond,
+			body,
+			otherwise,
+		} => {
+			visitor.expr(cond);
+			visitor.loop_body(body);
+			if let Some(otherwise) = otherwise {
+				visitor.expr(otherwise);
+			}
+		}
+		Expr::For {
+			var,
+			start,
+			end,
+			body,
+			otherwise,
+		} => {
+			visitor.loop_var(var);
+			visitor.expr(start);
+			visitor.expr(end);
+			visitor.loop_body(body);
+			if let Some(otherwise) = otherwise {
+				visitor.expr(otherwise);
+			}
+		}
+		Expr::Match { value, arms } => {
+			visitor.expr(value);
+			for (pattern, value) in arms {
+				visitor.pattern(pattern);
+				visitor.expr(value);
+			}
+		}
+	}
+}
@@ -1,16 +1,19 @@
 use crate::{
 	common::{
+		r#const::Const,
 		expr::Expr,
 		func::Func,
+		r#enum::Enum,
 		ident::Ident,
 		r#type::Type,
+		r#struct::Struct,
 		scope::Scope,
 		span::{Add, Spanned},
 		stmt::Stmt,
 	},
 	parser::types::{ParserExpr, ParserScope, ParserStmt},
 };
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 #[derive(Debug, Clone)]
 pub struct Var {
@@ -19,10 +22,15 @@ pub struct Var {
 }
 
 // NOTE: the Spans that these Spanned<T> hold are the declaration spans
+// NOTE: items are kept in the order they were declared in, which every later
+// step walks them in, so that ids and output don't change from run to run
 #[derive(Default, Debug, Clone)]
 pub struct HoistedScopeData {
-	pub vars: HashMap<Ident, Spanned<Var>>,
-	pub funcs: HashMap<Ident, Spanned<HoistedFunc>>,
+	pub vars: IndexMap<Ident, Spanned<Var>>,
+	pub funcs: IndexMap<Ident, Spanned<HoistedFunc>>,
+	pub structs: IndexMap<Ident, Spanned<Struct>>,
+	pub enums: IndexMap<Ident, Spanned<Enum>>,
+	pub consts: IndexMap<Ident, Spanned<HoistedConst>>,
 }
 
 impl std::ops::Add for HoistedScopeData {
@@ -35,6 +43,15 @@ impl std::ops::Add for HoistedScopeData {
 		for (k, v) in self.funcs {
 			rhs.funcs.insert(k, v);
 		}
+		for (k, v) in self.structs {
+			rhs.structs.insert(k, v);
+		}
+		for (k, v) in self.enums {
+			rhs.enums.insert(k, v);
+		}
+		for (k, v) in self.consts {
+			rhs.consts.insert(k, v);
+		}
 		rhs
 	}
 }
@@ -53,6 +70,18 @@ impl HoistedScope {
 	pub fn add_func(&mut self, ident: Ident, func: Spanned<HoistedFunc>) {
 		self.data.funcs.insert(ident, func);
 	}
+
+	pub fn add_struct(&mut self, ident: Ident, r#struct: Spanned<Struct>) {
+		self.data.structs.insert(ident, r#struct);
+	}
+
+	pub fn add_enum(&mut self, ident: Ident, r#enum: Spanned<Enum>) {
+		self.data.enums.insert(ident, r#enum);
+	}
+
+	pub fn add_const(&mut self, ident: Ident, r#const: Spanned<HoistedConst>) {
+		self.data.consts.insert(ident, r#const);
+	}
 }
 
 impl Scope for HoistedScope {
@@ -65,6 +94,15 @@ impl std::fmt::Display for HoistedScope {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		self.my_fmt(f)?;
 		f.write_str("\n")?;
+		for (id, r#struct) in &self.data.structs {
+			f.write_fmt(format_args!("\t{id} => {struct}\n"))?;
+		}
+		for (id, r#enum) in &self.data.enums {
+			f.write_fmt(format_args!("\t{id} => {enum}\n"))?;
+		}
+		for (id, r#const) in &self.data.consts {
+			f.write_fmt(format_args!("\t{id} => {const}\n"))?;
+		}
 		for (id, func) in &self.data.funcs {
 			let func = format!("{id} => {func}")
 				.split('\n')
@@ -80,6 +118,7 @@ impl std::fmt::Display for HoistedScope {
 pub type HoistedExpr = Expr<HoistedScope>;
 pub type HoistedStmt = Stmt<HoistedScope>;
 pub type HoistedFunc = Func<HoistedScope>;
+pub type HoistedConst = Const<HoistedScope>;
 
 trait Hoist {
 	type Output;
@@ -128,13 +167,34 @@ impl<T: Hoist> Hoist for Vec<T> {
 impl Hoist for ParserExpr {
 	type Output = HoistedExpr;
 
+	// every arm only rebuilds its kind of expr out of hoisted parts, which is
+	// plumbing that wouldn't read any better spread over several functions
+	#[allow(clippy::too_many_lines)]
 	fn hoist(&self) -> Self::Output {
 		match self {
 			Expr::NumberLiteral(x) => Expr::NumberLiteral(x.clone()),
+			Expr::StringLiteral(x) => Expr::StringLiteral(x.clone()),
+			Expr::CharLiteral(x) => Expr::CharLiteral(*x),
+			Expr::None => Expr::None,
 			Expr::Identifier(x) => Expr::Identifier(x.clone()),
+			Expr::Variant { r#enum, variant } => Expr::Variant {
+				r#enum: r#enum.clone(),
+				variant: variant.clone(),
+			},
 			Expr::BinaryOp(lhs, op, rhs) => Expr::BinaryOp(lhs.hoist(), *op, rhs.hoist()),
 			Expr::UnaryOp(op, value) => Expr::UnaryOp(*op, value.hoist()),
+			Expr::Ref { value, mutable } => Expr::Ref {
+				value: value.hoist(),
+				mutable: *mutable,
+			},
+			Expr::Deref(value) => Expr::Deref(value.hoist()),
+			Expr::Try(value) => Expr::Try(value.hoist()),
+			Expr::Cast { value, ty } => Expr::Cast {
+				value: value.hoist(),
+				ty: ty.clone(),
+			},
 			Expr::Scope(scope) => Expr::Scope(scope.hoist()),
+			Expr::Unsafe(scope) => Expr::Unsafe(scope.hoist()),
 			Expr::Call {
 				callee,
 				generics,
@@ -144,6 +204,74 @@ impl Hoist for ParserExpr {
 				generics: generics.clone(),
 				args: args.hoist(),
 			},
+			Expr::Construct { ty, fields } => Expr::Construct {
+				ty: ty.clone(),
+				fields: fields
+					.iter()
+					.map(|(field, value)| (field.clone(), value.hoist()))
+					.collect(),
+			},
+			Expr::If {
+				cond,
+				then,
+				otherwise,
+			} => Expr::If {
+				cond: cond.hoist(),
+				then: then.hoist(),
+				otherwise: otherwise.hoist(),
+			},
+			Expr::While {
+				cond,
+				body,
+				otherwise,
+			} => Expr::While {
+				cond: cond.hoist(),
+				body: body.hoist(),
+				otherwise: otherwise.hoist(),
+			},
+			Expr::For {
+				var,
+				start,
+				end,
+				body,
+				otherwise,
+			} => Expr::For {
+				var: var.clone(),
+				start: start.hoist(),
+				end: end.hoist(),
+				body: body.hoist(),
+				otherwise: otherwise.hoist(),
+			},
+			Expr::Match { value, arms } => Expr::Match {
+				value: value.hoist(),
+				arms: arms
+					.iter()
+					.map(|(pattern, value)| (pattern.clone(), value.hoist()))
+					.collect(),
+			},
+			Expr::Field { value, field } => Expr::Field {
+				value: value.hoist(),
+				field: field.clone(),
+			},
+			Expr::Tuple(values) => Expr::Tuple(values.hoist()),
+			Expr::TupleField { value, index } => Expr::TupleField {
+				value: value.hoist(),
+				index: *index,
+			},
+			Expr::Array(values) => Expr::Array(values.hoist()),
+			Expr::Index { value, index } => Expr::Index {
+				value: value.hoist(),
+				index: index.hoist(),
+			},
+			Expr::Closure {
+				signature,
+				body,
+				captures,
+			} => Expr::Closure {
+				signature: signature.clone(),
+				body: body.hoist(),
+				captures: captures.clone(),
+			},
 		}
 	}
 }
@@ -167,10 +295,32 @@ impl HoistWithScope for Spanned<ParserStmt> {
 					value: value.hoist(),
 				})
 			}
+			Stmt::Destructure {
+				ids,
+				mutable,
+				value,
+			} => {
+				for id in ids.value.iter().filter(|x| !x.value.is_discarded()) {
+					let var = Var {
+						ty: Type::Inferred,
+						mutable,
+					};
+					scope.add_var(id.value.clone(), var.add_span(id.span));
+				}
+				Some(Stmt::Destructure {
+					ids,
+					mutable,
+					value: value.hoist(),
+				})
+			}
 			Stmt::Set { id, value } => Some(Stmt::Set {
 				id,
 				value: value.hoist(),
 			}),
+			Stmt::Store { ptr, value } => Some(Stmt::Store {
+				ptr: ptr.hoist(),
+				value: value.hoist(),
+			}),
 			Stmt::Func {
 				id,
 				signature,
@@ -187,10 +337,44 @@ impl HoistWithScope for Spanned<ParserStmt> {
 				); // TODO: add the correct span
 				None
 			}
+			Stmt::Struct {
+				id,
+				generics,
+				fields,
+			} => {
+				scope.add_struct(
+					id.value.clone(),
+					Struct {
+						id,
+						generics,
+						fields,
+					}
+					.add_span(self.span),
+				);
+				None
+			}
+			Stmt::Enum { id, variants } => {
+				scope.add_enum(id.value.clone(), Enum { id, variants }.add_span(self.span));
+				None
+			}
+			Stmt::Const { id, ty, value } => {
+				let r#const = Const {
+					id: id.clone(),
+					ty,
+					value: value.hoist(),
+				};
+				scope.add_const(id.value, r#const.add_span(self.span));
+				None
+			}
 			Stmt::Return { value, is_yield } => Some(Stmt::Return {
 				value: value.hoist(),
 				is_yield,
 			}),
+			Stmt::Break { value } => Some(Stmt::Break {
+				value: value.hoist(),
+			}),
+			Stmt::Continue => Some(Stmt::Continue),
+			Stmt::Import { path } => Some(Stmt::Import { path }),
 		}
 		.map(|x| x.add_span(self.span))
 	}
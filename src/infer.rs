@@ -1,44 +1,948 @@
 // sincere thanks to https://play.rust-lang.org/?version=stable&mode=debug&edition=2018&gist=174ca95a8b938168764846e97d5e9a2c
 
-use self::{engine::Engine, mappings::Mappings, to_info::ToInfo, type_info::TypeInfo};
+use self::{
+	engine::Engine,
+	mappings::Mappings,
+	to_info::ToInfo,
+	type_info::{TypeId, TypeInfo},
+	typed::Typed,
+};
 use crate::{
 	common::{
+		diagnostics::{
+			duplicate_field, immutable_pointee, index_out_of_bounds, invalid_array_len,
+			missing_fields, non_exhaustive_match, non_integer_index, non_integer_range,
+			nonexistent_field, nonexistent_tuple_field, nonexistent_variant, not_a_pointer,
+			not_a_struct, not_a_tuple, not_an_array, not_an_optional, not_callable, recursive_enum,
+			recursive_struct, try_outside_optional, uninferred_type, wrong_arg_count,
+			wrong_generic_count, wrong_payload_count,
+		},
 		expr::Expr,
 		func::Signature,
-		r#type::{BuiltIn, Type},
-		span::{Add, Spanned},
+		ident::{Id, Ident},
+		pattern::Pattern,
+		r#enum::Enum,
+		r#struct::Struct,
+		r#type::{ArrayLen, BuiltIn, Type},
+		span::{Add, Span, Spanned},
 		stmt::Stmt,
 	},
-	hoister::{HoistedExpr, HoistedFunc, HoistedScope},
-	lexer::NumberLiteralType,
+	hoister::{HoistedConst, HoistedExpr, HoistedFunc, HoistedScope},
+	lexer::{NumberLiteral, NumberLiteralType, Operator},
+	session::CompilerSession,
 };
-use lazy_static::lazy_static;
-use std::sync::{Mutex, MutexGuard};
+use indexmap::IndexMap;
+use std::collections::HashMap;
 
+mod cast;
+mod consts;
 pub mod engine;
+mod exhaustiveness;
+mod literals;
 pub mod mappings;
 pub mod to_info;
 pub mod type_info;
+pub mod typed;
+
+
+/// Creates an instance of the struct `base`, filling in its generics with
+/// `generics` or with unknown types if they weren't specified.
+fn instantiate(
+	base: &Spanned<Type>,
+	generics: Option<Vec<Spanned<TypeId>>>,
+	span: Span,
+	mappings: &Mappings,
+) -> TypeInfo {
+	let Type::User(x) = &base.value else {
+		wrong_generic_count(mappings.session, span, 0, generics.map_or(0, |x| x.len()));
+		return TypeInfo::Bottom;
+	};
+	let Some(r#struct) = mappings.get_struct(x.id()) else {
+		wrong_generic_count(mappings.session, span, 0, generics.map_or(0, |x| x.len()));
+		return TypeInfo::Bottom;
+	};
+	let generics = match generics {
+		Some(generics) if generics.len() != r#struct.generics.value.len() => {
+			wrong_generic_count(
+				mappings.session,
+				span,
+				r#struct.generics.value.len(),
+				generics.len(),
+			);
+			return TypeInfo::Bottom;
+		}
+		Some(generics) => generics,
+		None => r#struct
+			.generics
+			.value
+			.iter()
+			.map(|_| mappings.engine().add_ty(TypeInfo::Unknown).add_span(span))
+			.collect(),
+	};
+	TypeInfo::Struct {
+		id: x.id(),
+		name: r#struct.id.value.to_string(),
+		generics,
+	}
+}
+
+/// Converts the type of a field of `r#struct`, replacing the generics of the
+/// struct with the ones of the instance being used.
+fn field_ty(
+	ty: &Spanned<Type>,
+	r#struct: &Struct,
+	generics: &[Spanned<TypeId>],
+	mappings: &mut Mappings,
+) -> Spanned<TypeId> {
+	match &ty.value {
+		Type::User(x) => {
+			let generic = r#struct
+				.generics
+				.value
+				.iter()
+				.position(|generic| generic.value == *x);
+			match generic {
+				// reuse the instance's type so that whatever we learn about the field also
+				// applies to the instance
+				Some(i) => generics[i],
+				None => ty.convert_and_add(mappings),
+			}
+		}
+		Type::Generic(base, args) => {
+			let args = args
+				.iter()
+				.map(|x| field_ty(x, r#struct, generics, mappings))
+				.collect();
+			let info = instantiate(base, Some(args), ty.span, mappings);
+			mappings.engine().add_ty(info).add_span(ty.span)
+		}
+		Type::Ref(inner, mutable) => {
+			let inner = field_ty(inner, r#struct, generics, mappings);
+			mappings
+				.engine()
+				.add_ty(TypeInfo::Ref(inner, *mutable))
+				.add_span(ty.span)
+		}
+		Type::Ptr(inner, mutable) => {
+			let inner = field_ty(inner, r#struct, generics, mappings);
+			mappings
+				.engine()
+				.add_ty(TypeInfo::Ptr(inner, *mutable))
+				.add_span(ty.span)
+		}
+		Type::Optional(inner) => {
+			let inner = field_ty(inner, r#struct, generics, mappings);
+			mappings.engine().add_ty(TypeInfo::Optional(inner)).add_span(ty.span)
+		}
+		Type::Func {
+			args,
+			return_ty,
+			is_unsafe,
+		} => {
+			let args = args
+				.iter()
+				.map(|x| field_ty(x, r#struct, generics, mappings))
+				.collect();
+			let return_ty = field_ty(return_ty, r#struct, generics, mappings);
+			let signature = TypeInfo::FuncSignature {
+				return_ty,
+				args,
+				generics: Vec::new(),
+				is_unsafe: *is_unsafe,
+			};
+			mappings.engine().add_ty(signature).add_span(ty.span)
+		}
+		Type::Array(inner, len) => {
+			let inner = field_ty(inner, r#struct, generics, mappings);
+			let info = array_len(len, mappings)
+				.map_or(TypeInfo::Bottom, |len| TypeInfo::Array(inner, len));
+			mappings.engine().add_ty(info).add_span(ty.span)
+		}
+		Type::Tuple(tys) => {
+			let tys = tys
+				.iter()
+				.map(|x| field_ty(x, r#struct, generics, mappings))
+				.collect();
+			mappings.engine().add_ty(TypeInfo::Tuple(tys)).add_span(ty.span)
+		}
+		Type::BuiltIn(_) | Type::Inferred => ty.convert_and_add(mappings),
+	}
+}
+
+/// Gets the length of an array type, reporting lengths given by a const whose
+/// value isn't a valid length.
+fn array_len(len: &Spanned<ArrayLen>, mappings: &Mappings) -> Option<usize> {
+	match &len.value {
+		ArrayLen::Literal(x) => Some(*x),
+		// a const that couldn't be evaluated was already reported
+		ArrayLen::Const(x) => {
+			let value = mappings.get_const(x.id())?;
+			let result = value.as_integer().and_then(|x| usize::try_from(x).ok());
+			if result.is_none() {
+				invalid_array_len(mappings.session, len.span, &value.to_string());
+			}
+			result
+		}
+	}
+}
 
-lazy_static! {
-	static ref ENGINE: Mutex<Engine> = Mutex::new(Engine::default());
+/// Gets the declaration of the struct behind `ty` along with the generics of
+/// the instance, reporting an error if it isn't a struct.
+fn as_struct(ty: Spanned<TypeId>, mappings: &Mappings) -> Option<(Struct, Vec<Spanned<TypeId>>)> {
+	let info = mappings.engine().follow(ty.value).clone();
+	match info {
+		TypeInfo::Struct { id, generics, .. } => {
+			Some((mappings.get_struct(id).unwrap().clone(), generics))
+		}
+		// an error was already reported
+		TypeInfo::Bottom => None,
+		TypeInfo::Unknown | TypeInfo::UnknownGeneric(_) => {
+			uninferred_type(mappings.session, ty.span);
+			None
+		}
+		info => {
+			not_a_struct(mappings.session, ty.span, &info.display(&mappings.engine()));
+			None
+		}
+	}
+}
+
+/// Returns the type of the component `index` of the tuple `value`.
+fn tuple_field(
+	value: &Spanned<HoistedExpr>,
+	index: Spanned<usize>,
+	mappings: &mut Mappings,
+) -> TypeInfo {
+	let tuple = value.convert_and_add(mappings);
+	let info = mappings.engine().follow(tuple.value).clone();
+	match info {
+		TypeInfo::Tuple(ref components) => {
+			if let Some(component) = components.get(index.value) {
+				TypeInfo::SameAs(*component)
+			} else {
+				let ty = info.display(&mappings.engine());
+				nonexistent_tuple_field(
+					mappings.session,
+					index.span,
+					index.value,
+					&ty,
+					components.len(),
+				);
+				TypeInfo::Bottom
+			}
+		}
+		// an error was already reported
+		TypeInfo::Bottom => TypeInfo::Bottom,
+		TypeInfo::Unknown | TypeInfo::UnknownGeneric(_) => {
+			uninferred_type(mappings.session, value.span);
+			TypeInfo::Bottom
+		}
+		info => {
+			not_a_tuple(mappings.session, value.span, &info.display(&mappings.engine()));
+			TypeInfo::Bottom
+		}
+	}
 }
 
-pub fn engine<'a>() -> MutexGuard<'a, Engine> {
-	ENGINE.lock().unwrap()
+/// Infers an array literal, whose elements must all be of the same type.
+/// `span` is the span of the whole expression.
+fn array(values: &[Spanned<HoistedExpr>], span: Span, mappings: &mut Mappings) -> TypeInfo {
+	let values: Vec<_> = values.iter().map(|x| x.convert_and_add(mappings)).collect();
+	let Some((first, rest)) = values.split_first() else {
+		// nothing tells us the type of the elements of an empty array yet
+		let element = mappings.engine().add_ty(TypeInfo::Unknown).add_span(span);
+		return TypeInfo::Array(element, 0);
+	};
+	for value in rest {
+		mappings.engine().unify_custom_error(
+			mappings.session,
+			*first,
+			*value,
+			"type conflict: array elements have different types",
+			&[],
+		);
+	}
+	TypeInfo::Array(*first, values.len())
+}
+
+/// Returns the type of the element of the array `value` at `index`, which
+/// may be of any integer type. Literal indices, negated or not, are checked
+/// against the length of the array.
+fn index(
+	value: &Spanned<HoistedExpr>,
+	index: &Spanned<HoistedExpr>,
+	mappings: &mut Mappings,
+) -> TypeInfo {
+	let array = value.convert_and_add(mappings);
+	let index_ty = index.convert_and_add(mappings);
+	mappings.insert_index(index_ty);
+	let info = mappings.engine().follow(array.value).clone();
+	match info {
+		TypeInfo::Array(element, len) => {
+			let literal = match &index.value {
+				Expr::NumberLiteral(x) => Some((x, false)),
+				Expr::UnaryOp(op, value) if op.value == Operator::Neg => match &value.value {
+					Expr::NumberLiteral(x) => Some((x, true)),
+					_ => None,
+				},
+				_ => None,
+			};
+			if let Some((x, negated)) = literal {
+				// float literals are reported once the index is known not to be an
+				// integer
+				let in_bounds = x.as_u128().map(|value| {
+					let value = usize::try_from(value);
+					(!negated || value == Ok(0)) && value.is_ok_and(|value| value < len)
+				});
+				if in_bounds == Some(false) {
+					let sign = if negated { "-" } else { "" };
+					let index_value = format!("{sign}{}", x.value);
+					index_out_of_bounds(mappings.session, index.span, &index_value, len);
+				}
+			}
+			TypeInfo::SameAs(element)
+		}
+		// an error was already reported
+		TypeInfo::Bottom => TypeInfo::Bottom,
+		TypeInfo::Unknown | TypeInfo::UnknownGeneric(_) => {
+			uninferred_type(mappings.session, value.span);
+			TypeInfo::Bottom
+		}
+		info => {
+			not_an_array(mappings.session, value.span, &info.display(&mappings.engine()));
+			TypeInfo::Bottom
+		}
+	}
+}
+
+/// Infers destructuring `value` into the vars `ids`, which it must have as
+/// many components as.
+fn destructure(
+	ids: &Spanned<Vec<Spanned<Ident>>>,
+	value: &Spanned<HoistedExpr>,
+	mappings: &mut Mappings,
+) {
+	let value_ty = value.convert_and_add(mappings);
+	let mut components = Vec::new();
+	for id in &ids.value {
+		components.push(match id.value {
+			Ident::Resolved(x) => mappings.get_var_ty(x).value.add_span(id.span),
+			_ => mappings.engine().add_ty(TypeInfo::Unknown).add_span(id.span),
+		});
+	}
+	let pattern = mappings
+		.engine()
+		.add_ty(TypeInfo::Tuple(components))
+		.add_span(ids.span);
+	mappings.engine().unify_custom_error(
+		mappings.session,
+		pattern,
+		value_ty,
+		"type conflict: value can't be destructured into these variables",
+		&[],
+	);
+}
+
+/// Infers the construction of a struct, checking that every field is given a
+/// value exactly once.
+fn construct(
+	ty: &Spanned<Type>,
+	fields: &[(Spanned<Ident>, Spanned<HoistedExpr>)],
+	mappings: &mut Mappings,
+) -> TypeInfo {
+	let ty = ty.convert_and_add(mappings);
+	let Some((r#struct, generics)) = as_struct(ty, mappings) else {
+		return TypeInfo::Bottom;
+	};
+	let mut given: HashMap<&Ident, Span> = HashMap::new();
+	for (field, value) in fields {
+		let value_ty = value.convert_and_add(mappings);
+		let Some(declared) = r#struct.field(&field.value) else {
+			nonexistent_field(
+				mappings.session,
+				field.span,
+				&field.value,
+				&r#struct.id.value.to_string(),
+			);
+			continue;
+		};
+		if let Some(first) = given.insert(&field.value, field.span) {
+			duplicate_field(mappings.session, field.span, first);
+			continue;
+		}
+		let field_ty = field_ty(&declared.value.ty, &r#struct, &generics, mappings);
+		let value_ty = coerce(field_ty, value_ty, mappings);
+		mappings.engine().unify(mappings.session, field_ty, value_ty);
+	}
+	let missing: Vec<String> = r#struct
+		.fields
+		.value
+		.iter()
+		.filter(|x| !given.contains_key(x.ident()))
+		.map(|x| format!("'{}'", x.ident()))
+		.collect();
+	if !missing.is_empty() {
+		missing_fields(mappings.session, ty.span, &r#struct.id.value.to_string(), &missing);
+	}
+	TypeInfo::SameAs(ty)
+}
+
+/// Gets the type `ptr` points to, along with whether it can be changed through
+/// `ptr`, reporting an error if it isn't a reference or pointer.
+fn pointee(ptr: Spanned<TypeId>, mappings: &Mappings) -> Option<(Spanned<TypeId>, bool)> {
+	let info = mappings.engine().follow(ptr.value).clone();
+	match info {
+		TypeInfo::Ref(pointee, mutable) | TypeInfo::Ptr(pointee, mutable) => Some((pointee, mutable)),
+		// an error was already reported
+		TypeInfo::Bottom => None,
+		TypeInfo::Unknown | TypeInfo::UnknownGeneric(_) => {
+			uninferred_type(mappings.session, ptr.span);
+			None
+		}
+		info => {
+			not_a_pointer(mappings.session, ptr.span, &info.display(&mappings.engine()));
+			None
+		}
+	}
+}
+
+/// Gets the type inside the optional `ty`, reporting an error if it isn't an
+/// optional.
+fn optional_inner(ty: Spanned<TypeId>, mappings: &Mappings) -> Option<Spanned<TypeId>> {
+	let info = mappings.engine().follow(ty.value).clone();
+	match info {
+		TypeInfo::Optional(inner) => Some(inner),
+		// an error was already reported
+		TypeInfo::Bottom => None,
+		TypeInfo::Unknown | TypeInfo::UnknownGeneric(_) => {
+			uninferred_type(mappings.session, ty.span);
+			None
+		}
+		info => {
+			not_an_optional(mappings.session, ty.span, &info.display(&mappings.engine()));
+			None
+		}
+	}
+}
+
+/// Lets `value` be used where a value of type `target` is expected, which
+/// wraps it into an optional if needed. Returns the type to unify `target`
+/// with.
+fn coerce(
+	target: Spanned<TypeId>,
+	value: Spanned<TypeId>,
+	mappings: &mut Mappings,
+) -> Spanned<TypeId> {
+	let coerced = mappings.engine().coerce(target, value);
+	if let Some(coerced) = coerced {
+		mappings.insert_coercion(value.span);
+		coerced
+	} else {
+		value
+	}
+}
+
+/// Infers `value?`, which is only allowed inside of functions returning an
+/// optional. A closure whose return type isn't known yet is made to return one.
+fn try_expr(value: &Spanned<HoistedExpr>, span: Span, mappings: &mut Mappings) -> TypeInfo {
+	let value_ty = value.convert_and_add(mappings);
+	let return_ty = mappings.return_ty();
+	let return_info = return_ty.map(|x| mappings.engine().follow(x.value).clone());
+	match return_info {
+		Some(TypeInfo::Optional(_) | TypeInfo::Bottom) => {}
+		Some(TypeInfo::Unknown) => {
+			let return_ty = return_ty.unwrap();
+			let inner = mappings.engine().add_ty(TypeInfo::Unknown).add_span(span);
+			let optional = TypeInfo::Optional(inner);
+			let optional = mappings.engine().add_ty(optional).add_span(span);
+			mappings.engine().unify(mappings.session, return_ty, optional);
+		}
+		Some(info) => try_outside_optional(
+			mappings.session,
+			span,
+			Some(&info.display(&mappings.engine())),
+		),
+		None => try_outside_optional(mappings.session, span, None),
+	}
+	optional_inner(value_ty, mappings).map_or(TypeInfo::Bottom, TypeInfo::SameAs)
+}
+
+/// Infers `*ptr = value`, which requires `ptr` to allow changing its pointee.
+fn store(ptr: &Spanned<HoistedExpr>, value: &Spanned<HoistedExpr>, mappings: &mut Mappings) {
+	let ptr_ty = ptr.convert_and_add(mappings);
+	let value_ty = value.convert_and_add(mappings);
+	if let Some((pointee, mutable)) = pointee(ptr_ty, mappings) {
+		if !mutable {
+			let info = mappings.engine().follow(ptr_ty.value).clone();
+			let ty = info.display(&mappings.engine());
+			immutable_pointee(mappings.session, ptr.span, &ty);
+		}
+		let value_ty = coerce(pointee, value_ty, mappings);
+		mappings.engine().unify(mappings.session, pointee, value_ty);
+	}
+}
+
+/// Adds a new `bool` type to the engine.
+fn bool_ty(span: Span, mappings: &Mappings) -> Spanned<TypeId> {
+	mappings
+		.engine()
+		.add_ty(TypeInfo::BuiltIn(BuiltIn::Integer {
+			bits: Some(1),
+			signed: false,
+		}))
+		.add_span(span)
+}
+
+/// Infers an if expression, whose condition must be a `bool`. Without an else
+/// branch, the expression is void.
+fn if_expr(
+	cond: &Spanned<HoistedExpr>,
+	then: &Spanned<HoistedExpr>,
+	otherwise: Option<&Spanned<HoistedExpr>>,
+	span: Span,
+	mappings: &mut Mappings,
+) -> TypeInfo {
+	let cond = cond.convert_and_add(mappings);
+	let bool_ty = bool_ty(cond.span, mappings);
+	mappings.engine().unify_custom_error(
+		mappings.session,
+		bool_ty,
+		cond,
+		"type conflict: condition is not a bool",
+		&[],
+	);
+	let then = then.convert_and_add(mappings);
+	if let Some(otherwise) = otherwise {
+		let otherwise = otherwise.convert_and_add(mappings);
+		mappings.engine().unify_custom_error(
+			mappings.session,
+			then,
+			otherwise,
+			"type conflict: if and else branches have different types",
+			&[],
+		);
+		// a branch that returns has no type of its own, so we take the other one
+		if *mappings.engine().follow(then.value) == TypeInfo::Bottom {
+			TypeInfo::SameAs(otherwise)
+		} else {
+			TypeInfo::SameAs(then)
+		}
+	} else {
+		let void = mappings
+			.engine()
+			.add_ty(TypeInfo::BuiltIn(BuiltIn::Void))
+			.add_span(span);
+		mappings.engine().unify_custom_error(
+			mappings.session,
+			void,
+			then,
+			"type conflict: if without else has a value",
+			&["an if without an else has no value when its condition is false, so it must be void"],
+		);
+		TypeInfo::BuiltIn(BuiltIn::Void)
+	}
+}
+
+/// Infers the body of a loop along with its else branch. The loop evaluates to
+/// the values it is broken out of with, which must match its else branch (or
+/// be void, if there's none).
+fn loop_body(
+	body: &Spanned<HoistedExpr>,
+	otherwise: Option<&Spanned<HoistedExpr>>,
+	span: Span,
+	mappings: &mut Mappings,
+) -> TypeInfo {
+	let break_ty = mappings.engine().add_ty(TypeInfo::Unknown).add_span(span);
+	// break stmts deep inside the body need to know what they should break with
+	let outer_break_ty = mappings.replace_break_ty(Some(break_ty));
+	body.convert_and_add(mappings);
+	mappings.replace_break_ty(outer_break_ty);
+	if let Some(otherwise) = otherwise {
+		let otherwise = otherwise.convert_and_add(mappings);
+		mappings.engine().unify_custom_error(
+			mappings.session,
+			break_ty,
+			otherwise,
+			"type conflict: loop breaks with a different type than its else branch",
+			&[],
+		);
+	} else {
+		let void = mappings
+			.engine()
+			.add_ty(TypeInfo::BuiltIn(BuiltIn::Void))
+			.add_span(span);
+		mappings.engine().unify_custom_error(
+			mappings.session,
+			void,
+			break_ty,
+			"type conflict: loop without else has a value",
+			&["a loop without an else has no value when it ends without breaking, so it must be void"],
+		);
+	}
+	TypeInfo::SameAs(break_ty)
+}
+
+/// Infers a for loop, whose bounds must be integers of the same type as the
+/// var going over them.
+fn for_expr(
+	var: &Spanned<Ident>,
+	start: &Spanned<HoistedExpr>,
+	end: &Spanned<HoistedExpr>,
+	(body, otherwise): (&Spanned<HoistedExpr>, Option<&Spanned<HoistedExpr>>),
+	span: Span,
+	mappings: &mut Mappings,
+) -> TypeInfo {
+	let start = start.convert_and_add(mappings);
+	let end = end.convert_and_add(mappings);
+	mappings.engine().unify(mappings.session, start, end);
+	let bound_ty = mappings.engine().follow(start.value).clone();
+	if let TypeInfo::BuiltIn(BuiltIn::Float { .. })
+	| TypeInfo::Number(Some(NumberLiteralType::Float { .. })) = bound_ty
+	{
+		non_integer_range(
+			mappings.session,
+			start.span + end.span,
+			&bound_ty.display(&mappings.engine()),
+		);
+	}
+	if let Ident::Resolved(id) = var.value {
+		mappings.insert_var_ty(id, start);
+	}
+	loop_body(body, otherwise, span, mappings)
+}
+
+/// Returns the type of a number literal, which may only be known partially.
+fn number_literal(x: &NumberLiteral) -> TypeInfo {
+	match x.ty.clone() {
+		Some(ty) => {
+			if ty.has_bits() {
+				// convert to BuiltIn
+				TypeInfo::BuiltIn(match ty {
+					NumberLiteralType::Integer { bits, signed } => {
+						// (unwrap is safe because we cleared that it has bits above)
+						BuiltIn::Integer {
+							bits: bits.unwrap(),
+							signed,
+						}
+					}
+					NumberLiteralType::Float { bits } => BuiltIn::Float {
+						// (unwrap is safe because we cleared that it has bits above)
+						bits: bits.unwrap(),
+					},
+				})
+			} else {
+				TypeInfo::Number(Some(ty))
+			}
+		}
+		// a literal with a decimal point can only ever be a float
+		None if x.value.contains('.') => {
+			TypeInfo::Number(Some(NumberLiteralType::Float { bits: None }))
+		}
+		None => TypeInfo::Number(None),
+	}
+}
+
+/// Adds the type of the number literal `x` at `span`, negated if `negated` is
+/// set, registering it so that its value can be checked against the type once
+/// that is known.
+fn literal(
+	x: &NumberLiteral,
+	negated: bool,
+	span: Span,
+	mappings: &mut Mappings,
+) -> Spanned<TypeId> {
+	let ty = mappings.engine().add_ty(number_literal(x)).add_span(span);
+	mappings.insert_literal(ty, x.clone(), negated);
+	ty
+}
+
+/// Returns the type of a call to `callee`, which must take as many arguments
+/// as the call passes.
+fn call(
+	callee: &Spanned<HoistedExpr>,
+	generics: Option<&[Spanned<Type>]>,
+	args: &[Spanned<HoistedExpr>],
+	span: Span,
+	mappings: &mut Mappings,
+) -> TypeInfo {
+	let func_signature = callee.convert_and_add(mappings);
+	let return_ty = mappings.engine().add_ty(TypeInfo::Unknown).add_span(span);
+	let generics = generics
+		.unwrap_or_default()
+		.iter()
+		.map(|x| x.convert_and_add(mappings))
+		.collect();
+	let callee_info = mappings.engine().follow(func_signature.value).clone();
+	let (declared_args, is_unsafe) = match &callee_info {
+		TypeInfo::FuncSignature {
+			args, is_unsafe, ..
+		} => (args.clone(), *is_unsafe),
+		_ => (Vec::new(), false),
+	};
+	let args: Vec<_> = args
+		.iter()
+		.enumerate()
+		.map(|(i, x)| {
+			let arg = x.convert_and_add(mappings);
+			match declared_args.get(i) {
+				Some(declared) => coerce(*declared, arg, mappings),
+				None => arg,
+			}
+		})
+		.collect();
+	// unification can't tell a call apart from any other use of a function, so
+	// what only goes wrong in calls is reported here
+	match callee_info {
+		TypeInfo::FuncSignature { .. } if declared_args.len() != args.len() => {
+			wrong_arg_count(mappings.session, span, declared_args.len(), args.len());
+			return TypeInfo::Bottom;
+		}
+		TypeInfo::FuncSignature { .. }
+		| TypeInfo::Unknown
+		| TypeInfo::UnknownGeneric(_)
+		| TypeInfo::Bottom => {}
+		info => {
+			not_callable(mappings.session, callee.span, &info.display(&mappings.engine()));
+			return TypeInfo::Bottom;
+		}
+	}
+	// whether calling it is allowed at all is up to the safety checker
+	let our_signature = TypeInfo::FuncSignature {
+		return_ty,
+		args,
+		generics,
+		is_unsafe,
+	};
+	let our_signature = mappings.engine().add_ty(our_signature).add_span(span);
+	match mappings.engine().unify(mappings.session, func_signature, our_signature) {
+		TypeInfo::Bottom => TypeInfo::Bottom, // an error occurred
+		// our return type got linked to the callee's during unification
+		_ => TypeInfo::SameAs(return_ty),
+	}
+}
+
+/// Returns the type of the variant `variant` of the enum `r#enum`: the enum
+/// itself if the variant holds nothing, otherwise a function building it out
+/// of its payload. `span` is the span of the whole expression.
+fn variant(
+	r#enum: &Spanned<Ident>,
+	variant: &Spanned<Ident>,
+	span: Span,
+	mappings: &mut Mappings,
+) -> TypeInfo {
+	let id = r#enum.value.id();
+	// the resolver only makes variants out of enums
+	let declaration = mappings.get_enum(id).unwrap();
+	let Some((index, declared)) = declaration.variant(&variant.value) else {
+		nonexistent_variant(
+			mappings.session,
+			variant.span,
+			&variant.value,
+			&declaration.id.value.to_string(),
+		);
+		return TypeInfo::Bottom;
+	};
+	let enum_ty = TypeInfo::Enum {
+		id,
+		name: declaration.id.value.to_string(),
+	};
+	if declared.value.payload.is_empty() {
+		let enum_ty = mappings.engine().add_ty(enum_ty).add_span(span);
+		return TypeInfo::SameAs(enum_ty);
+	}
+	let enum_ty = mappings.engine().add_ty(enum_ty).add_span(r#enum.span);
+	let args = mappings.engine().enums[&id][index].clone();
+	TypeInfo::FuncSignature {
+		return_ty: enum_ty,
+		args,
+		generics: Vec::new(),
+		is_unsafe: false,
+	}
+}
+
+/// Infers `pattern`, which must fit the values of type `ty`, giving the vars it
+/// binds the types of the parts of the value they stand for.
+fn pattern(pattern: &Spanned<Pattern>, ty: Spanned<TypeId>, mappings: &mut Mappings) {
+	let pattern_ty = match &pattern.value {
+		Pattern::Binding(Ident::Resolved(id)) => {
+			mappings.insert_var_ty(*id, ty);
+			return;
+		}
+		Pattern::Binding(_) => return,
+		Pattern::NumberLiteral(x) => TypeInfo::SameAs(literal(x, false, pattern.span, mappings)),
+		Pattern::CharLiteral(_) => TypeInfo::BuiltIn(BuiltIn::Char),
+		Pattern::Variant {
+			r#enum,
+			variant,
+			fields,
+		} => {
+			let Some(payload) = variant_pattern(
+				r#enum,
+				variant,
+				fields.len(),
+				ty,
+				pattern.span,
+				mappings,
+			) else {
+				// the vars must still have a type for the arm to be inferred
+				for binding in pattern.bindings() {
+					let bottom = mappings.engine().add_ty(TypeInfo::Bottom).add_span(binding.span);
+					mappings.insert_var_ty(binding.value.id(), bottom);
+				}
+				return;
+			};
+			for (field, ty) in fields.iter().zip(payload) {
+				self::pattern(field, ty, mappings);
+			}
+			return;
+		}
+	};
+	let pattern_ty = mappings.engine().add_ty(pattern_ty).add_span(pattern.span);
+	mappings.engine().unify_custom_error(
+		mappings.session,
+		ty,
+		pattern_ty,
+		"type conflict: pattern can't match the value",
+		&[],
+	);
+}
+
+/// Checks that values of type `ty` can be the variant `variant` of `r#enum`,
+/// holding `count` values. Returns the types of the values, or `None` if an
+/// error was reported. `span` is the span of the whole pattern.
+fn variant_pattern(
+	r#enum: &Spanned<Ident>,
+	variant: &Spanned<Ident>,
+	count: usize,
+	ty: Spanned<TypeId>,
+	span: Span,
+	mappings: &Mappings,
+) -> Option<Vec<Spanned<TypeId>>> {
+	let id = r#enum.value.id();
+	// the resolver already reported anything that isn't an enum
+	let declaration = mappings.get_enum(id)?;
+	let name = declaration.id.value.to_string();
+	let enum_ty = mappings
+		.engine()
+		.add_ty(TypeInfo::Enum {
+			id,
+			name: name.clone(),
+		})
+		.add_span(r#enum.span);
+	let unified = mappings.engine().unify_custom_error(
+		mappings.session,
+		ty,
+		enum_ty,
+		"type conflict: pattern can't match the value",
+		&[],
+	);
+	if unified == TypeInfo::Bottom {
+		return None;
+	}
+	let Some((index, declared)) = declaration.variant(&variant.value) else {
+		nonexistent_variant(mappings.session, variant.span, &variant.value, &name);
+		return None;
+	};
+	if declared.value.payload.len() != count {
+		let variant = format!("{name}::{}", variant.value);
+		wrong_payload_count(mappings.session, span, &variant, declared.value.payload.len(), count);
+		return None;
+	}
+	Some(mappings.engine().enums[&id][index].clone())
+}
+
+/// Infers a match expression, whose arms must all have the same type. Every
+/// pattern must fit the type of `value`, and every possible value must be
+/// matched by some arm.
+fn match_expr(
+	value: &Spanned<HoistedExpr>,
+	arms: &[(Spanned<Pattern>, Spanned<HoistedExpr>)],
+	mappings: &mut Mappings,
+) -> TypeInfo {
+	let value_ty = value.convert_and_add(mappings);
+	let mut result: Option<Spanned<TypeId>> = None;
+	for (arm_pattern, arm) in arms {
+		pattern(arm_pattern, value_ty, mappings);
+		let arm = arm.convert_and_add(mappings);
+		match result {
+			// an arm that returns has no type of its own, so we take the others
+			None if *mappings.engine().follow(arm.value) == TypeInfo::Bottom => {}
+			None => result = Some(arm),
+			Some(result) => {
+				mappings.engine().unify_custom_error(
+					mappings.session,
+					result,
+					arm,
+					"type conflict: match arms have different types",
+					&[],
+				);
+			}
+		}
+	}
+	let r#enum = match mappings.engine().follow(value_ty.value) {
+		TypeInfo::Enum { id, .. } => Some(*id),
+		_ => None,
+	};
+	let patterns: Vec<_> = arms.iter().map(|(x, _)| x).collect();
+	let missing = exhaustiveness::missing_patterns(&patterns, r#enum, mappings);
+	if !missing.is_empty() {
+		non_exhaustive_match(mappings.session, value.span, &missing);
+	}
+	result.map_or(TypeInfo::Bottom, TypeInfo::SameAs)
 }
 
 impl ToInfo for Spanned<Type> {
 	fn to_info(&self, mappings: &mut Mappings) -> Spanned<TypeInfo> {
 		match &self.value {
-			Type::User(x) => engine()
-				.tys
-				.get(&mappings.get_named_ty(x.id()).value)
-				.expect("??")
-				.clone()
-				.add_span(self.span),
+			Type::User(x) if mappings.get_struct(x.id()).is_some() => {
+				instantiate(self, None, self.span, mappings).add_span(self.span)
+			}
+			Type::User(x) if mappings.get_enum(x.id()).is_some() => TypeInfo::Enum {
+				id: x.id(),
+				name: mappings.get_enum(x.id()).unwrap().id.value.to_string(),
+			}
+			.add_span(self.span),
+			// every use of a generic has to be linked to it, rather than be a copy of
+			// it, so that whatever determines one use determines all of them
+			Type::User(x) => TypeInfo::SameAs(*mappings.get_named_ty(x.id())).add_span(self.span),
 			Type::BuiltIn(x) => TypeInfo::BuiltIn(x.clone()).add_span(self.span),
-			Type::Generic(..) => todo!("(generic type parsing is not even implemented yet)"),
+			Type::Generic(base, generics) => {
+				let generics = generics
+					.iter()
+					.map(|x| x.convert_and_add(mappings))
+					.collect();
+				instantiate(base, Some(generics), self.span, mappings).add_span(self.span)
+			}
+			Type::Ref(inner, mutable) => {
+				TypeInfo::Ref(inner.convert_and_add(mappings), *mutable).add_span(self.span)
+			}
+			Type::Ptr(inner, mutable) => {
+				TypeInfo::Ptr(inner.convert_and_add(mappings), *mutable).add_span(self.span)
+			}
+			Type::Optional(inner) => {
+				TypeInfo::Optional(inner.convert_and_add(mappings)).add_span(self.span)
+			}
+			Type::Func {
+				args,
+				return_ty,
+				is_unsafe,
+			} => TypeInfo::FuncSignature {
+				return_ty: return_ty.convert_and_add(mappings),
+				args: args.iter().map(|x| x.convert_and_add(mappings)).collect(),
+				// only named functions can be generic
+				generics: Vec::new(),
+				is_unsafe: *is_unsafe,
+			}
+			.add_span(self.span),
+			Type::Tuple(tys) => {
+				TypeInfo::Tuple(tys.iter().map(|x| x.convert_and_add(mappings)).collect())
+					.add_span(self.span)
+			}
+			Type::Array(inner, len) => match array_len(len, mappings) {
+				Some(len) => TypeInfo::Array(inner.convert_and_add(mappings), len),
+				None => TypeInfo::Bottom,
+			}
+			.add_span(self.span),
 			Type::Inferred => TypeInfo::Unknown.add_span(self.span),
 		}
 	}
@@ -49,7 +953,8 @@ impl ToInfo for Spanned<Signature> {
 		// 1. add all generics as UnknownGeneric for later unification/inference
 		let mut generics = Vec::new();
 		for generic in &self.value.generics.value {
-			let ty = engine()
+			let ty = mappings
+				.engine()
 				.add_ty(TypeInfo::UnknownGeneric(generic.value.id()))
 				.add_span(generic.span);
 			generics.push(ty);
@@ -68,6 +973,7 @@ impl ToInfo for Spanned<Signature> {
 			return_ty,
 			args,
 			generics,
+			is_unsafe: self.value.attribs.value.is_unsafe,
 		}
 		.add_span(self.span)
 	}
@@ -75,85 +981,253 @@ impl ToInfo for Spanned<Signature> {
 
 impl ToInfo for Spanned<HoistedExpr> {
 	fn to_info(&self, mappings: &mut Mappings) -> Spanned<TypeInfo> {
+		let info = self.expr_info(mappings);
+		let ty = mappings.engine().add_ty(info.value).add_span(info.span);
+		// every expr gets its own type so that it can be looked up once inferred
+		mappings.insert_expr_ty(self.span, ty);
+		TypeInfo::SameAs(ty).add_span(self.span)
+	}
+}
+
+impl Spanned<HoistedExpr> {
+	// the arms either build the type right away or hand off to a helper, so
+	// the length comes from the amount of kinds of exprs alone
+	#[allow(clippy::too_many_lines)]
+	fn expr_info(&self, mappings: &mut Mappings) -> Spanned<TypeInfo> {
 		match &self.value {
 			Expr::NumberLiteral(x) => {
-				match x.ty.clone() {
-					Some(ty) => {
-						if ty.has_bits() {
-							// convert to BuiltIn
-							TypeInfo::BuiltIn(match ty {
-								NumberLiteralType::Integer { bits, signed } => {
-									// (unwrap is safe because we cleared that it has bits above)
-									BuiltIn::Integer {
-										bits: bits.unwrap(),
-										signed,
-									}
-								}
-								NumberLiteralType::Float { bits } => BuiltIn::Float {
-									// (unwrap is safe because we cleared that it has bits above)
-									bits: bits.unwrap(),
-								},
-							})
-						} else {
-							TypeInfo::Number(Some(ty))
-						}
-					}
-					None => TypeInfo::Number(None),
-				}
-				.add_span(self.span)
+				TypeInfo::SameAs(literal(x, false, self.span, mappings)).add_span(self.span)
+			}
+			Expr::StringLiteral(_) => TypeInfo::BuiltIn(BuiltIn::Str).add_span(self.span),
+			Expr::CharLiteral(_) => TypeInfo::BuiltIn(BuiltIn::Char).add_span(self.span),
+			Expr::None => {
+				let inner = mappings.engine().add_ty(TypeInfo::Unknown).add_span(self.span);
+				TypeInfo::Optional(inner).add_span(self.span)
 			}
 			Expr::Identifier(x) => {
 				TypeInfo::SameAs(*mappings.get_var_ty(x.id())).add_span(self.span)
 			}
-			Expr::BinaryOp(lhs, _op, rhs) => {
-				// TODO: allow ops between different tys with custom return tys
-				let lhs = lhs.convert_and_add(mappings);
-				let rhs = rhs.convert_and_add(mappings);
-				engine().unify(lhs, rhs).add_span(self.span)
-			}
-			Expr::UnaryOp(_op, value) => {
+			Expr::BinaryOp(lhs, op, rhs) => binary_op(lhs, *op, rhs, self.span, mappings).add_span(self.span),
+			Expr::UnaryOp(op, value) => match &value.value {
+				// a negated literal is a literal of its own, whose range goes one further
+				// than the positive one for signed integers
+				Expr::NumberLiteral(x) if op.value == Operator::Neg => {
+					TypeInfo::SameAs(literal(x, true, self.span, mappings)).add_span(self.span)
+				}
 				// TODO: allow ops to have custom return tys
-				value.to_info(mappings)
+				_ => value.to_info(mappings),
+			},
+			Expr::Ref { value, mutable } => {
+				let value_ty = value.convert_and_add(mappings);
+				TypeInfo::Ref(value_ty, *mutable).add_span(self.span)
+			}
+			Expr::Deref(value) => {
+				let ptr = value.convert_and_add(mappings);
+				pointee(ptr, mappings)
+					.map_or(TypeInfo::Bottom, |(pointee, _)| TypeInfo::SameAs(pointee))
+					.add_span(self.span)
+			}
+			Expr::Try(value) => try_expr(value, self.span, mappings).add_span(self.span),
+			Expr::Cast { value, ty } => {
+				let value_ty = value.convert_and_add(mappings);
+				let ty = ty.convert_and_add(mappings);
+				// whether the cast makes sense at all can only be told once inference is
+				// done
+				mappings.insert_cast(self.span, value_ty, ty);
+				TypeInfo::SameAs(ty).add_span(self.span)
 			}
 			// FIXME: why do we need this clone???
-			Expr::Scope(inner) => inner.clone().add_span(self.span).to_info(mappings),
+			Expr::Scope(inner) | Expr::Unsafe(inner) => inner.clone().add_span(self.span).to_info(mappings),
 			Expr::Call {
 				callee,
 				generics,
 				args,
+			} => call(callee, generics.as_deref(), args, self.span, mappings).add_span(self.span),
+			Expr::Construct { ty, fields } => construct(ty, fields, mappings).add_span(self.span),
+			Expr::If {
+				cond,
+				then,
+				otherwise,
+			} => if_expr(cond, then, otherwise.as_deref(), self.span, mappings).add_span(self.span),
+			Expr::While {
+				cond,
+				body,
+				otherwise,
 			} => {
-				let func_signature = callee.convert_and_add(mappings);
-				let return_ty = engine().add_ty(TypeInfo::Unknown).add_span(self.span);
-				let generics = generics
-					.as_ref()
-					.map(|x| x.iter().map(|x| x.convert_and_add(mappings)).collect())
-					.unwrap_or_default();
-				let args = args.iter().map(|x| x.convert_and_add(mappings)).collect();
-				let our_signature = TypeInfo::FuncSignature {
-					return_ty,
-					args,
-					generics,
+				let cond = cond.convert_and_add(mappings);
+				let bool_ty = bool_ty(cond.span, mappings);
+				mappings.engine().unify_custom_error(
+					mappings.session,
+					bool_ty,
+					cond,
+					"type conflict: condition is not a bool",
+					&[],
+				);
+				loop_body(body, otherwise.as_deref(), self.span, mappings).add_span(self.span)
+			}
+			Expr::For {
+				var,
+				start,
+				end,
+				body,
+				otherwise,
+			} => for_expr(var, start, end, (body, otherwise.as_deref()), self.span, mappings)
+				.add_span(self.span),
+			Expr::Field { value, field } => {
+				let value_ty = value.convert_and_add(mappings);
+				let Some((r#struct, generics)) = as_struct(value_ty, mappings) else {
+					return TypeInfo::Bottom.add_span(self.span);
 				};
-				let our_signature = engine().add_ty(our_signature).add_span(self.span);
-				let unified_signature = engine().unify(func_signature, our_signature);
-				match unified_signature {
-					TypeInfo::FuncSignature {
-						return_ty,
-						args: _,
-						generics: _,
-					} => engine().tys[&return_ty.value].clone(),
-					_ => TypeInfo::Bottom, // an error occurred
-				}
-				.add_span(self.span)
+				let Some(declared) = r#struct.field(&field.value) else {
+					nonexistent_field(
+						mappings.session,
+						field.span,
+						&field.value,
+						&r#struct.id.value.to_string(),
+					);
+					return TypeInfo::Bottom.add_span(self.span);
+				};
+				let field_ty = field_ty(&declared.value.ty, &r#struct, &generics, mappings);
+				TypeInfo::SameAs(field_ty).add_span(self.span)
+			}
+			Expr::Tuple(values) => {
+				TypeInfo::Tuple(values.iter().map(|x| x.convert_and_add(mappings)).collect())
+					.add_span(self.span)
+			}
+			Expr::TupleField { value, index } => {
+				tuple_field(value, *index, mappings).add_span(self.span)
+			}
+			Expr::Array(values) => array(values, self.span, mappings).add_span(self.span),
+			Expr::Index { value, index: i } => {
+				index(value, i, mappings).add_span(self.span)
 			}
+			Expr::Closure {
+				signature, body, ..
+			} => closure(signature, body, self.span, mappings).add_span(self.span),
+			Expr::Variant {
+				r#enum,
+				variant: x,
+			} => variant(r#enum, x, self.span, mappings).add_span(self.span),
+			Expr::Match { value, arms } => {
+				match_expr(value, arms, mappings).add_span(self.span)
+			}
+		}
+	}
+}
+
+/// Returns the type of a closure, whose body is inferred right away like the
+/// body of a function.
+fn closure(
+	signature: &Signature,
+	body: &Spanned<HoistedScope>,
+	span: Span,
+	mappings: &mut Mappings,
+) -> TypeInfo {
+	let mut args = Vec::new();
+	for arg in &signature.args.value {
+		let ty = arg.value.ty.convert_and_add(mappings);
+		if let Ident::Resolved(x) = arg.ident() {
+			mappings.insert_var_ty(*x, ty);
+		}
+		args.push(ty);
+	}
+	let return_ty = signature.return_ty.convert_and_add(mappings);
+	let outer_return_ty = mappings.replace_return_ty(Some(return_ty));
+	let outer_break_ty = mappings.replace_break_ty(None);
+	let actual_return = body.convert_and_add(mappings);
+	mappings.replace_break_ty(outer_break_ty);
+	mappings.replace_return_ty(outer_return_ty);
+	unify_return(return_ty, actual_return, mappings);
+	let signature = mappings
+		.engine()
+		.add_ty(TypeInfo::FuncSignature {
+			return_ty,
+			args,
+			generics: Vec::new(),
+			is_unsafe: false,
+		})
+		.add_span(span);
+	TypeInfo::SameAs(signature)
+}
+
+/// Returns the type of the binary operation `lhs op rhs`.
+fn binary_op(
+	lhs: &Spanned<HoistedExpr>,
+	op: Spanned<Operator>,
+	rhs: &Spanned<HoistedExpr>,
+	span: Span,
+	mappings: &mut Mappings,
+) -> TypeInfo {
+	if op.value.is_logical() {
+		for operand in [lhs, rhs] {
+			let operand = operand.convert_and_add(mappings);
+			// (the engine can't be borrowed while `bool_ty` adds to it)
+			let bool_ty = bool_ty(operand.span, mappings);
+			mappings.engine().unify_custom_error(
+				mappings.session,
+				bool_ty,
+				operand,
+				"type conflict: operand of a logical operator is not a bool",
+				&[],
+			);
 		}
+		TypeInfo::SameAs(bool_ty(span, mappings))
+	} else if op.value.is_comparison() {
+		let lhs = lhs.convert_and_add(mappings);
+		let rhs = rhs.convert_and_add(mappings);
+		mappings.engine().unify(mappings.session, lhs, rhs);
+		TypeInfo::SameAs(bool_ty(span, mappings))
+	} else if op.value == Operator::UnwrapOr {
+		let lhs = lhs.convert_and_add(mappings);
+		let rhs = rhs.convert_and_add(mappings);
+		let Some(inner) = optional_inner(lhs, mappings) else {
+			return TypeInfo::Bottom;
+		};
+		mappings.engine().unify(mappings.session, inner, rhs);
+		TypeInfo::SameAs(inner)
+	} else {
+		// TODO: allow ops between different tys with custom return tys
+		let lhs = lhs.convert_and_add(mappings);
+		let rhs = rhs.convert_and_add(mappings);
+		mappings.engine().unify(mappings.session, lhs, rhs)
 	}
 }
 
+/// Unifies the declared return type of a function with the type of a value it
+/// returns.
+fn unify_return(
+	return_ty: Spanned<TypeId>,
+	actual_return: Spanned<TypeId>,
+	mappings: &mut Mappings,
+) -> TypeInfo {
+	let actual_return = coerce(return_ty, actual_return, mappings);
+	let mut engine = mappings.engine();
+	let return_ty_ty = engine.tys[&return_ty.value].clone();
+	let return_ty_ty = return_ty_ty.display(&engine);
+	let actual_return_ty = engine.tys[&actual_return.value].clone();
+	let actual_return_ty_display = actual_return_ty.display(&engine);
+	engine.unify_custom_error(
+		mappings.session,
+		return_ty,
+		actual_return,
+		"type conflict: incorrect return type",
+		&[&format!(
+			"return type was declared to be {} but a value of type {} was returned instead{}",
+			return_ty_ty,
+			actual_return_ty_display,
+			if actual_return_ty == TypeInfo::BuiltIn(BuiltIn::Void) {
+				" (or no return statement exists)"
+			} else {
+				""
+			}
+		)],
+	)
+}
+
 impl ToInfo for Spanned<HoistedFunc> {
 	fn to_info(&self, mappings: &mut Mappings) -> Spanned<TypeInfo> {
 		for generic in &self.value.signature.generics.value {
-			let ty = engine().add_ty(TypeInfo::Generic(generic.value.id()));
+			let ty = mappings.engine().add_ty(TypeInfo::Generic(generic.value.id()));
 			mappings.insert_named_ty(generic.value.id(), ty.add_span(generic.span));
 		}
 		for arg in &self.value.signature.args.value {
@@ -162,37 +1236,123 @@ impl ToInfo for Spanned<HoistedFunc> {
 		}
 		let return_ty = self.value.signature.return_ty.convert_and_add(mappings);
 		if let Some(inner) = &self.value.body {
+			// return stmts deep inside the body need to know what they should return
+			let outer_return_ty = mappings.replace_return_ty(Some(return_ty));
+			// loops outside of the function can't be broken out of from inside it
+			let outer_break_ty = mappings.replace_break_ty(None);
 			let actual_return = inner.convert_and_add(mappings);
-			let mut engine = engine();
-			let return_ty_ty = engine.tys[&return_ty.value].clone();
-			let return_ty_ty = return_ty_ty.display(&engine);
-			let actual_return_ty = engine.tys[&actual_return.value].clone();
-			let actual_return_ty_display = actual_return_ty.display(&engine);
-			engine
-				.unify_custom_error(
-					return_ty,
-					actual_return,
-					"type conflict: incorrect return type",
-					&[&format!(
-					"return type was declared to be {} but a value of type {} was returned instead{}",
-					return_ty_ty,
-					actual_return_ty_display,
-					if actual_return_ty == TypeInfo::BuiltIn(BuiltIn::Void) {
-						" (or no return statement exists)"
-					} else {
-						""
-					}
-				)],
-				)
-				.add_span(self.span)
+			mappings.replace_break_ty(outer_break_ty);
+			mappings.replace_return_ty(outer_return_ty);
+			unify_return(return_ty, actual_return, mappings).add_span(self.span)
 		} else {
 			TypeInfo::Bottom.add_span(self.span)
 		}
 	}
 }
 
+/// Registers the enums declared in a scope along with the types of their
+/// payloads, reporting the ones that would have to hold themselves.
+fn enums(enums: &IndexMap<Ident, Spanned<Enum>>, mappings: &mut Mappings) {
+	// payloads may hold any enum of the scope, so all of them must be known first
+	for (ident, r#enum) in enums {
+		mappings.insert_enum(ident.id(), r#enum.value.clone());
+	}
+	for (ident, r#enum) in enums {
+		let payloads = r#enum
+			.value
+			.variants
+			.value
+			.iter()
+			.map(|x| x.value.payload.iter().map(|x| x.convert_and_add(mappings)).collect())
+			.collect();
+		mappings.engine().enums.insert(ident.id(), payloads);
+	}
+	for (ident, r#enum) in enums {
+		let engine = mappings.engine();
+		let holds_itself = engine.enums[&ident.id()]
+			.iter()
+			.flatten()
+			.find(|x| engine.holds_enum(x.value, ident.id()))
+			.copied();
+		drop(engine);
+		if let Some(payload) = holds_itself {
+			recursive_enum(mappings.session, payload.span, &r#enum.value.id.value);
+		}
+	}
+}
+
+/// Returns whether values of type `ty` hold a value of the struct `id`
+/// directly, rather than through a reference or a pointer. `seen` are the
+/// structs and enums already looked into.
+fn holds_struct(ty: TypeId, id: Id, seen: &mut Vec<Id>, mappings: &mut Mappings) -> bool {
+	let info = mappings.engine().follow(ty).clone();
+	match info {
+		TypeInfo::Struct { id: x, .. } if x == id => true,
+		// a struct or enum holding itself is reported on its own
+		TypeInfo::Struct { id: x, .. } | TypeInfo::Enum { id: x, .. } if seen.contains(&x) => false,
+		TypeInfo::Struct { id: x, generics, .. } => {
+			seen.push(x);
+			let r#struct = mappings.get_struct(x).unwrap().clone();
+			r#struct.fields.value.iter().any(|field| {
+				let field_ty = field_ty(&field.value.ty, &r#struct, &generics, mappings);
+				holds_struct(field_ty.value, id, seen, mappings)
+			})
+		}
+		TypeInfo::Enum { id: x, .. } => {
+			seen.push(x);
+			let payloads: Vec<_> = mappings.engine().enums[&x].iter().flatten().copied().collect();
+			(payloads.iter()).any(|payload| holds_struct(payload.value, id, seen, mappings))
+		}
+		TypeInfo::Tuple(xs) => xs.iter().any(|x| holds_struct(x.value, id, seen, mappings)),
+		TypeInfo::Array(x, _) | TypeInfo::Optional(x) => holds_struct(x.value, id, seen, mappings),
+		_ => false,
+	}
+}
+
+/// Reports the structs declared in a scope that hold a value of their own type
+/// in one of their fields, which would make them infinitely big.
+fn check_struct_sizes(structs: &IndexMap<Ident, Spanned<Struct>>, mappings: &mut Mappings) {
+	for (ident, r#struct) in structs {
+		let r#struct = &r#struct.value;
+		// the generics don't matter, a field of a generic type only holds the
+		// struct if every instance does
+		let generics: Vec<_> = (r#struct.generics.value.iter())
+			.map(|x| mappings.engine().add_ty(TypeInfo::Unknown).add_span(x.span))
+			.collect();
+		let holds_itself = r#struct.fields.value.iter().find(|field| {
+			let field_ty = field_ty(&field.value.ty, r#struct, &generics, mappings);
+			holds_struct(field_ty.value, ident.id(), &mut Vec::new(), mappings)
+		});
+		if let Some(field) = holds_itself {
+			recursive_struct(mappings.session, field.span, &r#struct.id.value);
+		}
+	}
+}
+
+/// Registers the consts declared in a scope and infers their values, which
+/// were already evaluated but may still be of the wrong type.
+fn consts(consts: &IndexMap<Ident, Spanned<HoistedConst>>, mappings: &mut Mappings) {
+	// values may use any const of the scope
+	for (ident, r#const) in consts {
+		let ty = r#const.value.ty.convert_and_add(mappings);
+		mappings.insert_var_ty(ident.id(), ty);
+	}
+	for (ident, r#const) in consts {
+		let const_ty = *mappings.get_var_ty(ident.id());
+		let value_ty = r#const.value.value.convert_and_add(mappings);
+		let value_ty = coerce(const_ty, value_ty, mappings);
+		mappings.engine().unify(mappings.session, const_ty, value_ty);
+	}
+}
+
 impl ToInfo for Spanned<HoistedScope> {
 	fn to_info(&self, mappings: &mut Mappings) -> Spanned<TypeInfo> {
+		for (ident, r#struct) in &self.value.data.structs {
+			mappings.insert_struct(ident.id(), r#struct.value.clone());
+		}
+		enums(&self.value.data.enums, mappings);
+		// fields may hold enums of the scope, so those have to be registered first
+		check_struct_sizes(&self.value.data.structs, mappings);
 		for (ident, var) in &self.value.data.vars {
 			// FIXME: this span seems weird
 			let ty = var
@@ -208,7 +1368,11 @@ impl ToInfo for Spanned<HoistedScope> {
 				.map_ref(|x| x.signature.clone())
 				.convert_and_add(mappings);
 			mappings.insert_var_ty(ident.id(), ty);
-			// FIXME: stop this from crashing lol
+		}
+		consts(&self.value.data.consts, mappings);
+		// every signature must be known before inferring any body, since bodies may
+		// call funcs declared after them
+		for func in self.value.data.funcs.values() {
 			func.to_info(mappings);
 		}
 		let mut has_yielded_or_returned = false;
@@ -226,28 +1390,105 @@ impl ToInfo for Spanned<HoistedScope> {
 					let var_ty = *mappings.get_var_ty(ty_id.ident().id());
 					if let Some(value) = value {
 						let value_ty = value.convert_and_add(mappings);
-						engine().unify(var_ty, value_ty);
+						let value_ty = coerce(var_ty, value_ty, mappings);
+						mappings.engine().unify(mappings.session, var_ty, value_ty);
 					}
 				}
+				Stmt::Destructure { ids, value, .. } => destructure(ids, value, mappings),
 				Stmt::Set { id, value } => {
 					let var_ty = *mappings.get_var_ty(id.value.id());
 					let value_ty = value.convert_and_add(mappings);
-					engine().unify(var_ty, value_ty);
+					let value_ty = coerce(var_ty, value_ty, mappings);
+					mappings.engine().unify(mappings.session, var_ty, value_ty);
 				}
-				Stmt::Func { .. } => unreachable!(),
-				// TODO: do something with is_yield
-				Stmt::Return { value, is_yield: _ } => {
+				Stmt::Store { ptr, value } => store(ptr, value, mappings),
+				Stmt::Func { .. }
+				| Stmt::Struct { .. }
+				| Stmt::Enum { .. }
+				| Stmt::Const { .. } => unreachable!(),
+				// the resolver already made the imported items available
+				Stmt::Import { .. } => {}
+				Stmt::Return {
+					value,
+					is_yield: true,
+				} => {
 					has_yielded_or_returned = true;
 					return_type = value.to_info(mappings);
 				}
+				Stmt::Return {
+					value,
+					is_yield: false,
+				} => {
+					has_yielded_or_returned = true;
+					let value_ty = value.convert_and_add(mappings);
+					// the checker makes sure we can only return from inside a function
+					let return_ty = mappings.return_ty().expect("return outside of a function");
+					unify_return(return_ty, value_ty, mappings);
+					// the scope never finishes, so its type doesn't matter
+					return_type = TypeInfo::Bottom.add_span(stmt.span);
+				}
+				Stmt::Break { value } => {
+					has_yielded_or_returned = true;
+					let value_ty = match value {
+						Some(value) => value.convert_and_add(mappings),
+						None => mappings
+							.engine()
+							.add_ty(TypeInfo::BuiltIn(BuiltIn::Void))
+							.add_span(stmt.span),
+					};
+					// the checker makes sure we can only break from inside a loop
+					let break_ty = mappings.break_ty().expect("break outside of a loop");
+					mappings.engine().unify_custom_error(
+						mappings.session,
+						break_ty,
+						value_ty,
+						"type conflict: loop is broken out of with different types",
+						&[],
+					);
+					return_type = TypeInfo::Bottom.add_span(stmt.span);
+				}
+				Stmt::Continue => {
+					has_yielded_or_returned = true;
+					return_type = TypeInfo::Bottom.add_span(stmt.span);
+				}
 			}
 		}
 		return_type
 	}
 }
 
-pub fn infer(scope: &Spanned<HoistedScope>) {
-	let mut mappings = Mappings::default();
-	scope.to_info(&mut mappings);
-	engine().dump(&mappings);
+/// Reports every array index that isn't an integer, which may only be known
+/// once inference is done.
+fn check_indices(engine: &Engine, mappings: &Mappings) {
+	for ty in mappings.indices() {
+		let info = engine.follow(ty.value);
+		// an error was already reported, or the unknown type was reported once
+		// inference was done
+		if matches!(info, TypeInfo::Bottom | TypeInfo::Unknown) {
+			continue;
+		}
+		if !matches!(info.as_builtin(), Some(BuiltIn::Integer { .. })) {
+			non_integer_index(mappings.session, ty.span, &info.display(engine));
+		}
+	}
+}
+
+#[must_use]
+pub fn infer(session: &CompilerSession, program: Spanned<HoistedScope>) -> Typed<'_> {
+	let errors = session.error_count();
+	let mut mappings = Mappings::new(session);
+	consts::evaluate(&program.value.data, &mut mappings);
+	program.to_info(&mut mappings);
+	let engine = session.engine();
+	// errors usually leave some types unknown, reporting those would only
+	// repeat them
+	if session.error_count() == errors {
+		typed::report_uninferred(&engine, &mappings);
+	}
+	literals::check_literals(&engine, &mappings);
+	check_indices(&engine, &mappings);
+	cast::check_casts(&engine, &mappings);
+	let typed = Typed::new(program, mappings, &engine);
+	drop(engine);
+	typed
 }
@@ -0,0 +1,125 @@
+//! Checks the casts of a program once the type of every value in it is known,
+//! which isn't the case while inferring it.
+
+use super::{
+	engine::Engine,
+	literals::{fits, mantissa_bits},
+	mappings::Mappings,
+	type_info::TypeInfo,
+};
+use crate::{
+	codegen::ty::POINTER_WIDTH,
+	common::{
+		diagnostics::{invalid_cast, lossy_cast},
+		r#type::BuiltIn,
+	},
+	lexer::NumberLiteral,
+};
+use std::collections::HashMap;
+
+/// Returns why casting values of type `from` to `to` may lose information, or
+/// `None` if every value survives the cast.
+fn loss(from: &BuiltIn, to: &BuiltIn) -> Option<&'static str> {
+	match (from, to) {
+		(
+			BuiltIn::Integer {
+				bits: from_bits,
+				signed: from_signed,
+			},
+			BuiltIn::Integer {
+				bits: to_bits,
+				signed: to_signed,
+			},
+		) => {
+			let from_bits = from_bits.unwrap_or(POINTER_WIDTH);
+			let to_bits = to_bits.unwrap_or(POINTER_WIDTH);
+			if *from_signed && !to_signed {
+				Some("negative values wrap around to large positive ones")
+			} else if to_bits < from_bits || (to_bits == from_bits && from_signed != to_signed) {
+				Some("values that don't fit wrap around, keeping only their lowest bits")
+			} else {
+				None
+			}
+		}
+		(BuiltIn::Integer { bits, signed }, BuiltIn::Float { bits: to_bits }) => {
+			// the sign bit doesn't count, floats have a sign of their own
+			let digits = bits.unwrap_or(POINTER_WIDTH) - u32::from(*signed);
+			(digits > mantissa_bits(*to_bits))
+				.then_some("large values are rounded to the closest float")
+		}
+		(BuiltIn::Float { .. }, BuiltIn::Integer { .. }) => Some(
+			"the fractional part is dropped, and values out of the range of the integer type have \
+			 no meaningful result",
+		),
+		(BuiltIn::Float { bits: from_bits }, BuiltIn::Float { bits: to_bits }) => {
+			(to_bits < from_bits).then_some(
+				"precision is lost, and values out of the range of the smaller type become \
+				 infinite",
+			)
+		}
+		_ => None,
+	}
+}
+
+/// Returns why casting the number literal `literal`, negated if `negated` is
+/// set, to `to` loses information, or `None` if its value survives the cast.
+fn literal_loss(literal: &NumberLiteral, negated: bool, to: &BuiltIn) -> Option<&'static str> {
+	if fits(literal, negated, to) {
+		return None;
+	}
+	Some(match to {
+		BuiltIn::Integer { .. } if literal.as_f64().fract() != 0. => {
+			"the fractional part is dropped"
+		}
+		BuiltIn::Integer { .. } if literal.as_u128().is_none() => {
+			"the value is out of the range of the integer type, so the result has no meaningful \
+			 value"
+		}
+		BuiltIn::Integer { .. } => "the value doesn't fit, so only its lowest bits are kept",
+		_ => "the value can't be represented exactly, so it is rounded to the closest float",
+	})
+}
+
+/// Reports every cast between types that aren't both numeric, and warns about
+/// the ones that may lose information.
+pub fn check_casts(engine: &Engine, mappings: &Mappings) {
+	let session = mappings.session;
+	let literals: HashMap<_, _> = (mappings.literals().iter())
+		.map(|(ty, literal, negated)| (ty.span, (literal, *negated)))
+		.collect();
+	for (span, from, to) in mappings.casts() {
+		let numeric = |ty| {
+			engine
+				.builtin(ty)
+				.filter(|x| matches!(x, BuiltIn::Integer { .. } | BuiltIn::Float { .. }))
+		};
+		match (numeric(from.value), numeric(to.value)) {
+			// the value of a literal is right there, so only that value has to survive
+			// rather than every value of its type
+			(Some(_), Some(to)) if literals.contains_key(&from.span) => {
+				let (literal, negated) = literals[&from.span];
+				if let Some(loss) = literal_loss(literal, negated, &to) {
+					let sign = if negated { "-" } else { "" };
+					let from = format!("{sign}{}", literal.value);
+					lossy_cast(session, *span, &from, &to.to_string(), loss);
+				}
+			}
+			(Some(from), Some(to)) => {
+				if let Some(loss) = loss(&from, &to) {
+					lossy_cast(session, *span, &from.to_string(), &to.to_string(), loss);
+				}
+			}
+			// an error was already reported, or the unknown type was reported once
+			// inference was done
+			_ if [from, to].iter().any(|x| {
+				matches!(engine.follow(x.value), TypeInfo::Bottom | TypeInfo::Unknown)
+			}) => {}
+			_ => invalid_cast(
+				session,
+				*span,
+				&engine.follow(from.value).display(engine),
+				&engine.follow(to.value).display(engine),
+			),
+		}
+	}
+}
@@ -0,0 +1,434 @@
+//! Evaluates every const before inference, since the length of an array type
+//! may be a const and has to be known to infer anything of that type. Types
+//! aren't known yet at that point, so every value takes the type it's expected
+//! to have, which comes from the declared type of the const or from the
+//! signatures of the `pure` functions it calls.
+
+use super::{literals::fits_integer, mappings::Mappings, number_literal, type_info::TypeInfo};
+use crate::{
+	codegen::ty::POINTER_WIDTH,
+	common::{
+		diagnostics::{
+			const_eval_failed, const_overflow, invalid_const_ty, not_const, recursive_const,
+		},
+		expr::Expr,
+		ident::{Id, Ident},
+		r#type::{BuiltIn, Type},
+		span::{Span, Spanned},
+		stmt::Stmt,
+	},
+	hoister::{HoistedExpr, HoistedScope, HoistedScopeData},
+	interp::{value::Value, BOOL},
+	lexer::{NumberLiteral, Operator},
+	session::CompilerSession,
+};
+use std::collections::HashMap;
+
+/// How many calls may be nested while evaluating a const before we give up,
+/// since the function may well never return.
+const MAX_DEPTH: usize = 256;
+
+/// The values of the args and vars of the function being evaluated.
+type Locals = HashMap<Id, Value>;
+
+/// Returns the `BuiltIn` behind `ty` if values of it can be computed at compile
+/// time, which is only the case for integers and floats.
+fn numeric(ty: &Type) -> Option<BuiltIn> {
+	match ty {
+		Type::BuiltIn(x) if x.is_numeric() && Value::supports(x) => Some(x.clone()),
+		_ => None,
+	}
+}
+
+/// Returns whether `value` is of type `ty`.
+fn has_ty(value: Value, ty: &BuiltIn) -> bool {
+	match (value, ty) {
+		(Value::Int { bits, signed, .. }, BuiltIn::Integer { bits: x, signed: y }) => {
+			bits == x.unwrap_or(POINTER_WIDTH) && signed == *y
+		}
+		(Value::Float { bits, .. }, BuiltIn::Float { bits: x }) => bits == *x,
+		_ => false,
+	}
+}
+
+/// Returns the type of `value`.
+fn ty_of_value(value: Value) -> BuiltIn {
+	match value {
+		Value::Int { bits, signed, .. } => BuiltIn::Integer {
+			bits: Some(bits),
+			signed,
+		},
+		Value::Float { bits, .. } => BuiltIn::Float { bits },
+		Value::Void => BuiltIn::Void,
+	}
+}
+
+/// Evaluates a number literal of type `ty`, negating it if `negated` is set so
+/// that the smallest signed integers can be written down. Literals out of the
+/// range of their type have no value, and are reported once inference is done
+/// like every other literal.
+fn literal(literal: &NumberLiteral, ty: &BuiltIn, negated: bool) -> Option<Value> {
+	let value = match *ty {
+		BuiltIn::Integer { bits, signed } if fits_integer(literal, negated, bits, signed) => {
+			Value::int(bits.unwrap_or(POINTER_WIDTH), signed, literal.as_u128()?)
+		}
+		BuiltIn::Float { bits } => Value::float(bits, literal.as_f64()),
+		// a literal of any other type is reported during inference
+		_ => return None,
+	};
+	if negated {
+		Value::unary(Operator::Neg, value).ok()
+	} else {
+		Some(value)
+	}
+}
+
+struct Evaluator<'a> {
+	session: &'a CompilerSession,
+	data: &'a HoistedScopeData,
+	/// The value of every const evaluated so far, or `None` if it couldn't be
+	/// evaluated.
+	values: HashMap<Id, Option<Value>>,
+	/// The consts being evaluated, innermost last, to catch the ones that
+	/// depend on themselves.
+	stack: Vec<Id>,
+	/// How many calls are nested at the moment.
+	depth: usize,
+}
+
+impl Evaluator<'_> {
+	/// Evaluates the const `id`, which is used at `span`.
+	fn r#const(&mut self, id: Id, span: Span) -> Option<Value> {
+		if let Some(value) = self.values.get(&id) {
+			return *value;
+		}
+		let data = self.data;
+		let r#const = &data.consts[&Ident::Resolved(id)].value;
+		if self.stack.contains(&id) {
+			recursive_const(self.session, span, &r#const.id.value);
+			return None;
+		}
+		let value = if let Some(ty) = numeric(&r#const.ty.value) {
+			self.stack.push(id);
+			let value = self.expr(&r#const.value, &ty, &Locals::new());
+			self.stack.pop();
+			value
+		} else {
+			invalid_const_ty(self.session, r#const.ty.span, &r#const.ty.value);
+			None
+		};
+		self.values.insert(id, value);
+		value
+	}
+
+	/// Returns the type of `expr` if it can be told without evaluating it,
+	/// which isn't the case for number literals without a suffix.
+	fn ty_of(&self, expr: &Spanned<HoistedExpr>, locals: &Locals) -> Option<BuiltIn> {
+		match &expr.value {
+			Expr::NumberLiteral(x) => match number_literal(x) {
+				TypeInfo::BuiltIn(x) => Some(x),
+				_ => None,
+			},
+			Expr::Identifier(x) => locals.get(&x.id()).map(|x| ty_of_value(*x)).or_else(|| {
+				self.data
+					.consts
+					.get(x)
+					.and_then(|x| numeric(&x.value.ty.value))
+			}),
+			Expr::Call { callee, .. } => match &callee.value {
+				Expr::Identifier(x) => self
+					.data
+					.funcs
+					.get(x)
+					.and_then(|x| numeric(&x.value.signature.return_ty.value)),
+				_ => None,
+			},
+			Expr::Cast { ty, .. } => numeric(&ty.value),
+			Expr::BinaryOp(_, op, _) if op.value.is_comparison() || op.value.is_logical() => {
+				Some(BOOL)
+			}
+			Expr::BinaryOp(lhs, _, rhs) => {
+				self.ty_of(lhs, locals).or_else(|| self.ty_of(rhs, locals))
+			}
+			Expr::UnaryOp(_, value) => self.ty_of(value, locals),
+			_ => None,
+		}
+	}
+
+	/// Returns the type `exprs` have to share, like both operands of a
+	/// comparison, falling back to the type number literals default to when
+	/// it can't be told.
+	fn shared_ty(&self, exprs: &[&Spanned<HoistedExpr>], locals: &Locals) -> BuiltIn {
+		exprs
+			.iter()
+			.find_map(|x| self.ty_of(x, locals))
+			.or_else(|| {
+				exprs.iter().find_map(|x| match &x.value {
+					Expr::NumberLiteral(x) => number_literal(x).as_builtin(),
+					_ => None,
+				})
+			})
+			.unwrap_or(BuiltIn::Integer {
+				bits: Some(32),
+				signed: true,
+			})
+	}
+
+	/// Applies the arithmetic operator `op` on both values of type `ty`,
+	/// reporting results that don't fit in the type.
+	fn arithmetic(
+		&self,
+		op: Operator,
+		lhs: Value,
+		rhs: Value,
+		ty: &BuiltIn,
+		span: Span,
+	) -> Option<Value> {
+		match Value::binary(op, lhs, rhs) {
+			Ok(_) if Value::overflows(op, lhs, rhs) => {
+				const_overflow(self.session, span, ty);
+				None
+			}
+			Ok(value) => Some(value),
+			Err(err) => {
+				const_eval_failed(self.session, span, err);
+				None
+			}
+		}
+	}
+
+	/// Evaluates `expr`, which is expected to be of type `ty`.
+	fn expr(
+		&mut self,
+		expr: &Spanned<HoistedExpr>,
+		ty: &BuiltIn,
+		locals: &Locals,
+	) -> Option<Value> {
+		let value = match &expr.value {
+			Expr::NumberLiteral(x) => literal(x, ty, false),
+			Expr::Identifier(x) => match locals.get(&x.id()) {
+				Some(value) => Some(*value),
+				None if self.data.consts.contains_key(x) => self.r#const(x.id(), expr.span),
+				None => {
+					not_const(self.session, expr.span, "using a function as a value");
+					None
+				}
+			},
+			Expr::BinaryOp(lhs, op, rhs) if op.value.is_comparison() => {
+				let operand_ty = self.shared_ty(&[lhs, rhs], locals);
+				let lhs = self.expr(lhs, &operand_ty, locals)?;
+				let rhs = self.expr(rhs, &operand_ty, locals)?;
+				Value::compare(op.value, lhs, rhs).ok()
+			}
+			Expr::BinaryOp(lhs, op, rhs) if op.value.is_logical() => {
+				let lhs = self.expr(lhs, &BOOL, locals)?;
+				// the rhs is only evaluated if the lhs doesn't already decide the result
+				if lhs.is_true() == (op.value == Operator::Or) {
+					Some(lhs)
+				} else {
+					self.expr(rhs, &BOOL, locals)
+				}
+			}
+			Expr::BinaryOp(lhs, op, rhs)
+				if matches!(
+					op.value,
+					Operator::Plus | Operator::Neg | Operator::Star | Operator::Div
+				) =>
+			{
+				let lhs = self.expr(lhs, ty, locals)?;
+				let rhs = self.expr(rhs, ty, locals)?;
+				self.arithmetic(op.value, lhs, rhs, ty, op.span)
+			}
+			Expr::UnaryOp(op, value) if op.value == Operator::Neg => {
+				if let Expr::NumberLiteral(x) = &value.value {
+					return literal(x, ty, true);
+				}
+				let value = self.expr(value, ty, locals)?;
+				match value {
+					Value::Int { bits, signed, .. } => {
+						let zero = Value::int(bits, signed, 0);
+						self.arithmetic(Operator::Neg, zero, value, ty, expr.span)
+					}
+					_ => Value::unary(Operator::Neg, value).ok(),
+				}
+			}
+			Expr::BinaryOp(_, op, _) | Expr::UnaryOp(op, _) => {
+				not_const(self.session, op.span, &format!("the `{}` operator", op.value));
+				None
+			}
+			Expr::Cast { value, ty: to } => {
+				// casts between other types are reported during inference
+				let to = numeric(&to.value)?;
+				let from = self.shared_ty(&[value], locals);
+				self.expr(value, &from, locals)?.cast(&to).ok()
+			}
+			Expr::Scope(scope) => self.scope(scope, ty, locals, false),
+			Expr::If {
+				cond,
+				then,
+				otherwise: Some(otherwise),
+			} => {
+				if self.expr(cond, &BOOL, locals)?.is_true() {
+					self.expr(then, ty, locals)
+				} else {
+					self.expr(otherwise, ty, locals)
+				}
+			}
+			Expr::Call { callee, args, .. } => self.call(callee, args, expr.span, locals),
+			_ => {
+				not_const(self.session, expr.span, "this kind of expression");
+				None
+			}
+		};
+		// a value of the wrong type is reported during inference
+		value.filter(|x| has_ty(*x, ty))
+	}
+
+	/// Evaluates the stmts of `scope`, whose value of type `ty` is the one it
+	/// yields or, if it's the body of a function, the one it returns.
+	fn scope(
+		&mut self,
+		scope: &HoistedScope,
+		ty: &BuiltIn,
+		locals: &Locals,
+		is_body: bool,
+	) -> Option<Value> {
+		let mut locals = locals.clone();
+		for stmt in &scope.stmts {
+			match &stmt.value {
+				Stmt::Create {
+					ty_id,
+					value: Some(value),
+					..
+				} => {
+					let var_ty = match ty_id.ty() {
+						Type::Inferred => self.shared_ty(&[value], &locals),
+						ty => {
+							let Some(ty) = numeric(ty) else {
+								not_const(
+									self.session,
+									stmt.span,
+									"a variable of a non-numeric type",
+								);
+								return None;
+							};
+							ty
+						}
+					};
+					let value = self.expr(value, &var_ty, &locals)?;
+					locals.insert(ty_id.ident().id(), value);
+				}
+				Stmt::Return { value, is_yield } if *is_yield != is_body => {
+					return self.expr(value, ty, &locals);
+				}
+				_ => {
+					not_const(self.session, stmt.span, &format!("a {} stmt", stmt.value.variant()));
+					return None;
+				}
+			}
+		}
+		// a scope without a value is void, which is reported during inference
+		None
+	}
+
+	/// Evaluates the call of `callee` with `args`, where `span` is the span of
+	/// the whole call.
+	fn call(
+		&mut self,
+		callee: &Spanned<HoistedExpr>,
+		args: &[Spanned<HoistedExpr>],
+		span: Span,
+		locals: &Locals,
+	) -> Option<Value> {
+		let data = self.data;
+		let Expr::Identifier(ident) = &callee.value else {
+			not_const(self.session, callee.span, "calling anything but a function by its name");
+			return None;
+		};
+		let Some(func) = data.funcs.get(ident) else {
+			not_const(
+				self.session,
+				callee.span,
+				"calling a function that isn't declared at the top level",
+			);
+			return None;
+		};
+		let func = &func.value;
+		let signature = &func.signature;
+		let Some(body) = &func.body else {
+			not_const(
+				self.session,
+				span,
+				&format!("calling '{}', which has no body,", func.id.value),
+			);
+			return None;
+		};
+		if !signature.attribs.value.is_pure {
+			not_const(
+				self.session,
+				span,
+				&format!("calling '{}', which isn't `pure`,", func.id.value),
+			);
+			return None;
+		}
+		if !signature.generics.value.is_empty() {
+			not_const(self.session, span, "calling a generic function");
+			return None;
+		}
+		let arg_tys: Option<Vec<_>> = signature
+			.args
+			.value
+			.iter()
+			.map(|x| numeric(x.ty()))
+			.collect();
+		let (Some(arg_tys), Some(return_ty)) = (arg_tys, numeric(&signature.return_ty.value))
+		else {
+			not_const(
+				self.session,
+				span,
+				"calling a function that takes or returns non-numeric values",
+			);
+			return None;
+		};
+		// a wrong amount of args is reported during inference
+		if args.len() != arg_tys.len() {
+			return None;
+		}
+		if self.depth == MAX_DEPTH {
+			const_eval_failed(
+				self.session,
+				span,
+				"calls are nested too deeply, the function may never return",
+			);
+			return None;
+		}
+		let mut frame = Locals::new();
+		for ((arg, arg_ty), decl) in args.iter().zip(&arg_tys).zip(&signature.args.value) {
+			let value = self.expr(arg, arg_ty, locals)?;
+			if let Ident::Resolved(id) = decl.ident() {
+				frame.insert(*id, value);
+			}
+		}
+		self.depth += 1;
+		let value = self.scope(&body.value, &return_ty, &frame, true);
+		self.depth -= 1;
+		value
+	}
+}
+
+/// Evaluates every const of the program in `data`, registering the values of
+/// the ones that could be evaluated and reporting the others.
+pub fn evaluate(data: &HoistedScopeData, mappings: &mut Mappings) {
+	let mut evaluator = Evaluator {
+		session: mappings.session,
+		data,
+		values: HashMap::new(),
+		stack: Vec::new(),
+		depth: 0,
+	};
+	for (ident, r#const) in &data.consts {
+		if let Some(value) = evaluator.r#const(ident.id(), r#const.span) {
+			mappings.insert_const(ident.id(), value);
+		}
+	}
+}
@@ -1,22 +1,25 @@
 use crate::{
 	common::{
-		diagnostics::add_diagnostic,
-		r#type,
+		ident::{Id, Ident},
+		r#type::{self, ArrayLen, Type},
 		span::{Add, Spanned},
 	},
 	infer::type_info::TypeInfo,
 	lexer::NumberLiteralType,
+	session::CompilerSession,
 };
 use codespan_reporting::diagnostic::{Diagnostic, Label};
-use itertools::Itertools;
 use std::collections::HashMap;
 
-use super::{mappings::Mappings, type_info::TypeId};
+use super::type_info::TypeId;
 
 #[derive(Default)]
 pub struct Engine {
 	id_counter: TypeId,
 	pub tys: HashMap<TypeId, TypeInfo>,
+	/// The payload types of every variant of every enum, which enums can share
+	/// between all of their instances since they can't be generic.
+	pub enums: HashMap<Id, Vec<Vec<Spanned<TypeId>>>>,
 }
 
 impl Engine {
@@ -26,6 +29,130 @@ impl Engine {
 		self.id_counter
 	}
 
+	/// Follows `TypeInfo::SameAs` links until reaching the type they point to.
+	#[must_use]
+	pub fn follow(&self, id: TypeId) -> &TypeInfo {
+		match &self.tys[&id] {
+			TypeInfo::SameAs(x) => self.follow(x.value),
+			x => x,
+		}
+	}
+
+	/// Returns the `BuiltIn` behind `id`, if any. Numbers whose width was never
+	/// determined default to `i32`, `u32` or `f64`.
+	#[must_use]
+	pub fn builtin(&self, id: TypeId) -> Option<r#type::BuiltIn> {
+		self.follow(id).as_builtin()
+	}
+
+	/// Follows `TypeInfo::SameAs` links from `id` until reaching the id of the
+	/// type they point to.
+	#[must_use]
+	pub fn root(&self, id: TypeId) -> TypeId {
+		match &self.tys[&id] {
+			TypeInfo::SameAs(x) => self.root(x.value),
+			_ => id,
+		}
+	}
+
+	/// Returns the root of every part of the type behind `id` that was never
+	/// inferred, like the inner type of `none` if nothing ever told what it holds.
+	#[must_use]
+	pub fn unknowns(&self, id: TypeId) -> Vec<TypeId> {
+		let all = |xs: &[Spanned<TypeId>]| xs.iter().flat_map(|x| self.unknowns(x.value)).collect();
+		match self.follow(id) {
+			TypeInfo::Unknown => vec![self.root(id)],
+			TypeInfo::FuncSignature { return_ty, args, .. } => {
+				let mut unknowns: Vec<_> = all(args);
+				unknowns.extend(self.unknowns(return_ty.value));
+				unknowns
+			}
+			TypeInfo::Struct { generics: xs, .. } | TypeInfo::Tuple(xs) => all(xs),
+			TypeInfo::Ref(x, _)
+			| TypeInfo::Ptr(x, _)
+			| TypeInfo::Optional(x)
+			| TypeInfo::Array(x, _) => self.unknowns(x.value),
+			_ => Vec::new(),
+		}
+	}
+
+	/// Turns the type behind `id` back into a `Type`, following every
+	/// `TypeInfo::SameAs` link and defaulting numbers just like `builtin`.
+	/// Types that were never inferred become `Type::Inferred`, and generics that
+	/// couldn't be filled in stay generic. Bottoms are `void`, since nothing
+	/// ever gets their value.
+	#[must_use]
+	pub fn resolve(&self, id: Spanned<TypeId>) -> Spanned<Type> {
+		let resolve_all =
+			|xs: &[Spanned<TypeId>]| xs.iter().map(|x| self.resolve(*x)).collect();
+		let boxed = |x: Spanned<TypeId>| Box::new(self.resolve(x));
+		match self.follow(id.value) {
+			TypeInfo::Unknown => Type::Inferred,
+			TypeInfo::SameAs(_) => unreachable!("followed type is a link"),
+			x @ (TypeInfo::BuiltIn(_) | TypeInfo::Number(_)) => {
+				Type::BuiltIn(x.as_builtin().unwrap())
+			}
+			TypeInfo::FuncSignature {
+				return_ty,
+				args,
+				is_unsafe,
+				..
+			} => Type::Func {
+				args: resolve_all(args),
+				return_ty: boxed(*return_ty),
+				is_unsafe: *is_unsafe,
+			},
+			TypeInfo::Struct { id: x, generics, .. } if !generics.is_empty() => Type::Generic(
+				Box::new(Type::User(Ident::Resolved(*x)).add_span(id.span)),
+				resolve_all(generics),
+			),
+			TypeInfo::Struct { id: x, .. }
+			| TypeInfo::Enum { id: x, .. }
+			| TypeInfo::Generic(x)
+			| TypeInfo::UnknownGeneric(x) => Type::User(Ident::Resolved(*x)),
+			TypeInfo::Ref(x, mutable) => Type::Ref(boxed(*x), *mutable),
+			TypeInfo::Ptr(x, mutable) => Type::Ptr(boxed(*x), *mutable),
+			TypeInfo::Optional(x) => Type::Optional(boxed(*x)),
+			TypeInfo::Tuple(xs) => Type::Tuple(resolve_all(xs)),
+			TypeInfo::Array(x, len) => {
+				Type::Array(boxed(*x), ArrayLen::Literal(*len).add_span(x.span))
+			}
+			TypeInfo::Bottom => Type::BuiltIn(r#type::BuiltIn::Void),
+		}
+		.add_span(id.span)
+	}
+
+	/// Returns whether values of type `ty` hold a value of the enum `id`
+	/// directly, rather than through a reference or a pointer.
+	#[must_use]
+	pub fn holds_enum(&self, ty: TypeId, id: Id) -> bool {
+		fn holds(engine: &Engine, ty: TypeId, id: Id, seen: &mut Vec<Id>) -> bool {
+			match engine.follow(ty) {
+				TypeInfo::Enum { id: x, .. } if *x == id => true,
+				// an enum holding itself is reported on its own
+				TypeInfo::Enum { id: x, .. } if seen.contains(x) => false,
+				TypeInfo::Enum { id: x, .. } => {
+					seen.push(*x);
+					engine.enums[x]
+						.iter()
+						.flatten()
+						.any(|x| holds(engine, x.value, id, seen))
+				}
+				TypeInfo::Tuple(xs) => xs.iter().any(|x| holds(engine, x.value, id, seen)),
+				TypeInfo::Array(x, _) | TypeInfo::Optional(x) => holds(engine, x.value, id, seen),
+				_ => false,
+			}
+		}
+		holds(self, ty, id, &mut Vec::new())
+	}
+
+	// the match below is the whole unification algorithm, splitting it up would
+	// only make it harder to follow
+	#[allow(
+		clippy::too_many_lines,
+		clippy::many_single_char_names,
+		clippy::enum_glob_use
+	)]
 	fn unify_inner(
 		&mut self,
 		a: Spanned<TypeId>,
@@ -36,7 +163,10 @@ impl Engine {
 			b: String,
 		) -> Result<(), (String, String, String)> {
 			Err((
-				format!("disallowed implicit cast between numeric types {a} and {b}"),
+				format!(
+					"disallowed implicit cast between numeric types {a} and {b}, cast \
+					 explicitly with `as` if you meant to (like `x as {a}`)"
+				),
 				a,
 				b,
 			))
@@ -46,9 +176,14 @@ impl Engine {
 		let c = self.tys[&a.value].clone();
 		let d = self.tys[&b.value].clone();
 
-		let mut unify_num_and_builtin = |x: Option<NumberLiteralType>, y: r#type::BuiltIn| {
+		// `num` is the side holding the `Number`, `other` the side holding the
+		// `BuiltIn`
+		let mut unify_num_and_builtin = |num: Spanned<TypeId>,
+		                                 other: Spanned<TypeId>,
+		                                 x: Option<NumberLiteralType>,
+		                                 y: r#type::BuiltIn| {
 			let mut accept_specific_type = || {
-				self.tys.insert(a.value, TypeInfo::SameAs(b));
+				self.tys.insert(num.value, TypeInfo::SameAs(other));
 				Ok(())
 			};
 			// we can safely assume both of these are numeric types, we
@@ -91,37 +226,75 @@ impl Engine {
 			}
 		};
 
-		match (c.clone(), d.clone()) {
-			(a, b) if a == b => Ok(()),
+		if a.value == b.value {
+			return Ok(());
+		}
 
+		match (c.clone(), d.clone()) {
 			(SameAs(a), _) => self.unify_inner(a, b),
 			(_, SameAs(b)) => self.unify_inner(a, b),
 
 			(Bottom, _) | (_, Bottom) => Ok(()),
 
-			(Unknown | UnknownGeneric(_), _) => {
+			// unknowns are linked before generics are, so that a generic nothing
+			// determined stays one instead of becoming an unknown
+			(Unknown, _) => {
+				self.tys.insert(a.value, TypeInfo::SameAs(b));
+				Ok(())
+			}
+			(_, Unknown) => {
+				self.tys.insert(b.value, TypeInfo::SameAs(a));
+				Ok(())
+			}
+			(UnknownGeneric(_), _) => {
 				self.tys.insert(a.value, TypeInfo::SameAs(b));
 				Ok(())
 			}
-			(_, Unknown | UnknownGeneric(_)) => {
+			(_, UnknownGeneric(_)) => {
 				self.tys.insert(b.value, TypeInfo::SameAs(a));
 				Ok(())
 			}
 
+			// two numbers must be linked even if they look the same, so that whatever
+			// ends up determining one of them also determines the other
+			(Number(x), Number(y)) => {
+				let compatible = match (&x, &y) {
+					(
+						Some(NumberLiteralType::Integer { signed: x, .. }),
+						Some(NumberLiteralType::Integer { signed: y, .. }),
+					) => x == y,
+					(None, _)
+					| (_, None)
+					| (Some(NumberLiteralType::Float { .. }), Some(NumberLiteralType::Float { .. })) => true,
+					_ => false,
+				};
+				if !compatible {
+					return disallowed_implicit_num_cast(c.display(self), d.display(self));
+				}
+				if x.is_none() {
+					self.tys.insert(a.value, TypeInfo::SameAs(b));
+				} else {
+					self.tys.insert(b.value, TypeInfo::SameAs(a));
+				}
+				Ok(())
+			}
+
+			(a, b) if a == b => Ok(()),
+
 			// void doesn't unify with anything but void itself
 			// don't need to check whether lhs is void, earlier match arm would've caught
-			(_, BuiltIn(y)) if y == r#type::BuiltIn::Void => {
+			(_, BuiltIn(r#type::BuiltIn::Void)) => {
 				let a = c.display(self);
 				let b = d.display(self);
 				Err((format!("({a} is a non-void type)"), a, b))
 			}
-			(BuiltIn(x), _) if x == r#type::BuiltIn::Void => {
+			(BuiltIn(r#type::BuiltIn::Void), _) => {
 				let a = c.display(self);
 				let b = d.display(self);
 				Err((format!("({b} is a non-void type)"), a, b))
 			}
 
-			(BuiltIn(x), BuiltIn(y)) => {
+			(BuiltIn(x), BuiltIn(y)) if x.is_numeric() && y.is_numeric() => {
 				if x == y {
 					Ok(())
 				} else {
@@ -133,27 +306,33 @@ impl Engine {
 				}
 			}
 
-			(Number(x), BuiltIn(y)) => unify_num_and_builtin(x, y),
-			(BuiltIn(y), Number(x)) => unify_num_and_builtin(x, y),
+			(Number(x), BuiltIn(y)) if y.is_numeric() => unify_num_and_builtin(a, b, x, y),
+			(BuiltIn(y), Number(x)) if y.is_numeric() => unify_num_and_builtin(b, a, x, y),
 
 			(
 				FuncSignature {
 					return_ty: ret_a,
 					args: args_a,
 					generics: gens_a,
+					is_unsafe: unsafe_a,
 				},
 				FuncSignature {
 					return_ty: ret_b,
 					args: args_b,
 					generics: gens_b,
+					is_unsafe: unsafe_b,
 				},
-			) => {
+			) if unsafe_a == unsafe_b => {
 				// unification is supposed to be bidirectional, but just for a moment let's say
-				// that the left hand side has the correct amount of args and generics since
-				// that's how we call this function in `Expr::Call`
+				// that the left hand side has the correct amount of generics since that's how
+				// we call this function in `Expr::Call`
 				if args_a.len() != args_b.len() {
 					return Err((
-						"incorrect number of arguments passed into function call".into(),
+						format!(
+							"functions taking {} and {} arguments are different types",
+							args_a.len(),
+							args_b.len()
+						),
 						c.display(self),
 						d.display(self),
 					));
@@ -161,21 +340,82 @@ impl Engine {
 				for (x, y) in args_a.iter().zip(args_b.iter()) {
 					self.unify_inner(*x, *y)?;
 				}
-				for i in 0..gens_a.len() {
-					let gen_a = gens_a[i];
-					let gen_b = gens_b
+				for (i, generic) in gens_a.iter().enumerate() {
+					let other = gens_b
 						.get(i)
 						.copied()
-						.unwrap_or_else(|| self.add_ty(TypeInfo::Unknown).add_span(gen_a.span));
-					self.unify_inner(gen_a, gen_b)?;
+						.unwrap_or_else(|| self.add_ty(TypeInfo::Unknown).add_span(generic.span));
+					self.unify_inner(*generic, other)?;
 				}
 				self.unify_inner(ret_a, ret_b)
 			}
 
+			(
+				Struct {
+					id: id_a,
+					generics: gens_a,
+					..
+				},
+				Struct {
+					id: id_b,
+					generics: gens_b,
+					..
+				},
+			) if id_a == id_b => {
+				for (x, y) in gens_a.iter().zip(gens_b.iter()) {
+					self.unify_inner(*x, *y)?;
+				}
+				Ok(())
+			}
+
+			// the payload types are shared by every instance, so they match already
+			(Enum { id: id_a, .. }, Enum { id: id_b, .. }) if id_a == id_b => Ok(()),
+
+			// whether the pointee can be changed is part of the type, so it has to match
+			(Ref(x, mutable_a), Ref(y, mutable_b)) | (Ptr(x, mutable_a), Ptr(y, mutable_b))
+				if mutable_a == mutable_b =>
+			{
+				self.unify_inner(x, y)
+			}
+
+			(Optional(x), Optional(y)) => self.unify_inner(x, y),
+
+			(Tuple(xs), Tuple(ys)) => {
+				if xs.len() != ys.len() {
+					return Err((
+						"tuples have different numbers of components".into(),
+						c.display(self),
+						d.display(self),
+					));
+				}
+				for (x, y) in xs.into_iter().zip(ys) {
+					self.unify_inner(x, y)?;
+				}
+				Ok(())
+			}
+
+			(Array(x, len_a), Array(y, len_b)) => {
+				if len_a != len_b {
+					return Err((
+						"arrays have different lengths".into(),
+						c.display(self),
+						d.display(self),
+					));
+				}
+				self.unify_inner(x, y)
+			}
+
 			(a, b) => Err({
+				// a function used where what it returns was expected is missing a call
+				let is_func = |x: &TypeInfo| matches!(x, FuncSignature { .. });
+				let hint = if is_func(&a) == is_func(&b) {
+					""
+				} else {
+					" (a function is a value of its function type, call it to use what it returns)"
+				};
 				let a = a.display(self);
 				let b = b.display(self);
-				(format!("could not unify {a} and {b}"), a, b)
+				(format!("could not unify {a} and {b}{hint}"), a, b)
 			}),
 		}
 	}
@@ -185,6 +425,7 @@ impl Engine {
 	/// changing the name and notes of the error.
 	pub fn unify_custom_error(
 		&mut self,
+		session: &CompilerSession,
 		a: Spanned<TypeId>,
 		b: Spanned<TypeId>,
 		title: &str,
@@ -194,7 +435,7 @@ impl Engine {
 		if let Err(ref err) = unified {
 			let mut notes: Vec<String> = notes.iter().map(|x| (*x).to_string()).collect();
 			notes.push(err.0.clone());
-			add_diagnostic(
+			session.add_diagnostic(
 				Diagnostic::error()
 					.with_message(title)
 					.with_labels(vec![
@@ -206,27 +447,43 @@ impl Engine {
 					.with_notes(notes),
 			);
 		}
-		unified.map_or(TypeInfo::Bottom, |_| {
+		unified.map_or(TypeInfo::Bottom, |()| {
 			self.tys.get(&a.value).unwrap().clone()
 		})
 	}
 
 	/// Returns `TypeInfo::Bottom` if unification failed, otherwise returns
 	/// `TypeInfo` corresponding to the unified type of both sides.
-	pub fn unify(&mut self, a: Spanned<TypeId>, b: Spanned<TypeId>) -> TypeInfo {
-		self.unify_custom_error(a, b, "type conflict", &[])
+	pub fn unify(
+		&mut self,
+		session: &CompilerSession,
+		a: Spanned<TypeId>,
+		b: Spanned<TypeId>,
+	) -> TypeInfo {
+		self.unify_custom_error(session, a, b, "type conflict", &[])
 	}
 
-	pub fn dump(&self, mappings: &Mappings) {
-		for k in self.tys.keys().sorted() {
-			let v = &self.tys[k];
-			let id = mappings
-				.get_id_from_ty(*k)
-				.map_or("(anon)".into(), |x| format!("(@{x})"));
-			println!(
-				"{id} @{k} -> {}",
-				v.display_custom(|x| format!("[@{x} ({})]", self.tys[&x.value].display(self)))
-			);
+	/// Returns `value` wrapped into an optional if `target` is an optional and
+	/// `value` definitely isn't one, so that `T` can be used where `T?` is
+	/// expected. `T` and `T?` never unify on their own, callers decide which
+	/// sites are explicit enough to coerce.
+	pub fn coerce(
+		&mut self,
+		target: Spanned<TypeId>,
+		value: Spanned<TypeId>,
+	) -> Option<Spanned<TypeId>> {
+		let TypeInfo::Optional(_) = self.follow(target.value) else {
+			return None;
+		};
+		match self.follow(value.value) {
+			// unknown types may still turn out to be optionals, and bottoms already
+			// had an error reported
+			TypeInfo::Unknown
+			| TypeInfo::UnknownGeneric(_)
+			| TypeInfo::Bottom
+			| TypeInfo::Optional(_)
+			| TypeInfo::BuiltIn(r#type::BuiltIn::Void) => None,
+			_ => Some(self.add_ty(TypeInfo::Optional(value)).add_span(value.span)),
 		}
 	}
 }
@@ -0,0 +1,109 @@
+//! Finds the values none of the arms of a `match` apply to, going over the
+//! patterns column by column like in "Warnings for pattern matching" by Luc
+//! Maranget: a column of variants is split up into one matrix per variant of
+//! the enum, and any other column only counts the rows where it's a wildcard,
+//! since literals can never cover every value of their type.
+
+use super::mappings::Mappings;
+use crate::common::{
+	ident::{Id, Ident},
+	join::Join,
+	pattern::Pattern,
+	span::Spanned,
+};
+use std::iter;
+
+static WILDCARD: Pattern = Pattern::Binding(Ident::Discarded);
+
+/// Returns the values no row of `rows` matches, each row holding a pattern for
+/// each of `len` consecutive values. The values are made up of one pattern per
+/// column, as they would be written in code. `hint` is the enum the first
+/// column is known to be of, for when no row tells.
+fn missing(
+	rows: &[Vec<&Pattern>],
+	len: usize,
+	hint: Option<Id>,
+	mappings: &Mappings,
+) -> Vec<Vec<String>> {
+	if rows.is_empty() {
+		return vec![vec!["_".into(); len]];
+	}
+	if len == 0 {
+		return Vec::new();
+	}
+	let r#enum = rows
+		.iter()
+		.find_map(|row| match row[0] {
+			Pattern::Variant { r#enum, .. } => Some(r#enum.value.id()),
+			_ => None,
+		})
+		.or(hint)
+		.and_then(|id| mappings.get_enum(id));
+	let Some(r#enum) = r#enum else {
+		let rest: Vec<_> = rows
+			.iter()
+			.filter(|row| matches!(row[0], Pattern::Binding(_)))
+			.map(|row| row[1..].to_vec())
+			.collect();
+		return missing(&rest, len - 1, None, mappings)
+			.into_iter()
+			.map(|rest| iter::once("_".into()).chain(rest).collect())
+			.collect();
+	};
+	let mut result = Vec::new();
+	for variant in &r#enum.variants.value {
+		let arity = variant.value.payload.len();
+		let specialized: Vec<Vec<&Pattern>> = rows
+			.iter()
+			.filter_map(|row| match row[0] {
+				Pattern::Variant {
+					variant: x, fields, ..
+				} if x.value == variant.value.id.value && fields.len() == arity => Some(
+					fields
+						.iter()
+						.map(|x| &x.value)
+						.chain(row[1..].iter().copied())
+						.collect(),
+				),
+				// a wrong number of fields was already reported, so we don't report the
+				// variant as missing on top of that
+				Pattern::Variant { variant: x, .. } if x.value == variant.value.id.value => Some(
+					iter::repeat_n(&WILDCARD, arity)
+						.chain(row[1..].iter().copied())
+						.collect(),
+				),
+				Pattern::Binding(_) => Some(
+					iter::repeat_n(&WILDCARD, arity)
+						.chain(row[1..].iter().copied())
+						.collect(),
+				),
+				_ => None,
+			})
+			.collect();
+		for witness in missing(&specialized, arity + len - 1, None, mappings) {
+			let (fields, rest) = witness.split_at(arity);
+			let value = format!(
+				"{}::{}{}",
+				r#enum.id.value,
+				variant.value.id.value,
+				fields.join_comma_wrapped("(", ")")
+			);
+			result.push(iter::once(value).chain(rest.iter().cloned()).collect());
+		}
+	}
+	result
+}
+
+/// Returns the values of type `r#enum` (or any other type, if it's `None`)
+/// that none of `patterns` match.
+pub fn missing_patterns(
+	patterns: &[&Spanned<Pattern>],
+	r#enum: Option<Id>,
+	mappings: &Mappings,
+) -> Vec<String> {
+	let rows: Vec<_> = patterns.iter().map(|x| vec![&x.value]).collect();
+	missing(&rows, 1, r#enum, mappings)
+		.into_iter()
+		.map(|mut x| x.remove(0))
+		.collect()
+}
@@ -0,0 +1,97 @@
+//! Checks that number literals fit in the type they end up with, which isn't
+//! known while inferring them.
+
+use super::{engine::Engine, mappings::Mappings};
+use crate::{
+	codegen::ty::POINTER_WIDTH,
+	common::{diagnostics::number_out_of_range, r#type::BuiltIn},
+	lexer::NumberLiteral,
+};
+
+/// How many significant bits a float of every width holds.
+pub fn mantissa_bits(bits: u8) -> u32 {
+	match bits {
+		16 => 11,
+		32 => 24,
+		64 => 53,
+		_ => 113,
+	}
+}
+
+/// Returns the value of `literal` if it is a whole number, even when written
+/// as a float.
+fn whole(literal: &NumberLiteral) -> Option<u128> {
+	literal.as_u128().or_else(|| {
+		let value = literal.as_f64();
+		// the cast saturates at the bounds, but we already know the value is a
+		// whole number and the literal has no sign
+		#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+		(value.fract() == 0. && value < 2f64.powi(128)).then_some(value as u128)
+	})
+}
+
+/// Returns whether an integer type `bits` wide holds the value of `literal`,
+/// negated if `negated` is set. Literals with a fractional part fit in no
+/// integer type.
+pub fn fits_integer(
+	literal: &NumberLiteral,
+	negated: bool,
+	bits: Option<u32>,
+	signed: bool,
+) -> bool {
+	let bits = bits.unwrap_or(POINTER_WIDTH);
+	let max = match (signed, bits) {
+		(true, _) => (1 << (bits - 1)) - u128::from(!negated),
+		(false, 128..) => u128::MAX,
+		(false, _) => (1 << bits) - 1,
+	};
+	// only zero can be negated without going below the range of unsigned types
+	whole(literal).is_some_and(|value| value <= max && (signed || !negated || value == 0))
+}
+
+/// Returns whether a float `bits` wide holds the value of `literal` exactly,
+/// without rounding it.
+pub fn fits_float(literal: &NumberLiteral, bits: u8) -> bool {
+	let max = match bits {
+		16 => 65504.,
+		32 => f64::from(f32::MAX),
+		_ => f64::MAX,
+	};
+	let value = literal.as_f64();
+	// the digits are counted from the literal itself when we can, since big
+	// integers may already have been rounded to make an `f64`
+	let digits = match literal.as_u128() {
+		Some(0) => 0,
+		Some(x) => 128 - x.leading_zeros() - x.trailing_zeros(),
+		None if value == 0. => 0,
+		None => {
+			// the implicit leading one of normal floats counts too
+			let mantissa = value.to_bits() & ((1 << 52) - 1) | 1 << 52;
+			53 - mantissa.trailing_zeros()
+		}
+	};
+	value <= max && digits <= mantissa_bits(bits)
+}
+
+/// Returns whether `ty` holds the value of `literal`, negated if `negated` is
+/// set, without changing it.
+pub fn fits(literal: &NumberLiteral, negated: bool, ty: &BuiltIn) -> bool {
+	match *ty {
+		BuiltIn::Integer { bits, signed } => fits_integer(literal, negated, bits, signed),
+		BuiltIn::Float { bits } => fits_float(literal, bits),
+		_ => true,
+	}
+}
+
+/// Reports every integer literal out of the range of its type. Floats too big
+/// for their type become infinite instead.
+pub fn check_literals(engine: &Engine, mappings: &Mappings) {
+	for (ty, literal, negated) in mappings.literals() {
+		let Some(BuiltIn::Integer { bits, signed }) = engine.builtin(ty.value) else {
+			continue;
+		};
+		if !fits_integer(literal, *negated, bits, signed) {
+			number_out_of_range(mappings.session, ty.span, BuiltIn::Integer { bits, signed });
+		}
+	}
+}
@@ -1,6 +1,19 @@
-use super::type_info::TypeId;
-use crate::common::{ident::Id, span::Spanned};
-use std::collections::HashMap;
+use super::{engine::Engine, type_info::TypeId};
+use crate::{
+	common::{
+		ident::Id,
+		r#enum::Enum,
+		r#struct::Struct,
+		span::{Span, Spanned},
+	},
+	interp::value::Value,
+	lexer::NumberLiteral,
+	session::CompilerSession,
+};
+use std::{
+	cell::RefMut,
+	collections::{HashMap, HashSet},
+};
 
 /// Maps names to types, disambiguating variable names and type names.
 ///
@@ -9,13 +22,63 @@ use std::collections::HashMap;
 /// associations made for types pertaining to variables ("variable types") and
 /// types actually associated to a type in code, such as a `Type::User` or a
 /// generic ("named types").
-#[derive(Debug, Default)]
-pub struct Mappings {
+pub struct Mappings<'a> {
+	pub session: &'a CompilerSession,
 	named_tys: HashMap<Id, Spanned<TypeId>>,
 	var_tys: HashMap<Id, Spanned<TypeId>>,
+	structs: HashMap<Id, Struct>,
+	enums: HashMap<Id, Enum>,
+	/// The declared return type of the function whose body is being inferred.
+	return_ty: Option<Spanned<TypeId>>,
+	/// The type of the values the innermost loop being inferred is broken out
+	/// of with.
+	break_ty: Option<Spanned<TypeId>>,
+	/// The type of every expr, keyed by its span.
+	expr_tys: HashMap<Span, Spanned<TypeId>>,
+	/// The spans of the exprs whose value has to be wrapped into an optional
+	/// before being used.
+	coercions: HashSet<Span>,
+	/// The span of every cast along with the type of the value being cast and
+	/// the type it casts to, which can only be checked once the type of every
+	/// value is known.
+	casts: Vec<(Span, Spanned<TypeId>, Spanned<TypeId>)>,
+	/// The type of every number literal along with the literal and whether it
+	/// is negated, whose value can only be checked once its type is known.
+	literals: Vec<(Spanned<TypeId>, NumberLiteral, bool)>,
+	/// The type of every array index, which may be any integer type and so
+	/// can only be checked once it is known.
+	indices: Vec<Spanned<TypeId>>,
+	/// The value of every const that could be evaluated, which backends use
+	/// in place of the const.
+	consts: HashMap<Id, Value>,
 }
 
-impl Mappings {
+impl<'a> Mappings<'a> {
+	#[must_use]
+	pub fn new(session: &'a CompilerSession) -> Self {
+		Self {
+			session,
+			named_tys: HashMap::new(),
+			var_tys: HashMap::new(),
+			structs: HashMap::new(),
+			enums: HashMap::new(),
+			return_ty: None,
+			break_ty: None,
+			expr_tys: HashMap::new(),
+			coercions: HashSet::new(),
+			casts: Vec::new(),
+			literals: Vec::new(),
+			indices: Vec::new(),
+			consts: HashMap::new(),
+		}
+	}
+
+	/// Gives access to the inference engine of the session.
+	#[must_use]
+	pub fn engine(&self) -> RefMut<'a, Engine> {
+		self.session.engine()
+	}
+
 	/// Gets the named type associated to the provided `Id`.
 	///
 	/// # Panics
@@ -64,21 +127,130 @@ impl Mappings {
 		self.var_tys.insert(id, ty);
 	}
 
-	/// Gets the `Id` associated to the provided `TypeId`.
-	///
-	/// This function is really inefficient and should only be called for
-	/// debugging purposes.
-	pub fn get_id_from_ty(&self, ty: TypeId) -> Option<Id> {
-		for (id, t) in &self.var_tys {
-			if t.value == ty {
-				return Some(*id);
-			}
-		}
-		for (id, t) in &self.named_tys {
-			if t.value == ty {
-				return Some(*id);
-			}
-		}
-		None
+	/// Gets the declaration of the struct with the provided `Id`, if it is a
+	/// struct at all.
+	#[must_use]
+	pub fn get_struct(&self, id: Id) -> Option<&Struct> {
+		self.structs.get(&id)
+	}
+
+	/// Registers the declaration of a struct so that it can be instantiated.
+	pub fn insert_struct(&mut self, id: Id, r#struct: Struct) {
+		self.structs.insert(id, r#struct);
+	}
+
+	/// Gets the declaration of the enum with the provided `Id`, if it is an
+	/// enum at all.
+	#[must_use]
+	pub fn get_enum(&self, id: Id) -> Option<&Enum> {
+		self.enums.get(&id)
+	}
+
+	/// Registers the declaration of an enum so that its variants can be used.
+	pub fn insert_enum(&mut self, id: Id, r#enum: Enum) {
+		self.enums.insert(id, r#enum);
+	}
+
+	/// Gets the value of the const with the provided `Id`, if it is a const
+	/// and its value could be evaluated.
+	#[must_use]
+	pub fn get_const(&self, id: Id) -> Option<Value> {
+		self.consts.get(&id).copied()
+	}
+
+	/// Registers the value a const evaluated to.
+	pub fn insert_const(&mut self, id: Id, value: Value) {
+		self.consts.insert(id, value);
+	}
+
+	/// Registers the type of the expr at the provided `Span`.
+	pub fn insert_expr_ty(&mut self, expr: Span, ty: Spanned<TypeId>) {
+		self.expr_tys.insert(expr, ty);
+	}
+
+	/// Gets the type of every expr, along with the span of the expr.
+	pub fn expr_tys(&self) -> impl Iterator<Item = (Span, Spanned<TypeId>)> + '_ {
+		self.expr_tys.iter().map(|(span, ty)| (*span, *ty))
+	}
+
+	/// Gets the variable type of every variable, argument, const and function.
+	pub fn var_tys(&self) -> impl Iterator<Item = (Id, Spanned<TypeId>)> + '_ {
+		self.var_tys.iter().map(|(id, ty)| (*id, *ty))
+	}
+
+	/// Returns whether the value of the expr at the provided `Span` has to be
+	/// wrapped into an optional.
+	#[must_use]
+	pub fn is_coerced(&self, expr: Span) -> bool {
+		self.coercions.contains(&expr)
+	}
+
+	/// Registers that the value of the expr at the provided `Span` has to be
+	/// wrapped into an optional.
+	pub fn insert_coercion(&mut self, expr: Span) {
+		self.coercions.insert(expr);
+	}
+
+	/// Gets every cast inferred so far, along with the type of the value being
+	/// cast and the type it casts to.
+	#[must_use]
+	pub fn casts(&self) -> &[(Span, Spanned<TypeId>, Spanned<TypeId>)] {
+		&self.casts
+	}
+
+	/// Registers the cast at the provided `Span`, which casts a value of type
+	/// `from` to `to`.
+	pub fn insert_cast(&mut self, cast: Span, from: Spanned<TypeId>, to: Spanned<TypeId>) {
+		self.casts.push((cast, from, to));
+	}
+
+	/// Gets every number literal inferred so far, along with whether it is
+	/// negated.
+	#[must_use]
+	pub fn literals(&self) -> &[(Spanned<TypeId>, NumberLiteral, bool)] {
+		&self.literals
+	}
+
+	/// Registers a number literal of type `ty`, which is negated if `negated` is
+	/// set.
+	pub fn insert_literal(&mut self, ty: Spanned<TypeId>, literal: NumberLiteral, negated: bool) {
+		self.literals.push((ty, literal, negated));
+	}
+
+	/// Gets the type of every array index inferred so far.
+	#[must_use]
+	pub fn indices(&self) -> &[Spanned<TypeId>] {
+		&self.indices
+	}
+
+	/// Registers an array index of type `ty`.
+	pub fn insert_index(&mut self, ty: Spanned<TypeId>) {
+		self.indices.push(ty);
+	}
+
+	/// Gets the declared return type of the function whose body is being
+	/// inferred, if any.
+	#[must_use]
+	pub fn return_ty(&self) -> Option<Spanned<TypeId>> {
+		self.return_ty
+	}
+
+	/// Sets the declared return type of the function whose body is about to
+	/// be inferred, returning the previous one so that it can be restored.
+	pub fn replace_return_ty(&mut self, ty: Option<Spanned<TypeId>>) -> Option<Spanned<TypeId>> {
+		std::mem::replace(&mut self.return_ty, ty)
+	}
+
+	/// Gets the type of the values the innermost loop being inferred is broken
+	/// out of with, if any.
+	#[must_use]
+	pub fn break_ty(&self) -> Option<Spanned<TypeId>> {
+		self.break_ty
+	}
+
+	/// Sets the break type of the loop whose body is about to be inferred,
+	/// returning the previous one so that it can be restored.
+	pub fn replace_break_ty(&mut self, ty: Option<Spanned<TypeId>>) -> Option<Spanned<TypeId>> {
+		std::mem::replace(&mut self.break_ty, ty)
 	}
 }
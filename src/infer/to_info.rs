@@ -1,5 +1,4 @@
 use super::{
-	engine,
 	type_info::{TypeId, TypeInfo}, mappings::Mappings,
 };
 use crate::common::span::{Add, Spanned};
@@ -8,7 +7,7 @@ pub trait ToInfo {
 	fn to_info(&self, mappings: &mut Mappings) -> Spanned<TypeInfo>;
 	fn convert_and_add(&self, mappings: &mut Mappings) -> Spanned<TypeId> {
 		let info = self.to_info(mappings);
-		engine().add_ty(info.value).add_span(info.span)
+		mappings.engine().add_ty(info.value).add_span(info.span)
 	}
 }
 
@@ -22,7 +22,34 @@ pub enum TypeInfo {
 		return_ty: Spanned<TypeId>,
 		args: Vec<Spanned<TypeId>>,
 		generics: Vec<Spanned<TypeId>>,
+		/// Whether calling the function is unsafe, which must match for two
+		/// signatures to unify.
+		is_unsafe: bool,
 	},
+	/// An instance of the struct `id`, with its generics filled in. The name is
+	/// only kept around for diagnostics.
+	Struct {
+		id: Id,
+		name: String,
+		generics: Vec<Spanned<TypeId>>,
+	},
+	/// A value of the enum `id`, whose payload types live in `Engine::enums`
+	/// since they're the same for every instance. The name is only kept around
+	/// for diagnostics.
+	Enum { id: Id, name: String },
+	/// A reference to a value of the inner type, through which the value can
+	/// be changed if the reference is mutable.
+	Ref(Spanned<TypeId>, bool),
+	/// A raw pointer to a value of the inner type, mutable or not just like
+	/// `TypeInfo::Ref`.
+	Ptr(Spanned<TypeId>, bool),
+	/// Either a value of the inner type or nothing. Values of the inner type
+	/// only become optional at sites that expect one, see `Engine::coerce`.
+	Optional(Spanned<TypeId>),
+	/// A fixed amount of values of possibly different types.
+	Tuple(Vec<Spanned<TypeId>>),
+	/// A fixed amount of values of the inner type.
+	Array(Spanned<TypeId>, usize),
 	/// This type is passed in as a generic to a function/struct/class. It does
 	/// not unify with anything, it simply is a type that we don't know in the
 	/// function/struct/class body that varies depending on who calls it.
@@ -38,9 +65,32 @@ pub enum TypeInfo {
 }
 
 impl TypeInfo {
+	/// Returns the `BuiltIn` this type is, if any. Numbers whose width was
+	/// never determined default to `i32`, `u32` or `f64`.
+	#[must_use]
+	pub fn as_builtin(&self) -> Option<BuiltIn> {
+		match self {
+			TypeInfo::BuiltIn(x) => Some(x.clone()),
+			TypeInfo::Number(None) => Some(BuiltIn::Integer {
+				bits: Some(32),
+				signed: true,
+			}),
+			TypeInfo::Number(Some(NumberLiteralType::Integer { signed, .. })) => {
+				Some(BuiltIn::Integer {
+					bits: Some(32),
+					signed: *signed,
+				})
+			}
+			TypeInfo::Number(Some(NumberLiteralType::Float { .. })) => {
+				Some(BuiltIn::Float { bits: 64 })
+			}
+			_ => None,
+		}
+	}
+
 	pub fn display_custom(&self, follow_ref: impl Fn(&Spanned<usize>) -> String + Clone) -> String {
 		match self {
-			TypeInfo::Unknown => "?".into(),
+			TypeInfo::Unknown => "[unknown]".into(),
 			TypeInfo::SameAs(x) => follow_ref(x),
 			TypeInfo::BuiltIn(x) => format!("{x}"),
 			TypeInfo::Number(x) => match x {
@@ -51,17 +101,37 @@ impl TypeInfo {
 				return_ty,
 				args,
 				generics,
+				is_unsafe,
 			} => format!(
-				"[{}{} -> {}]",
+				"[{}{}({}) -> {}]",
+				if *is_unsafe { "unsafe " } else { "" },
 				(&generics
 					.iter()
 					.map(follow_ref.clone())
 					.collect::<Vec<String>>())
 					.join_comma_wrapped("<", ">"),
 				(&args.iter().map(follow_ref.clone()).collect::<Vec<String>>())
-					.join_comma_wrapped("(", ")"),
+					.join_comma_or_empty(),
 				follow_ref(return_ty)
 			),
+			TypeInfo::Struct { name, generics, .. } => format!(
+				"{name}{}",
+				(&generics.iter().map(follow_ref).collect::<Vec<String>>())
+					.join_comma_wrapped("<", ">")
+			),
+			TypeInfo::Enum { name, .. } => name.clone(),
+			TypeInfo::Ref(x, mutable) => {
+				format!("&{}{}", if *mutable { "mut " } else { "" }, follow_ref(x))
+			}
+			TypeInfo::Ptr(x, mutable) => {
+				format!("*{}{}", if *mutable { "mut " } else { "" }, follow_ref(x))
+			}
+			TypeInfo::Optional(x) => format!("{}?", follow_ref(x)),
+			TypeInfo::Tuple(x) => format!(
+				"({})",
+				(&x.iter().map(follow_ref).collect::<Vec<String>>()).join_comma_or_empty()
+			),
+			TypeInfo::Array(x, len) => format!("[{}; {len}]", follow_ref(x)),
 			TypeInfo::Generic(x) => format!("[generic @{x}]"),
 			TypeInfo::UnknownGeneric(x) => format!("[unresolved generic @{x}]"),
 			TypeInfo::Bottom => "[!]".into(),
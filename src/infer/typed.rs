@@ -0,0 +1,126 @@
+use super::{engine::Engine, mappings::Mappings, type_info::TypeId};
+use crate::{
+	common::{
+		diagnostics::uninferred_type,
+		ident::Id,
+		r#type::Type,
+		span::{Span, Spanned},
+	},
+	hoister::{HoistedExpr, HoistedScope},
+};
+use std::collections::{HashMap, HashSet};
+
+/// A program along with the type of everything in it, which is what inference
+/// produces. Types are fully resolved: there are no links between them left,
+/// and numbers whose width was never determined got their default one.
+pub struct Typed<'a> {
+	pub program: Spanned<HoistedScope>,
+	/// Everything else inference learned about the program, like the values of
+	/// consts and which values have to be wrapped into optionals.
+	pub mappings: Mappings<'a>,
+	/// The type of every expr, keyed by its span.
+	exprs: HashMap<Span, Spanned<Type>>,
+	/// The type of every variable, argument, const and function.
+	vars: HashMap<Id, Spanned<Type>>,
+	/// The payload types of every variant of every enum.
+	enums: HashMap<Id, Vec<Vec<Spanned<Type>>>>,
+}
+
+impl<'a> Typed<'a> {
+	/// Resolves every type inferred for `program` into `mappings`.
+	#[must_use]
+	pub fn new(program: Spanned<HoistedScope>, mappings: Mappings<'a>, engine: &Engine) -> Self {
+		let exprs = mappings
+			.expr_tys()
+			.map(|(span, ty)| (span, engine.resolve(ty)))
+			.collect();
+		let vars = mappings
+			.var_tys()
+			.map(|(id, ty)| (id, engine.resolve(ty)))
+			.collect();
+		let enums = engine
+			.enums
+			.iter()
+			.map(|(id, variants)| {
+				let variants = variants
+					.iter()
+					.map(|payload| payload.iter().map(|x| engine.resolve(*x)).collect())
+					.collect();
+				(*id, variants)
+			})
+			.collect();
+		Self {
+			program,
+			mappings,
+			exprs,
+			vars,
+			enums,
+		}
+	}
+
+	/// Gets the type of the provided expr.
+	///
+	/// # Panics
+	///
+	/// The function will panic if the expr isn't part of the program.
+	#[must_use]
+	pub fn expr_ty(&self, expr: &Spanned<HoistedExpr>) -> &Spanned<Type> {
+		self.exprs
+			.get(&expr.span)
+			.unwrap_or_else(|| panic!("tried to access type of nonexistent expr at {}", expr.span))
+	}
+
+	/// Gets the type of the variable, argument, const or function with the
+	/// provided `Id`.
+	///
+	/// # Panics
+	///
+	/// The function will panic if there is nothing with that `Id`.
+	#[must_use]
+	pub fn var_ty(&self, id: Id) -> &Spanned<Type> {
+		self.vars
+			.get(&id)
+			.unwrap_or_else(|| panic!("tried to access type of nonexistent var {id}"))
+	}
+
+	/// Gets the payload types of every variant of the enum with the provided
+	/// `Id`, or `None` if it's something else, like a struct or a generic.
+	#[must_use]
+	pub fn enum_payloads(&self, id: Id) -> Option<&[Vec<Spanned<Type>>]> {
+		self.enums.get(&id).map(Vec::as_slice)
+	}
+}
+
+/// Reports every type that was never inferred, once per unknown type no matter
+/// how many things have it, at the first of them in the code.
+pub fn report_uninferred(engine: &Engine, mappings: &Mappings) {
+	let mut tys: Vec<Spanned<TypeId>> = mappings
+		.var_tys()
+		.map(|(_, ty)| ty)
+		.chain(mappings.expr_tys().map(|(_, ty)| ty))
+		.collect();
+	tys.sort_by_key(|x| (x.span.file_id, x.span.start, x.span.end));
+	let mut reported = HashSet::new();
+	for ty in tys {
+		let unknowns = engine.unknowns(ty.value);
+		if unknowns.into_iter().filter(|x| reported.insert(*x)).count() > 0 {
+			uninferred_type(mappings.session, ty.span);
+		}
+	}
+}
+
+impl std::fmt::Display for Typed<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let mut vars: Vec<_> = self.vars.iter().collect();
+		vars.sort_by_key(|(id, _)| **id);
+		for (id, ty) in vars {
+			f.write_fmt(format_args!("@{id}: {ty}\n"))?;
+		}
+		let mut exprs: Vec<_> = self.exprs.iter().collect();
+		exprs.sort_by_key(|(span, _)| (span.file_id, span.start, span.end));
+		for (span, ty) in exprs {
+			f.write_fmt(format_args!("{span}: {ty}\n"))?;
+		}
+		Ok(())
+	}
+}
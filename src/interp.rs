@@ -0,0 +1,520 @@
+//! A tree-walking interpreter for resolved and type-inferred programs. It is
+//! nowhere near fast, but it gives us a reference semantics to test the
+//! backends against.
+
+use self::value::Value;
+use crate::{
+	common::{
+		expr::Expr,
+		ident::{Id, Ident},
+		pattern::Pattern,
+		r#type::{BuiltIn, Type},
+		span::{Span, Spanned},
+		stmt::Stmt,
+		visit::{walk_scope, Visit},
+	},
+	hoister::{HoistedExpr, HoistedFunc, HoistedScope},
+	infer::typed::Typed,
+	lexer::{NumberLiteral, Operator},
+	session::CompilerSession,
+};
+use codespan_reporting::diagnostic::{Diagnostic, Label};
+use std::collections::HashMap;
+
+pub mod value;
+
+pub const BOOL: BuiltIn = BuiltIn::Integer {
+	bits: Some(1),
+	signed: false,
+};
+
+/// How many calls may be nested before we give up and report a stack
+/// overflow. The native stack grows as needed, so this is the only limit.
+const MAX_CALL_DEPTH: usize = 2048;
+/// How much native stack evaluating an expr may use before more is allocated,
+/// which has to cover everything a call goes through before the exprs in the
+/// callee are evaluated.
+const STACK_RED_ZONE: usize = 256 * 1024;
+/// How much native stack is allocated at once when it runs out.
+const STACK_GROWTH: usize = 4 * 1024 * 1024;
+
+/// The reasons evaluation may stop before reaching the end of a scope.
+enum Unwind {
+	Return(Value),
+	Break(Value),
+	Continue,
+	Error(Diagnostic<usize>),
+}
+
+type Eval<T> = Result<T, Unwind>;
+
+fn error(span: Span, message: &str) -> Unwind {
+	Unwind::Error(
+		Diagnostic::error()
+			.with_message(format!("runtime error: {message}"))
+			.with_labels(vec![Label::primary(span.file_id, span.range())]),
+	)
+}
+
+/// Reports that the program uses something the interpreter can't run, even
+/// though it's valid.
+fn unsupported(span: Span, what: &str) -> Unwind {
+	Unwind::Error(
+		Diagnostic::error()
+			.with_message(format!("{what} are not supported by the interpreter"))
+			.with_labels(vec![Label::primary(span.file_id, span.range())]),
+	)
+}
+
+struct Interpreter<'a> {
+	typed: &'a Typed<'a>,
+	/// Every function in the program, indexed by its resolved id.
+	funcs: HashMap<Id, &'a Spanned<HoistedFunc>>,
+	/// The return type of the function currently being executed.
+	return_ty: BuiltIn,
+	/// The types of the loops currently being executed, innermost last.
+	break_tys: Vec<BuiltIn>,
+	depth: usize,
+}
+
+/// Every function in a program, however deeply it's nested, indexed by its
+/// resolved id.
+#[derive(Default)]
+struct Funcs<'a>(HashMap<Id, &'a Spanned<HoistedFunc>>);
+
+impl<'a> Visit<'a, HoistedScope> for Funcs<'a> {
+	fn scope(&mut self, scope: &'a HoistedScope) {
+		for (ident, func) in &scope.data.funcs {
+			self.0.insert(ident.id(), func);
+			if let Some(body) = &func.value.body {
+				self.scope(&body.value);
+			}
+		}
+		walk_scope(self, scope);
+	}
+}
+
+/// The variables of the function currently being executed. Nested funcs can't
+/// capture anything, so this is all a function can see.
+type Frame = HashMap<Id, Value>;
+
+impl Interpreter<'_> {
+	/// Returns the type of the variable (or function) `id`.
+	fn var_ty(&self, id: Id, span: Span) -> Eval<BuiltIn> {
+		self.builtin(self.typed.var_ty(id), span)
+	}
+
+	/// Returns the type of the value of `expr`.
+	fn expr_ty(&self, expr: &Spanned<HoistedExpr>) -> Eval<BuiltIn> {
+		self.builtin(self.typed.expr_ty(expr), expr.span)
+	}
+
+	/// Returns the `BuiltIn` `ty` is, if the interpreter supports it.
+	fn builtin(&self, ty: &Spanned<Type>, span: Span) -> Eval<BuiltIn> {
+		match &ty.value {
+			Type::BuiltIn(ty) if Value::supports(ty) => Ok(ty.clone()),
+			Type::BuiltIn(ty) => Err(unsupported(span, &format!("values of type {ty}"))),
+			Type::User(x) if self.typed.mappings.get_struct(x.id()).is_some() => {
+				Err(unsupported(span, "structs"))
+			}
+			Type::User(x) if self.typed.mappings.get_enum(x.id()).is_some() => {
+				Err(unsupported(span, "enums"))
+			}
+			// generics are shared by every call rather than instantiated per call, so
+			// they only stay generic if no call determined them
+			Type::User(_) => Err(unsupported(span, "generic functions")),
+			// only structs can have generics filled in
+			Type::Generic(..) => Err(unsupported(span, "structs")),
+			Type::Ref(..) | Type::Ptr(..) => Err(unsupported(span, "pointers")),
+			Type::Optional(_) => Err(unsupported(span, "optionals")),
+			Type::Func { .. } => Err(unsupported(span, "functions as values")),
+			Type::Tuple(_) => Err(unsupported(span, "tuples")),
+			Type::Array(..) => Err(unsupported(span, "arrays")),
+			Type::Inferred => Err(error(span, "could not infer type")),
+		}
+	}
+
+	/// Returns the return and argument types of the function `id`.
+	fn signature(&self, id: Id, span: Span) -> Eval<(BuiltIn, Vec<BuiltIn>)> {
+		let Type::Func { args, return_ty, .. } = &self.typed.var_ty(id).value else {
+			return Err(error(span, "called something that is not a function"));
+		};
+		Ok((
+			self.builtin(return_ty, span)?,
+			args.iter()
+				.map(|x| self.builtin(x, span))
+				.collect::<Eval<_>>()?,
+		))
+	}
+
+	fn call(&mut self, id: Id, args: Vec<Value>, span: Span) -> Eval<Value> {
+		let func = self.funcs[&id];
+		let Some(body) = &func.value.body else {
+			return Err(unsupported(span, "calls to external functions"));
+		};
+		if self.depth == MAX_CALL_DEPTH {
+			return Err(error(span, "stack overflow"));
+		}
+		let (return_ty, _) = self.signature(id, span)?;
+		let mut frame = Frame::new();
+		for (arg, value) in func.value.signature.args.value.iter().zip(args) {
+			if let Ident::Resolved(id) = arg.ident() {
+				frame.insert(*id, value);
+			}
+		}
+		let caller_return_ty = std::mem::replace(&mut self.return_ty, return_ty.clone());
+		self.depth += 1;
+		let result = self.scope(&body.value, &return_ty, &mut frame);
+		self.depth -= 1;
+		self.return_ty = caller_return_ty;
+		match result {
+			Ok(value) | Err(Unwind::Return(value)) => Ok(value),
+			Err(err) => Err(err),
+		}
+	}
+
+	/// Executes every stmt in `scope`, returning the yielded value (or void).
+	fn scope(&mut self, scope: &HoistedScope, ty: &BuiltIn, frame: &mut Frame) -> Eval<Value> {
+		for stmt in &scope.stmts {
+			match &stmt.value {
+				Stmt::Create { ty_id, value, .. } => {
+					let id = ty_id.ident().id();
+					if let Some(value) = value {
+						let var_ty = self.var_ty(id, ty_id.span)?;
+						let value = self.expr(value, &var_ty, frame)?;
+						frame.insert(id, value);
+					}
+				}
+				Stmt::Set { id, value } => {
+					let var_ty = self.var_ty(id.value.id(), id.span)?;
+					let value = self.expr(value, &var_ty, frame)?;
+					frame.insert(id.value.id(), value);
+				}
+				Stmt::Store { .. } => return Err(unsupported(stmt.span, "pointers")),
+				Stmt::Destructure { .. } => return Err(unsupported(stmt.span, "tuples")),
+				// imports can only be at the top level, where there's no code to run
+				Stmt::Func { .. }
+				| Stmt::Struct { .. }
+				| Stmt::Enum { .. }
+				| Stmt::Const { .. }
+				| Stmt::Import { .. } => unreachable!(),
+				Stmt::Return {
+					value,
+					is_yield: true,
+				} => return self.expr(value, ty, frame),
+				Stmt::Return {
+					value,
+					is_yield: false,
+				} => {
+					// the return type is figured out again by the function call
+					let value = self.expr_untyped(value, frame)?;
+					return Err(Unwind::Return(value));
+				}
+				Stmt::Break { value } => {
+					// the checker makes sure we can only break from inside a loop
+					let ty = self.break_tys.last().expect("break outside of a loop").clone();
+					let value = match value {
+						Some(value) => self.expr(value, &ty, frame)?,
+						None => Value::Void,
+					};
+					return Err(Unwind::Break(value));
+				}
+				Stmt::Continue => return Err(Unwind::Continue),
+			}
+		}
+		Ok(Value::Void)
+	}
+
+	fn literal(literal: &NumberLiteral, ty: &BuiltIn) -> Value {
+		match *ty {
+			BuiltIn::Integer { bits, signed } => {
+				let bits = bits.unwrap_or(crate::codegen::ty::POINTER_WIDTH);
+				let value = literal
+					.as_u128()
+					.expect("literals out of range of their type are reported during inference");
+				Value::int(bits, signed, value)
+			}
+			BuiltIn::Float { bits } => Value::float(bits, literal.as_f64()),
+			BuiltIn::Void => Value::Void,
+			// number literals only ever unify with numeric types
+			BuiltIn::Char | BuiltIn::Str => unreachable!("number literal of type {ty}"),
+		}
+	}
+
+	/// Runs one iteration of the body of a loop of type `ty`, returning the value
+	/// the loop was broken out of with, if it was.
+	fn iteration(
+		&mut self,
+		body: &Spanned<HoistedExpr>,
+		ty: &BuiltIn,
+		frame: &mut Frame,
+	) -> Eval<Option<Value>> {
+		self.break_tys.push(ty.clone());
+		let result = self.expr(body, &BuiltIn::Void, frame);
+		self.break_tys.pop();
+		match result {
+			Ok(_) | Err(Unwind::Continue) => Ok(None),
+			Err(Unwind::Break(value)) => Ok(Some(value)),
+			Err(err) => Err(err),
+		}
+	}
+
+	/// Runs a for loop of type `ty`, counting up from `start` to `end`.
+	fn for_expr(
+		&mut self,
+		var: &Spanned<Ident>,
+		(start, end): (&Spanned<HoistedExpr>, &Spanned<HoistedExpr>),
+		body: &Spanned<HoistedExpr>,
+		ty: &BuiltIn,
+		frame: &mut Frame,
+	) -> Eval<Option<Value>> {
+		let bound_ty = self.builtin(self.typed.expr_ty(start), var.span)?;
+		let BuiltIn::Integer { bits, signed } = bound_ty else {
+			return Err(unsupported(var.span, "ranges over non-integers"));
+		};
+		let one = Value::int(bits.unwrap_or(crate::codegen::ty::POINTER_WIDTH), signed, 1);
+		let mut current = self.expr(start, &bound_ty, frame)?;
+		let end = self.expr(end, &bound_ty, frame)?;
+		let in_range = |current| Value::compare(Operator::Lt, current, end).map(Value::is_true);
+		while in_range(current).map_err(|x| error(var.span, x))? {
+			if let Ident::Resolved(id) = var.value {
+				frame.insert(id, current);
+			}
+			if let Some(value) = self.iteration(body, ty, frame)? {
+				return Ok(Some(value));
+			}
+			current = Value::binary(Operator::Plus, current, one).map_err(|x| error(var.span, x))?;
+		}
+		Ok(None)
+	}
+
+	/// Evaluates the arm of the first pattern the value of a match expression of
+	/// type `ty` matches.
+	fn match_expr(
+		&mut self,
+		value: &Spanned<HoistedExpr>,
+		arms: &[(Spanned<Pattern>, Spanned<HoistedExpr>)],
+		ty: &BuiltIn,
+		frame: &mut Frame,
+	) -> Eval<Value> {
+		let value_ty = self.expr_ty(value)?;
+		let value = self.expr(value, &value_ty, frame)?;
+		for (pattern, arm) in arms {
+			if Self::matches(pattern, value, &value_ty, frame)? {
+				return self.expr(arm, ty, frame);
+			}
+		}
+		unreachable!("inference made sure every value is matched by some arm")
+	}
+
+	/// Returns whether `value`, of type `ty`, matches `pattern`, binding it to
+	/// the var of the pattern if it has one.
+	fn matches(
+		pattern: &Spanned<Pattern>,
+		value: Value,
+		ty: &BuiltIn,
+		frame: &mut Frame,
+	) -> Eval<bool> {
+		let literal = match &pattern.value {
+			Pattern::Binding(ident) => {
+				if let Ident::Resolved(id) = ident {
+					frame.insert(*id, value);
+				}
+				return Ok(true);
+			}
+			Pattern::NumberLiteral(x) => Self::literal(x, ty),
+			Pattern::CharLiteral(x) => Value::int(32, false, u128::from(u32::from(*x))),
+			Pattern::Variant { .. } => return Err(unsupported(pattern.span, "enums")),
+		};
+		Value::compare(Operator::Eq, value, literal)
+			.map(Value::is_true)
+			.map_err(|x| error(pattern.span, x))
+	}
+
+	/// Evaluates an expression whose type isn't known from its context. This is
+	/// only the case for `return`, whose type comes from the function instead.
+	fn expr_untyped(&mut self, expr: &Spanned<HoistedExpr>, frame: &mut Frame) -> Eval<Value> {
+		let ty = self.return_ty.clone();
+		self.expr(expr, &ty, frame)
+	}
+
+	/// Evaluates `expr`, whose type is `ty`.
+	fn expr(
+		&mut self,
+		expr: &Spanned<HoistedExpr>,
+		ty: &BuiltIn,
+		frame: &mut Frame,
+	) -> Eval<Value> {
+		// evaluation recurses along with the program, which may go deeper than the
+		// stack of whichever thread we run on allows
+		stacker::maybe_grow(STACK_RED_ZONE, STACK_GROWTH, || self.eval(expr, ty, frame))
+	}
+
+	// the bigger exprs are already evaluated by methods of their own, the arms
+	// left here are short but there are many of them
+	#[allow(clippy::too_many_lines)]
+	fn eval(
+		&mut self,
+		expr: &Spanned<HoistedExpr>,
+		ty: &BuiltIn,
+		frame: &mut Frame,
+	) -> Eval<Value> {
+		match &expr.value {
+			Expr::NumberLiteral(x) => Ok(Self::literal(x, ty)),
+			Expr::CharLiteral(x) => Ok(Value::int(32, false, u128::from(u32::from(*x)))),
+			Expr::StringLiteral(_) => Err(unsupported(expr.span, "strings")),
+			Expr::Identifier(x) if self.funcs.contains_key(&x.id()) => {
+				Err(unsupported(expr.span, "functions as values"))
+			}
+			// consts were already evaluated before inference
+			Expr::Identifier(x) => match self.typed.mappings.get_const(x.id()) {
+				Some(value) => Ok(value),
+				None => frame
+					.get(&x.id())
+					.copied()
+					.ok_or_else(|| error(expr.span, "used a variable before assigning to it")),
+			},
+			Expr::BinaryOp(lhs, op, rhs) if op.value.is_logical() => {
+				let lhs = self.expr(lhs, &BOOL, frame)?;
+				// the rhs is only evaluated if the lhs doesn't already decide the result
+				if lhs.is_true() == (op.value == Operator::Or) {
+					Ok(lhs)
+				} else {
+					self.expr(rhs, &BOOL, frame)
+				}
+			}
+			Expr::BinaryOp(_, op, _) if op.value == Operator::UnwrapOr => {
+				Err(unsupported(expr.span, "optionals"))
+			}
+			Expr::BinaryOp(lhs, op, rhs) if op.value.is_comparison() => {
+				let operand_ty = self.expr_ty(lhs)?;
+				let lhs = self.expr(lhs, &operand_ty, frame)?;
+				let rhs = self.expr(rhs, &operand_ty, frame)?;
+				Value::compare(op.value, lhs, rhs).map_err(|x| error(expr.span, x))
+			}
+			Expr::BinaryOp(lhs, op, rhs) => {
+				let lhs = self.expr(lhs, ty, frame)?;
+				let rhs = self.expr(rhs, ty, frame)?;
+				Value::binary(op.value, lhs, rhs).map_err(|x| error(expr.span, x))
+			}
+			Expr::UnaryOp(op, value) => {
+				let value = self.expr(value, ty, frame)?;
+				Value::unary(op.value, value).map_err(|x| error(expr.span, x))
+			}
+			Expr::Scope(scope) | Expr::Unsafe(scope) => self.scope(scope, ty, frame),
+			Expr::Call { callee, .. } if matches!(callee.value, Expr::Variant { .. }) => {
+				Err(unsupported(expr.span, "enums"))
+			}
+			Expr::Call { callee, args, .. } => {
+				let Expr::Identifier(Ident::Resolved(id)) = callee.value else {
+					return Err(unsupported(callee.span, "calls to anything but named functions"));
+				};
+				if !self.funcs.contains_key(&id) {
+					return Err(unsupported(callee.span, "functions as values"));
+				}
+				let (_, arg_tys) = self.signature(id, callee.span)?;
+				let mut values = Vec::new();
+				for (arg, arg_ty) in args.iter().zip(arg_tys) {
+					values.push(self.expr(arg, &arg_ty, frame)?);
+				}
+				self.call(id, values, expr.span)
+			}
+			Expr::If {
+				cond,
+				then,
+				otherwise,
+			} => {
+				if self.expr(cond, &BOOL, frame)?.is_true() {
+					self.expr(then, ty, frame)
+				} else if let Some(otherwise) = otherwise {
+					self.expr(otherwise, ty, frame)
+				} else {
+					Ok(Value::Void)
+				}
+			}
+			Expr::While {
+				cond,
+				body,
+				otherwise,
+			} => {
+				while self.expr(cond, &BOOL, frame)?.is_true() {
+					if let Some(value) = self.iteration(body, ty, frame)? {
+						return Ok(value);
+					}
+				}
+				otherwise.as_ref().map_or(Ok(Value::Void), |x| self.expr(x, ty, frame))
+			}
+			Expr::For {
+				var,
+				start,
+				end,
+				body,
+				otherwise,
+			} => match self.for_expr(var, (start, end), body, ty, frame)? {
+				Some(value) => Ok(value),
+				None => otherwise.as_ref().map_or(Ok(Value::Void), |x| self.expr(x, ty, frame)),
+			},
+			Expr::Construct { .. } | Expr::Field { .. } => {
+				Err(unsupported(expr.span, "structs"))
+			}
+			Expr::Ref { .. } | Expr::Deref(_) => Err(unsupported(expr.span, "pointers")),
+			Expr::None | Expr::Try(_) => Err(unsupported(expr.span, "optionals")),
+			Expr::Cast { value, .. } => {
+				let from = self.expr_ty(value)?;
+				let value = self.expr(value, &from, frame)?;
+				value.cast(ty).map_err(|x| error(expr.span, x))
+			}
+			Expr::Closure { .. } => Err(unsupported(expr.span, "closures")),
+			Expr::Tuple(_) | Expr::TupleField { .. } => {
+				Err(unsupported(expr.span, "tuples"))
+			}
+			Expr::Array(_) | Expr::Index { .. } => Err(unsupported(expr.span, "arrays")),
+			Expr::Variant { .. } => Err(unsupported(expr.span, "enums")),
+			Expr::Match { value, arms } => self.match_expr(value, arms, ty, frame),
+		}
+	}
+}
+
+/// Runs the `main` function of `typed`. Returns whatever `main` returned, or
+/// `None` if a runtime error occurred (and was reported).
+#[must_use]
+pub fn run(session: &CompilerSession, typed: &Typed) -> Option<Value> {
+	let scope = &typed.program.value;
+	let mut funcs = Funcs::default();
+	funcs.scope(scope);
+	let mut interpreter = Interpreter {
+		typed,
+		funcs: funcs.0,
+		return_ty: BuiltIn::Void,
+		break_tys: Vec::new(),
+		depth: 0,
+	};
+	let Some((id, _)) = scope
+		.data
+		.funcs
+		.iter()
+		.find(|(_, func)| func.value.id.value == Ident::Named("main".into()))
+	else {
+		session.add_diagnostic(Diagnostic::error().with_message("no main function to run"));
+		return None;
+	};
+	let main = &scope.data.funcs[id];
+	let args = &main.value.signature.args;
+	if !args.value.is_empty() {
+		session.add_diagnostic(
+			Diagnostic::error()
+				.with_message("main must take no arguments")
+				.with_labels(vec![Label::primary(args.span.file_id, args.span.range())
+					.with_message("there's nothing to pass to these when running the program")]),
+		);
+		return None;
+	}
+	match interpreter.call(id.id(), Vec::new(), main.span) {
+		Ok(value) => Some(value),
+		Err(Unwind::Error(diagnostic)) => {
+			session.add_diagnostic(diagnostic);
+			None
+		}
+		Err(Unwind::Return(_) | Unwind::Break(_) | Unwind::Continue) => unreachable!(),
+	}
+}
@@ -0,0 +1,301 @@
+use crate::{codegen::ty::POINTER_WIDTH, common::r#type::BuiltIn, lexer::Operator};
+
+/// A `Value` is what expressions evaluate to in the interpreter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+	/// An integer `bits` wide, stored as its two's complement representation.
+	/// Every operation wraps around, just like it would in LLVM.
+	Int { bits: u32, signed: bool, value: u128 },
+	/// A float `bits` wide. Rust has no stable 16 or 128 bit floats, so halves
+	/// are computed with `f32` precision and quads with `f64` precision.
+	Float { bits: u8, value: f64 },
+	Void,
+}
+
+fn mask(bits: u32) -> u128 {
+	if bits >= 128 {
+		u128::MAX
+	} else {
+		(1 << bits) - 1
+	}
+}
+
+impl Value {
+	/// Creates an integer value, wrapping `value` around to fit in `bits`.
+	#[must_use]
+	pub fn int(bits: u32, signed: bool, value: u128) -> Self {
+		Self::Int {
+			bits,
+			signed,
+			value: value & mask(bits),
+		}
+	}
+
+	/// Creates a float value, rounding `value` to the precision of the type.
+	#[must_use]
+	pub fn float(bits: u8, value: f64) -> Self {
+		// (lossy on purpose, we want the closest value of the target type)
+		#[allow(clippy::cast_possible_truncation)]
+		let value = if bits <= 32 {
+			f64::from(value as f32)
+		} else {
+			value
+		};
+		Self::Float { bits, value }
+	}
+
+	/// Returns whether the value is a `bool` holding `true`.
+	#[must_use]
+	pub fn is_true(self) -> bool {
+		matches!(self, Self::Int { value: 1, .. })
+	}
+
+	/// Returns whether values of type `builtin` can be represented by a
+	/// `Value`, which isn't the case for integers wider than 128 bits.
+	#[must_use]
+	pub fn supports(builtin: &BuiltIn) -> bool {
+		match builtin {
+			BuiltIn::Integer { bits, .. } => bits.unwrap_or(POINTER_WIDTH) <= 128,
+			BuiltIn::Char | BuiltIn::Float { .. } | BuiltIn::Void => true,
+			BuiltIn::Str => false,
+		}
+	}
+
+	/// Returns the integer as a signed number, sign-extending it if its type is
+	/// signed.
+	fn as_i128(bits: u32, signed: bool, value: u128) -> i128 {
+		if signed && bits < 128 && value >> (bits - 1) & 1 == 1 {
+			// the value is negative, so fill in the upper bits with ones
+			#[allow(clippy::cast_possible_wrap)]
+			let value = (value | !mask(bits)) as i128;
+			value
+		} else {
+			#[allow(clippy::cast_possible_wrap)]
+			let value = value as i128;
+			value
+		}
+	}
+
+	/// Returns whether integers `bits` wide can hold `value`.
+	fn fits(bits: u32, signed: bool, value: i128) -> bool {
+		match (signed, bits) {
+			(true, 128..) | (false, 127..) => value >= 0 || signed,
+			(true, _) => (-(1 << (bits - 1))..1 << (bits - 1)).contains(&value),
+			(false, _) => (0..1 << bits).contains(&value),
+		}
+	}
+
+	/// Returns the integer as a number, or `None` if the value isn't an integer
+	/// or doesn't fit in an `i128`.
+	#[must_use]
+	pub fn as_integer(self) -> Option<i128> {
+		match self {
+			Self::Int {
+				bits,
+				signed: false,
+				value,
+			} if bits >= 128 => i128::try_from(value).ok(),
+			Self::Int {
+				bits,
+				signed,
+				value,
+			} => Some(Self::as_i128(bits, signed, value)),
+			Self::Float { .. } | Self::Void => None,
+		}
+	}
+
+	/// Returns whether applying the arithmetic operator `op` on both integers,
+	/// which must have the same type, gives a result out of the range of their
+	/// type. `Value::binary` wraps such results around instead.
+	#[must_use]
+	pub fn overflows(op: Operator, lhs: Self, rhs: Self) -> bool {
+		let (
+			Self::Int {
+				bits,
+				signed,
+				value: x,
+			},
+			Self::Int { value: y, .. },
+		) = (lhs, rhs)
+		else {
+			return false;
+		};
+		// the only type whose values don't all fit in an i128
+		if !signed && bits >= 128 {
+			return match op {
+				Operator::Plus => x.checked_add(y).is_none(),
+				Operator::Neg => x.checked_sub(y).is_none(),
+				Operator::Star => x.checked_mul(y).is_none(),
+				_ => false,
+			};
+		}
+		let x = Self::as_i128(bits, signed, x);
+		let y = Self::as_i128(bits, signed, y);
+		let result = match op {
+			Operator::Plus => x.checked_add(y),
+			Operator::Neg => x.checked_sub(y),
+			Operator::Star => x.checked_mul(y),
+			Operator::Div => x.checked_div(y),
+			_ => return false,
+		};
+		result.is_none_or(|x| !Self::fits(bits, signed, x))
+	}
+
+	/// Applies the binary operator `op` on both values, which must have the same
+	/// type. Returns an error message if the operation is invalid.
+	pub fn binary(op: Operator, lhs: Self, rhs: Self) -> Result<Self, &'static str> {
+		match (lhs, rhs) {
+			(
+				Self::Int {
+					bits,
+					signed,
+					value: x,
+				},
+				Self::Int { value: y, .. },
+			) => {
+				let value = match op {
+					Operator::Plus => x.wrapping_add(y),
+					Operator::Neg => x.wrapping_sub(y),
+					Operator::Star => x.wrapping_mul(y),
+					Operator::Div if y == 0 => return Err("division by zero"),
+					Operator::Div if signed => {
+						let x = Self::as_i128(bits, signed, x);
+						let y = Self::as_i128(bits, signed, y);
+						#[allow(clippy::cast_sign_loss)]
+						let value = x.wrapping_div(y) as u128;
+						value
+					}
+					Operator::Div => x / y,
+					_ => return Err("unsupported operator"),
+				};
+				Ok(Self::int(bits, signed, value))
+			}
+			(Self::Float { bits, value: x }, Self::Float { value: y, .. }) => {
+				let value = match op {
+					Operator::Plus => x + y,
+					Operator::Neg => x - y,
+					Operator::Star => x * y,
+					Operator::Div => x / y,
+					_ => return Err("unsupported operator"),
+				};
+				Ok(Self::float(bits, value))
+			}
+			_ => Err("mismatched operand types"),
+		}
+	}
+
+	/// Converts the value to the numeric type `ty`. Integers wrap around like
+	/// in LLVM, while floats that don't fit in an integer saturate. Returns an
+	/// error message if either type isn't numeric.
+	pub fn cast(self, ty: &BuiltIn) -> Result<Self, &'static str> {
+		match (self, ty) {
+			(
+				Self::Int {
+					bits,
+					signed,
+					value,
+				},
+				BuiltIn::Integer {
+					bits: to,
+					signed: to_signed,
+				},
+			) => {
+				// (the two's complement representation is exactly what we're after)
+				#[allow(clippy::cast_sign_loss)]
+				let value = Self::as_i128(bits, signed, value) as u128;
+				Ok(Self::int(to.unwrap_or(POINTER_WIDTH), *to_signed, value))
+			}
+			(
+				Self::Int {
+					bits,
+					signed,
+					value,
+				},
+				BuiltIn::Float { bits: to },
+			) => {
+				// (lossy on purpose, casts round to the closest value of the target type)
+				#[allow(clippy::cast_precision_loss)]
+				let value = if signed {
+					Self::as_i128(bits, signed, value) as f64
+				} else {
+					value as f64
+				};
+				Ok(Self::float(*to, value))
+			}
+			(Self::Float { value, .. }, BuiltIn::Integer { bits, signed }) => {
+				let bits = bits.unwrap_or(POINTER_WIDTH);
+				// (rust's float to int casts saturate, so we only have to clamp)
+				#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+				let value = if *signed {
+					let max = i128::MAX >> (128 - bits);
+					(value as i128).clamp(!max, max) as u128
+				} else {
+					(value as u128).min(mask(bits))
+				};
+				Ok(Self::int(bits, *signed, value))
+			}
+			(Self::Float { value, .. }, BuiltIn::Float { bits }) => Ok(Self::float(*bits, value)),
+			_ => Err("cast between non-numeric types"),
+		}
+	}
+
+	/// Compares both values, which must have the same type, with the comparison
+	/// operator `op`. Returns an error message if the comparison is invalid.
+	pub fn compare(op: Operator, lhs: Self, rhs: Self) -> Result<Self, &'static str> {
+		let ordering = match (lhs, rhs) {
+			(
+				Self::Int {
+					bits,
+					signed,
+					value: x,
+				},
+				Self::Int { value: y, .. },
+			) => Some(Self::as_i128(bits, signed, x).cmp(&Self::as_i128(bits, signed, y))),
+			(Self::Float { value: x, .. }, Self::Float { value: y, .. }) => x.partial_cmp(&y),
+			_ => return Err("mismatched operand types"),
+		};
+		// a missing ordering means one of the floats is NaN, which only `!=` accepts
+		let result = ordering.is_some_and(|ordering| match op {
+			Operator::Eq => ordering.is_eq(),
+			Operator::Ne => ordering.is_ne(),
+			Operator::Lt => ordering.is_lt(),
+			Operator::Gt => ordering.is_gt(),
+			Operator::Le => ordering.is_le(),
+			Operator::Ge => ordering.is_ge(),
+			_ => unreachable!(),
+		}) || (ordering.is_none() && op == Operator::Ne);
+		Ok(Self::int(1, false, u128::from(result)))
+	}
+
+	/// Applies the unary operator `op` on the value. Returns an error message if
+	/// the operation is invalid.
+	pub fn unary(op: Operator, value: Self) -> Result<Self, &'static str> {
+		match (op, value) {
+			(
+				Operator::Neg,
+				Self::Int {
+					bits,
+					signed,
+					value,
+				},
+			) => Ok(Self::int(bits, signed, value.wrapping_neg())),
+			(Operator::Neg, Self::Float { bits, value }) => Ok(Self::float(bits, -value)),
+			_ => Err("unsupported operator"),
+		}
+	}
+}
+
+impl std::fmt::Display for Value {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match *self {
+			Self::Int {
+				bits,
+				signed: true,
+				value,
+			} => f.write_fmt(format_args!("{}", Self::as_i128(bits, true, value))),
+			Self::Int { value, .. } => f.write_fmt(format_args!("{value}")),
+			Self::Float { value, .. } => f.write_fmt(format_args!("{value}")),
+			Self::Void => f.write_str("void"),
+		}
+	}
+}
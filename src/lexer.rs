@@ -1,6 +1,6 @@
-use crate::common::{
-	diagnostics::add_diagnostics,
-	span::{Span, SpannedRaw},
+use crate::{
+	common::span::{Span, SpannedRaw},
+	session::CompilerSession,
 };
 use chumsky::Span as _;
 use codespan_reporting::diagnostic::{Diagnostic, Label};
@@ -64,6 +64,43 @@ pub struct NumberLiteral {
 	pub ty: Option<NumberLiteralType>,
 }
 
+impl NumberLiteral {
+	/// Returns the radix of the literal along with its digits, stripped of any
+	/// prefix and `_` separators.
+	#[must_use]
+	pub fn digits(&self) -> (u32, String) {
+		let (radix, digits) = match self.kind {
+			NumberLiteralKind::Decimal => (10, self.value.as_str()),
+			NumberLiteralKind::Binary => (2, &self.value[2..]),
+			NumberLiteralKind::Octal => (8, &self.value[2..]),
+			NumberLiteralKind::Hex => (16, &self.value[2..]),
+		};
+		(radix, digits.replace('_', ""))
+	}
+
+	/// Returns the value of an integer literal, or `None` if it doesn't fit in a
+	/// `u128` or has a decimal part.
+	#[must_use]
+	pub fn as_u128(&self) -> Option<u128> {
+		let (radix, digits) = self.digits();
+		u128::from_str_radix(&digits, radix).ok()
+	}
+
+	/// Returns the value of the literal as the closest `f64`.
+	#[must_use]
+	pub fn as_f64(&self) -> f64 {
+		let (radix, digits) = self.digits();
+		if radix == 10 {
+			// we know the regex only lets valid floats through
+			digits.parse().unwrap()
+		} else {
+			digits.chars().fold(0., |acc, digit| {
+				acc * f64::from(radix) + f64::from(digit.to_digit(radix).unwrap())
+			})
+		}
+	}
+}
+
 fn skip_first(s: &str) -> Option<&str> {
 	let s = s.chars().next().map(|c| &s[c.len_utf8()..]);
 	s.filter(|x| !x.is_empty())
@@ -207,9 +244,14 @@ macro_rules! def_token {
 			/// Note: in hexadecimal values, the `f` suffix is changed to `p`, since `f` already represents a hexadecimal value.
 			#[regex(r"(?:([0-9][0-9_]*|(?:[0-9][0-9_]*)?\.[0-9][0-9_]*|0b[01][01_]*|0o[0-7][0-7_]*)(i(?:z|[0-9]*)|u(?:z|[0-9]*)?|f(?:16|32|64|128)?)?|(0x[0-9a-fA-F][0-9a-fA-F_]*)(i(?:z|[0-9]*)|u(?:z|[0-9]*)?|p(?:16|32|64|128)?)?)", parse_number_literal)]
 			NumberLiteral(NumberLiteral),
-			#[regex(r"'.'", lex_to_str)]
+			/// Represents a char literal, like `'a'` or `'\n'`. Escape sequences are
+			/// only decoded by the parser, which also makes sure there's exactly one
+			/// char.
+			#[regex(r"'(?:[^'\\\n]|\\[^\n])*'", lex_to_str)]
 			CharLiteral(String),
-			#[regex(r#""(?:[^"]|\\")*""#, lex_to_str)]
+			/// Represents a string literal, like `"hello\n"`, escape sequences
+			/// included.
+			#[regex(r#""(?:[^"\\]|\\[\s\S])*""#, lex_to_str)]
 			StringLiteral(String),
 			$(
 				$(#[token($match, |_| $vid::$to)])*
@@ -228,6 +270,20 @@ macro_rules! def_token {
 			"unsafe" => Unsafe,
 			"let" => Let,
 			"mut" => Mut,
+			"struct" => Struct,
+			"enum" => Enum,
+			"const" => Const,
+			"if" => If,
+			"else" => Else,
+			"while" => While,
+			"for" => For,
+			"match" => Match,
+			"in" => In,
+			"as" => As,
+			"break" => Break,
+			"continue" => Continue,
+			"import" => Import,
+			"none" => None,
 			"_" => DontCare,
 		}
 	};
@@ -236,23 +292,24 @@ macro_rules! def_token {
 def_token!(
 	Operator {
 		// unary
-		// "?" => Question,
+		"?" => Question,
 		// "!" => Bang,
-		// "&" => Amp,
 		// unary/binary
+		"&" => Amp,
 		"-" => Neg,
 		"*" => Star,
 		// binary
 		"+" => Plus,
 		"/" => Div,
-		// "==" => Eq,
-		// "!=" => Ne,
+		"==" => Eq,
+		"!=" => Ne,
 		"<" => Lt,
 		">" => Gt,
-		// "<=" => Le,
-		// ">=" => Ge,
-		// "&&" => And,
-		// "||" => Or,
+		"<=" => Le,
+		">=" => Ge,
+		"&&" => And,
+		"||" => Or,
+		"??" => UnwrapOr,
 	}
 
 	AssignmentOp {
@@ -262,23 +319,40 @@ def_token!(
 	Punctuation {
 		"(" => LParen,
 		")" => RParen,
-		// "[" => LBracket,
-		// "]" => RBracket,
+		"[" => LBracket,
+		"]" => RBracket,
 		"{" => LBrace,
 		"}" => RBrace,
-		// "." => Dot,
-		// ".." => DotDot,
+		"." => Dot,
+		".." => DotDot,
 		"," => Comma,
-		// ":" => Colon,
-		// "::" => ColonColon,
-		// "->" => Arrow,
+		":" => Colon,
+		"::" => ColonColon,
+		"->" => Arrow,
 		"=>" => FatArrow,
 		";" => Semicolon,
 	}
 );
 
+impl Operator {
+	/// Returns whether the operator compares its operands, producing a `bool`.
+	#[must_use]
+	pub fn is_comparison(self) -> bool {
+		matches!(
+			self,
+			Self::Eq | Self::Ne | Self::Lt | Self::Gt | Self::Le | Self::Ge
+		)
+	}
+
+	/// Returns whether the operator takes two `bool`s, producing another one.
+	#[must_use]
+	pub fn is_logical(self) -> bool {
+		matches!(self, Self::And | Self::Or)
+	}
+}
+
 #[must_use]
-pub fn lex(code: &str, file_id: usize) -> Vec<SpannedRaw<Token>> {
+pub fn lex(session: &CompilerSession, code: &str, file_id: usize) -> Vec<SpannedRaw<Token>> {
 	let lex = Token::lexer(code).spanned();
 	let tokens = lex
 		.map(|(token, range)| (token, Span::new(file_id, range)))
@@ -299,6 +373,6 @@ pub fn lex(code: &str, file_id: usize) -> Vec<SpannedRaw<Token>> {
 		.cloned() // TODO: ewww
 		.filter_map(|(token, range)| token.ok().map(|x| (x, range)))
 		.collect();
-	add_diagnostics(&mut diagnostics);
+	session.add_diagnostics(&mut diagnostics);
 	tokens
 }
@@ -0,0 +1,204 @@
+//! The compiler as a library. `compile` takes a program from source text to
+//! its typed form, and every step it goes through is also exposed on its own
+//! so that callers can stop anywhere in between or look at what a step made.
+//!
+//! Diagnostics are collected in the `CompilerSession` passed to every step,
+//! with `Span`s pointing into sources by their position in the program.
+
+// TODO: (global) write more /// and //! comments
+
+#![warn(clippy::all, clippy::pedantic)]
+// most panics in the compiler are internal invariants rather than something the
+// caller could trigger, so documenting every one of them would be noise
+#![allow(clippy::missing_panics_doc, clippy::missing_errors_doc)]
+
+use crate::{
+	common::{
+		ident::Ident,
+		span::{Add, Span},
+	},
+	hoister::{hoist, HoistedScope},
+	infer::typed::Typed,
+	parser::types::ParserScope,
+	session::CompilerSession,
+};
+use chumsky::{Span as _, Stream};
+use codespan_reporting::diagnostic::Diagnostic;
+use std::{collections::HashMap, path::Path};
+
+// Compilation steps:
+// X - Lexing (into Token)
+// X - Parsing (into AST)
+//   - CHR
+// X - Module ordering (every file is a module, resolved after its imports and
+//     then linked into a single program)
+// X - Type inference
+// X - another checking pass that checks, now that we know types, whether
+//     mutability is being violated
+// X - ??? (perhaps passes to detect ptr dereference outside of unsafe and stuff
+//     like that, ensuring code is safe) - for now, enforcing `pure` and `unsafe`
+// X - Codegen (generate LLVM IR), or interpreting the program instead
+//   - Compile (compile LLVM IR down to an actual .o file or executable file)
+
+pub mod checker;
+pub mod codegen;
+pub mod common;
+pub mod hoister;
+pub mod infer;
+pub mod interp;
+pub mod lexer;
+pub mod module;
+pub mod parser;
+pub mod resolver;
+pub mod session;
+
+/// A file making up one module of a program. Its file id, which its spans
+/// and diagnostics refer to it by, is its position among the program's
+/// sources.
+#[derive(Clone, Copy, Debug)]
+pub struct Source<'a> {
+	/// The path of the file, which decides the path other modules import it by.
+	pub name: &'a str,
+	pub code: &'a str,
+}
+
+/// The last step `compile_until` runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Step {
+	Parse,
+	Hoist,
+	Resolve,
+	Infer,
+}
+
+/// What `compile_until` made out of a program. Every step that didn't get to
+/// run, because it came after the last one wanted or after a step reporting
+/// errors, left its field empty.
+#[derive(Default)]
+pub struct Compilation<'a> {
+	/// The syntax tree of every source.
+	pub parsed: Vec<ParserScope>,
+	/// Every module after hoisting.
+	pub hoisted: Option<Vec<HoistedScope>>,
+	/// Every module after name resolution.
+	pub resolved: Option<Vec<HoistedScope>>,
+	/// The typed program. Inference may still have reported errors about it.
+	pub program: Option<Typed<'a>>,
+	/// Every diagnostic reported while compiling, in the order they were.
+	pub diagnostics: Vec<Diagnostic<usize>>,
+}
+
+/// Lexes and parses the code of the source with id `file_id`, then runs the
+/// checks that don't need to know what names refer to.
+#[must_use]
+pub fn parse(session: &CompilerSession, file_id: usize, code: &str) -> ParserScope {
+	let code_len = code.len();
+	let tokens = lexer::lex(session, code, file_id);
+	let stream = Stream::from_iter(Span::new(file_id, code_len..code_len), tokens.into_iter());
+	let scope = parser::parse(session, stream);
+	checker::check(session, &scope);
+	scope
+}
+
+/// Resolves every module, each one after the modules it imports so that it
+/// can see what they export. `paths` are the import paths of the modules as
+/// given by `module::paths`. Returns `None` if the modules can't be ordered,
+/// in which case none of them are resolved.
+#[must_use]
+pub fn resolve(
+	session: &CompilerSession,
+	paths: &[Ident],
+	modules: &[HoistedScope],
+) -> Option<Vec<HoistedScope>> {
+	let errors = session.error_count();
+	let order = module::order(session, paths, modules);
+	if session.error_count() > errors {
+		return None;
+	}
+	let mut exports = HashMap::new();
+	let mut resolved = vec![HoistedScope::default(); modules.len()];
+	for i in order {
+		resolved[i] = resolver::resolve(session, &modules[i], &exports);
+		exports.insert(paths[i].clone(), module::exports(&resolved[i]));
+	}
+	Some(resolved)
+}
+
+/// Links the resolved modules into a single program rooted at the first one,
+/// whose code is `root_len` bytes long, infers its types and then runs the
+/// checks that need to know them.
+#[must_use]
+pub fn infer<'a>(
+	session: &'a CompilerSession,
+	paths: &[Ident],
+	modules: Vec<HoistedScope>,
+	root_len: usize,
+) -> Typed<'a> {
+	let program = module::link(paths, modules).add_span(Span::new(0, 0..root_len));
+	let typed = infer::infer(session, program);
+	checker::mutability::check_mutability(session, &typed.program.value);
+	checker::safety::check_safety(session, &typed);
+	typed
+}
+
+/// Compiles the program made out of `sources` up to its typed form, stopping
+/// early as soon as a step reports errors, since later steps assume valid
+/// input. The first source is the root of the program.
+///
+/// The diagnostics reported so far are taken out of `session`, which is kept
+/// around by the returned program.
+#[must_use]
+pub fn compile<'a>(session: &'a CompilerSession, sources: &[Source]) -> Compilation<'a> {
+	compile_until(session, sources, Step::Infer)
+}
+
+/// Compiles the program made out of `sources` like `compile` does, but only up
+/// to and including `last`.
+#[must_use]
+pub fn compile_until<'a>(
+	session: &'a CompilerSession,
+	sources: &[Source],
+	last: Step,
+) -> Compilation<'a> {
+	let mut compilation = Compilation::default();
+	run_steps(session, sources, last, &mut compilation);
+	compilation.diagnostics = session.take_diagnostics();
+	compilation
+}
+
+fn run_steps<'a>(
+	session: &'a CompilerSession,
+	sources: &[Source],
+	last: Step,
+	compilation: &mut Compilation<'a>,
+) {
+	let failed = || session.error_count() > 0;
+	let Some(root) = sources.first() else {
+		return;
+	};
+
+	compilation.parsed = sources
+		.iter()
+		.enumerate()
+		.map(|(id, source)| parse(session, id, source.code))
+		.collect();
+	if last == Step::Parse || failed() {
+		return;
+	}
+
+	let hoisted = compilation.hoisted.insert(compilation.parsed.iter().map(hoist).collect());
+	if last == Step::Hoist || failed() {
+		return;
+	}
+
+	let paths = module::paths(&sources.iter().map(|x| Path::new(x.name)).collect::<Vec<_>>());
+	let Some(resolved) = resolve(session, &paths, hoisted) else {
+		return;
+	};
+	let resolved = compilation.resolved.insert(resolved);
+	if last == Step::Resolve || failed() {
+		return;
+	}
+
+	compilation.program = Some(infer(session, &paths, resolved.clone(), root.code.len()));
+}
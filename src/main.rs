@@ -1,71 +1,121 @@
-// TODO: (global) write more /// and //! comments
-
 #![warn(clippy::all, clippy::pedantic)]
 
-use crate::{checker::check, common::span::Add, hoister::hoist, resolver::resolve};
-use chumsky::{Span as _, Stream};
+use crate::cli::{Cli, Command};
+use clap::Parser;
 use codespan_reporting::{
-	diagnostic::Severity,
+	diagnostic::{Diagnostic, Severity},
 	files::SimpleFiles,
-	term::{
-		self,
-		termcolor::{ColorChoice, StandardStream},
-	},
+	term::{self, termcolor::StandardStream},
 };
-use common::{diagnostics, span::Span};
-use std::fs;
+use lang::{codegen, interp, session::CompilerSession, Source};
+use std::{fs, path::Path, process::ExitCode};
 
-// Compilation steps:
-// X - Lexing (into Token)
-// X - Parsing (into AST)
-//   - CHR
-//   - some intermediate step (maybe even done in CHR) where the files being
-//     compiled are merged to resolve imports and etc
-//   - Type inference
-//   - another checking pass that checks, now that we know types, whether
-//     mutability is being violated
-//   - ??? (perhaps passes to detect ptr dereference outside of unsafe and stuff
-//     like that, ensuring code is safe)
-//   - Codegen (generate LLVM IR)
-//   - Compile (compile LLVM IR down to an actual .o file or executable file)
+mod cli;
 
-pub mod checker;
-pub mod common;
-pub mod hoister;
-pub mod infer;
-pub mod lexer;
-pub mod parser;
-pub mod resolver;
+/// Compiles the program made out of `sources` as far as `command` needs it
+/// to, then does what the command does with it. The first source is the root
+/// of the program. Returns every diagnostic reported along the way.
+fn compile(sources: &[Source], command: &Command) -> Vec<Diagnostic<usize>> {
+	let session = CompilerSession::default();
+	let mut compilation = lang::compile_until(&session, sources, command.step());
+	let modules = match command {
+		Command::DumpAst(_) => Some(dumped(&compilation.parsed)),
+		Command::DumpHoisted(_) => compilation.hoisted.as_deref().map(dumped),
+		Command::DumpResolved(_) => compilation.resolved.as_deref().map(dumped),
+		_ => None,
+	};
+	for (id, module) in modules.into_iter().flatten().enumerate() {
+		if sources.len() > 1 {
+			println!("==> {} <==", sources[id].name);
+		}
+		println!("{module}");
+	}
 
-fn main() {
-	let sources = vec!["code"];
-	let mut files = SimpleFiles::new();
-	let mut source_ids = Vec::new();
-	for file in sources {
-		let code = fs::read_to_string(file).unwrap();
-		let file_id = files.add(file, code);
-		source_ids.push(file_id);
+	let Some(typed) = &compilation.program else {
+		return compilation.diagnostics;
+	};
+	if let Command::DumpTypes(_) = command {
+		print!("{typed}");
+	}
+	// later steps assume the program is valid
+	if compilation.diagnostics.iter().any(|x| x.severity >= Severity::Error) {
+		return compilation.diagnostics;
 	}
+	match command {
+		Command::Run(_) => {
+			if let Some(value) = interp::run(&session, typed) {
+				if value != interp::value::Value::Void {
+					println!("{value}");
+				}
+			}
+		}
+		Command::Build { output, .. } => {
+			let root = sources[0].name;
+			let ir = codegen::codegen(&session, root, typed);
+			if session.error_count() == 0 {
+				write_ir(&session, &ir, output.as_deref(), root);
+			}
+		}
+		_ => {}
+	}
+	compilation.diagnostics.append(&mut session.take_diagnostics());
+	compilation.diagnostics
+}
 
-	for id in source_ids {
-		let file = files.get(id).unwrap();
-		let code_len = file.source().len();
-		let tokens = lexer::lex(file.source(), id);
-		let lex_iter = Stream::from_iter(Span::new(id, code_len..code_len), tokens.into_iter());
-		let parsed = parser::parse(lex_iter);
-		check(&parsed);
-		let hoisted = hoist(&parsed);
-		let resolved = resolve(&hoisted, &hoister::HoistedScopeData::default());
+fn dumped<T: std::fmt::Display>(modules: &[T]) -> Vec<&dyn std::fmt::Display> {
+	modules.iter().map(|x| x as _).collect()
+}
 
-		println!("{resolved}");
+/// Writes `ir` to `output`, `-` meaning stdout, or next to the `root` source
+/// if no output was given.
+fn write_ir(session: &CompilerSession, ir: &str, output: Option<&Path>, root: &str) {
+	let path = output.map_or_else(|| Path::new(root).with_extension("ll"), Path::to_path_buf);
+	if path == Path::new("-") {
+		print!("{ir}");
+	} else if let Err(err) = fs::write(&path, ir) {
+		session.add_diagnostic(
+			Diagnostic::error().with_message(format!("could not write {}: {err}", path.display())),
+		);
+	}
+}
 
-		infer::infer(&resolved.add_span(Span::new(id, 0..code_len)));
+fn main() -> ExitCode {
+	let cli = Cli::parse();
+	let inputs = &cli.command.inputs().paths;
+	let mut files = SimpleFiles::new();
+	for path in inputs {
+		match fs::read_to_string(path) {
+			Ok(code) => {
+				files.add(path.display().to_string(), code);
+			}
+			Err(err) => {
+				eprintln!("error: could not read {}: {err}", path.display());
+				return ExitCode::from(2);
+			}
+		}
 	}
+	let sources: Vec<_> = (0..inputs.len())
+		.map(|id| {
+			let file = files.get(id).unwrap();
+			Source {
+				name: file.name(),
+				code: file.source(),
+			}
+		})
+		.collect();
 
-	let diagnostics = diagnostics::own();
+	let mut diagnostics = compile(&sources, &cli.command);
+	if cli.deny_warnings {
+		for diagnostic in &mut diagnostics {
+			if diagnostic.severity == Severity::Warning {
+				diagnostic.severity = Severity::Error;
+			}
+		}
+	}
+	let has_errors = diagnostics.iter().any(|x| x.severity >= Severity::Error);
 	if !diagnostics.is_empty() {
 		// Print errors and/or warnings
-		let writer = StandardStream::stderr(ColorChoice::Always);
+		let writer = StandardStream::stderr(cli.color.choice());
 		let config = term::Config::default();
 		let amount = diagnostics.len();
 		let warnings = diagnostics
@@ -75,7 +125,7 @@ fn main() {
 		for diagnostic in &diagnostics {
 			term::emit(&mut writer.lock(), &config, &files, diagnostic).unwrap();
 		}
-		println!(
+		eprintln!(
 			"{amount} diagnostic{} total ({warnings} warning{}, {} error{})",
 			if amount == 1 { "" } else { "s" },
 			if warnings == 1 { "" } else { "s" },
@@ -83,8 +133,14 @@ fn main() {
 			if amount - warnings == 1 { "" } else { "s" },
 		);
 
-		if diagnostics.iter().any(|x| x.severity == Severity::Error) {
-			println!("one or more errors present, cannot compile :(");
+		if has_errors {
+			eprintln!("one or more errors present, cannot compile :(");
 		}
 	}
+
+	if has_errors {
+		ExitCode::FAILURE
+	} else {
+		ExitCode::SUCCESS
+	}
 }
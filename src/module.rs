@@ -0,0 +1,198 @@
+//! Every input file is a module. This maps files to the paths other modules
+//! import them by, orders them so that every module is resolved after the ones
+//! it imports, and links the resolved modules back into a single program.
+
+use crate::{
+	common::{
+		diagnostics::{import_cycle, nonexistent_module},
+		ident::Ident,
+		span::Span,
+		stmt::Stmt,
+	},
+	hoister::{HoistedScope, HoistedScopeData},
+	session::CompilerSession,
+};
+use std::path::Path;
+
+/// Turns the path of every input file into the path of its module: the file
+/// path relative to the directory all inputs have in common, without its
+/// extension. For example, `src/foo/bar.lang` becomes `foo::bar` when compiled
+/// along with `src/main.lang`.
+#[must_use]
+pub fn paths(files: &[&Path]) -> Vec<Ident> {
+	let mut root = files
+		.first()
+		.and_then(|x| x.parent())
+		.unwrap_or(Path::new(""));
+	for file in files {
+		while !file.starts_with(root) {
+			root = root.parent().unwrap_or(Path::new(""));
+		}
+	}
+	files
+		.iter()
+		.map(|file| {
+			let relative = file.strip_prefix(root).unwrap().with_extension("");
+			Ident::qualify(
+				relative
+					.iter()
+					.map(|x| Ident::Named(x.to_string_lossy().into_owned()))
+					.collect(),
+			)
+		})
+		.collect()
+}
+
+/// Returns the index of the module `path` refers to in an import: either the
+/// module itself or, when importing a single item, the module holding it.
+fn imported_module(path: &Ident, modules: &[Ident]) -> Option<usize> {
+	let segments = path.segments();
+	modules.iter().position(|x| *x == *path).or_else(|| {
+		let module = &segments[..segments.len() - 1];
+		(!module.is_empty())
+			.then(|| Ident::qualify(module.to_vec()))
+			.and_then(|module| modules.iter().position(|x| *x == module))
+	})
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Visit {
+	Pending,
+	InProgress,
+	Done,
+	/// The module is part of an import cycle, or imports a module that is.
+	Broken,
+}
+
+struct Orderer<'a> {
+	session: &'a CompilerSession,
+	paths: &'a [Ident],
+	/// The modules every module imports, along with the span of the import.
+	imports: Vec<Vec<(usize, Span)>>,
+	visits: Vec<Visit>,
+	/// The imports followed to get to the module being visited.
+	stack: Vec<(usize, usize, Span)>,
+	order: Vec<usize>,
+}
+
+impl Orderer<'_> {
+	/// Visits every module `module` imports before adding it to the order.
+	/// Returns whether the module can be resolved.
+	fn visit(&mut self, module: usize) -> bool {
+		match self.visits[module] {
+			Visit::Done => return true,
+			Visit::Broken => return false,
+			Visit::InProgress => {
+				let start = self
+					.stack
+					.iter()
+					.position(|(from, ..)| *from == module)
+					.unwrap();
+				let cycle: Vec<_> = self.stack[start..]
+					.iter()
+					.map(|(from, to, span)| (*span, &self.paths[*from], &self.paths[*to]))
+					.collect();
+				import_cycle(self.session, &cycle);
+				return false;
+			}
+			Visit::Pending => {}
+		}
+		self.visits[module] = Visit::InProgress;
+		let mut ok = true;
+		for (import, span) in self.imports[module].clone() {
+			self.stack.push((module, import, span));
+			ok &= self.visit(import);
+			self.stack.pop();
+		}
+		if ok {
+			self.visits[module] = Visit::Done;
+			self.order.push(module);
+		} else {
+			self.visits[module] = Visit::Broken;
+		}
+		ok
+	}
+}
+
+/// Orders the modules so that every one comes after the ones it imports,
+/// returning their indices. Reports imports of nonexistent modules and import
+/// cycles; modules that are part of a cycle (or import one that is) are left
+/// out.
+#[must_use]
+pub fn order(session: &CompilerSession, paths: &[Ident], modules: &[HoistedScope]) -> Vec<usize> {
+	let imports = modules
+		.iter()
+		.map(|module| {
+			let mut imports: Vec<(usize, Span)> = Vec::new();
+			for stmt in &module.stmts {
+				let Stmt::Import { path } = &stmt.value else {
+					continue;
+				};
+				match imported_module(&path.value, paths) {
+					// importing several items of the same module is still a single dependency
+					Some(module) if imports.iter().any(|(x, _)| *x == module) => {}
+					Some(module) => imports.push((module, path.span)),
+					None => nonexistent_module(session, path.span, &path.value),
+				}
+			}
+			imports
+		})
+		.collect();
+	let mut orderer = Orderer {
+		session,
+		paths,
+		imports,
+		visits: vec![Visit::Pending; modules.len()],
+		stack: Vec::new(),
+		order: Vec::new(),
+	};
+	for module in 0..modules.len() {
+		orderer.visit(module);
+	}
+	orderer.order
+}
+
+/// Returns the items a resolved module makes available to its importers.
+#[must_use]
+pub fn exports(module: &HoistedScope) -> HoistedScopeData {
+	HoistedScopeData {
+		funcs: module.data.funcs.clone(),
+		structs: module.data.structs.clone(),
+		enums: module.data.enums.clone(),
+		consts: module.data.consts.clone(),
+		..Default::default()
+	}
+}
+
+/// Merges the resolved modules into a single scope holding every item in the
+/// program. The items of every module but the first (the root of the program)
+/// are renamed after the path of their module, so that they don't clash with
+/// each other once compiled.
+#[must_use]
+pub fn link(paths: &[Ident], modules: Vec<HoistedScope>) -> HoistedScope {
+	let mut program = HoistedScope::default();
+	for (i, (path, module)) in paths.iter().zip(modules).enumerate() {
+		let mut data = module.data;
+		if i != 0 {
+			let qualify = |name: &Ident| {
+				let mut segments = path.segments();
+				segments.push(name.clone());
+				Ident::Qualified(segments)
+			};
+			for func in data.funcs.values_mut() {
+				func.value.id.value = qualify(&func.value.id.value);
+			}
+			for r#struct in data.structs.values_mut() {
+				r#struct.value.id.value = qualify(&r#struct.value.id.value);
+			}
+			for r#enum in data.enums.values_mut() {
+				r#enum.value.id.value = qualify(&r#enum.value.id.value);
+			}
+			for r#const in data.consts.values_mut() {
+				r#const.value.id.value = qualify(&r#const.value.id.value);
+			}
+		}
+		program.data = program.data + data;
+	}
+	program
+}
@@ -2,9 +2,10 @@ use self::{
 	stmt::stmt,
 	types::{CodeStream, ParserScope},
 };
-use crate::common::diagnostics::add_diagnostics;
+use crate::session::CompilerSession;
 use chumsky::{error::SimpleReason, prelude::*};
 use codespan_reporting::diagnostic::{Diagnostic, Label};
+use itertools::Itertools;
 
 #[macro_use]
 pub mod macros;
@@ -26,7 +27,7 @@ pub fn parser() -> token_parser!(ParserScope) {
 }
 
 #[must_use]
-pub fn parse(code_stream: CodeStream) -> ParserScope {
+pub fn parse(session: &CompilerSession, code_stream: CodeStream) -> ParserScope {
 	let (parsed, errors) = parser().parse_recovery(code_stream);
 	let mut diagnostics = vec![];
 	if errors.is_empty() {
@@ -57,10 +58,12 @@ pub fn parse(code_stream: CodeStream) -> ParserScope {
 						.with_message("this token is invalid")])
 					.with_notes(vec![format!(
 						"expected one of {}",
+						// the expected tokens come out of a hash set, so they're sorted to
+						// keep the note the same from build to build
 						err.expected()
 							.map(|x| x.as_ref().map_or("[?]".to_string(), |x| format!("'{x}'")))
-							.reduce(|acc, b| acc + ", " + &b)
-							.unwrap_or(String::new())
+							.sorted()
+							.join(", ")
 					)]),
 			),
 			SimpleReason::Custom(label) => add_diagnostic(
@@ -70,6 +73,6 @@ pub fn parse(code_stream: CodeStream) -> ParserScope {
 			),
 		}
 	}
-	add_diagnostics(&mut diagnostics);
+	session.add_diagnostics(&mut diagnostics);
 	parsed.map_or_else(|| ParserScope { stmts: Vec::new() }, |x| x.value)
 }
@@ -1,4 +1,6 @@
 pub mod expr;
 pub mod ident;
+pub mod literal;
+pub mod pattern;
 pub mod ty;
 pub mod ty_ident;
@@ -1,10 +1,16 @@
 use super::ident;
+use super::literal;
+use super::pattern::pattern;
 use super::ty::ty;
 use crate::common::expr::Expr;
+use crate::common::func::{Attribs, Linkage, Signature};
+use crate::common::ident::Ident;
+use crate::common::r#type::Type;
 use crate::common::span::{Add, Span, Spanned};
-use crate::lexer::Token;
+use crate::lexer::{NumberLiteral, NumberLiteralKind, Operator, Token};
+use crate::parser::stmt::func::func_args;
 use crate::parser::types::ScopeRecursive;
-use crate::parser::types::{ExprRecursive, ParserExpr};
+use crate::parser::types::{ExprRecursive, ParserExpr, ParserScope, ParserStmt};
 use chumsky::prelude::*;
 
 macro_rules! binop_parser {
@@ -20,17 +26,6 @@ macro_rules! binop_parser {
 	};
 }
 
-macro_rules! unop_parser {
-	($($op:ident)* => $next:ident$(($($x:expr),*))?) => {
-		|| choice(($(span!(jop!($op)),)*)).repeated()
-			.then($next($($($x),*)?))
-			.foldr(|op, rhs| {
-				let span = op.span.clone() + rhs.span.clone();
-				Expr::UnaryOp(op.map(|x| force_token!(x => Operator)), Box::new(rhs)).add_span(span)
-			})
-	};
-}
-
 macro_rules! literal_parser {
 	($kind:ident) => {
 		filter(|x| matches!(x, $crate::lexer::Token::$kind(_))).map(|x| {
@@ -41,56 +36,278 @@ macro_rules! literal_parser {
 
 // NOTE: can't make a type to encapsulate the return below here, because the
 // feature "type alias impl trait" SEGFAULTS!!! ahh, rust's so safe...
+#[allow(clippy::too_many_lines)] // one parser per kind of expr, splitting it up wouldn't help
 fn atom<'a>(
 	e: ExprRecursive<'a>,
 	s: ScopeRecursive<'a>,
 ) -> impl Parser<Token, Spanned<ParserExpr>, Error = Simple<Token, Span>> + 'a {
+	let field = ident::nodiscard()
+		.then_ignore(jpunct!(Colon))
+		.then(e.clone());
+	let else_expr = jkeyword!(Else).ignore_then(e.clone()).or_not();
+	let if_expr = jkeyword!(If)
+		.ignore_then(parened!(e.clone()))
+		.then(e.clone())
+		.then(else_expr.clone())
+		.map(|((cond, then), otherwise)| Expr::If {
+			cond: Box::new(cond),
+			then: Box::new(then),
+			otherwise: otherwise.map(Box::new),
+		});
+	let body = span!(braced!(s.clone()).map(Expr::Scope));
+	let unsafe_expr = jkeyword!(Unsafe)
+		.ignore_then(braced!(s.clone()))
+		.map(Expr::Unsafe);
+	let closure_body = choice((
+		jpunct!(FatArrow)
+			.ignore_then(e.clone())
+			.map_with_span(|value, span| {
+				ParserScope {
+					stmts: vec![ParserStmt::Return {
+						value,
+						is_yield: false,
+					}
+					.add_span(span)],
+				}
+				.add_span(span)
+			}),
+		span!(braced!(s)),
+	));
+	let closure = span!(jkeyword!(Function))
+		.then(ty().or_not())
+		.then(func_args())
+		.then(closure_body)
+		.map(|(((func, return_ty), args), body)| Expr::Closure {
+			signature: Signature {
+				attribs: Attribs::default().add_span(func.span),
+				linkage: Linkage::default().add_span(func.span),
+				return_ty: return_ty.unwrap_or_else(|| Type::Inferred.add_span(func.span)),
+				args,
+				generics: Vec::new().add_span(func.span),
+			},
+			body: Box::new(body),
+			captures: Vec::new(),
+		});
+	let while_expr = jkeyword!(While)
+		.ignore_then(parened!(e.clone()))
+		.then(body.clone())
+		.then(else_expr.clone())
+		.map(|((cond, body), otherwise)| Expr::While {
+			cond: Box::new(cond),
+			body: Box::new(body),
+			otherwise: otherwise.map(Box::new),
+		});
+	let for_expr = jkeyword!(For)
+		.ignore_then(ident::ident())
+		.then_ignore(jkeyword!(In))
+		.then(e.clone())
+		.then_ignore(jpunct!(DotDot))
+		.then(e.clone())
+		.then(body.clone())
+		.then(else_expr)
+		.map(|((((var, start), end), body), otherwise)| Expr::For {
+			var,
+			start: Box::new(start),
+			end: Box::new(end),
+			body: Box::new(body),
+			otherwise: otherwise.map(Box::new),
+		});
+	let arm = pattern().then_ignore(jpunct!(FatArrow)).then(e.clone());
+	let match_expr = jkeyword!(Match)
+		.ignore_then(parened!(e.clone()))
+		.then(braced!(arm,))
+		.map(|(value, arms)| Expr::Match {
+			value: Box::new(value),
+			arms,
+		});
+	// a single parenthesized expr is just that expr
+	let tuple = parened!(e.clone().separated_by(jpunct!(Comma)).at_least(2).allow_trailing())
+		.map(Expr::Tuple);
+	let array = bracketed!(e.clone(),).map(Expr::Array);
 	choice((
 		parened!(e),
+		span!(tuple),
+		span!(array),
+		span!(if_expr),
+		span!(while_expr),
+		span!(for_expr),
+		span!(match_expr),
+		span!(unsafe_expr),
+		span!(closure),
 		span!(literal_parser!(NumberLiteral)),
-		// TODO: potentially_qualified_ident
-		span!(ident::nospan().map(Expr::Identifier)),
-		span!(braced!(s).map(Expr::Scope)),
+		literal::string().map(|x| x.map(Expr::StringLiteral)),
+		literal::char().map(|x| x.map(Expr::CharLiteral)),
+		span!(jkeyword!(None).to(Expr::None)),
+		span!(ty()
+			.then(braced!(field,))
+			.map(|(ty, fields)| Expr::Construct { ty, fields })),
+		span!(ident::potentially_qualified().map(|x| Expr::Identifier(x.value))),
+		body,
 	))
 }
 
-fn call<'a>(
+#[derive(Clone)]
+enum Prefix {
+	Neg,
+	Ref { mutable: bool },
+	Deref,
+}
+
+fn prefix<'a>(
 	e: ExprRecursive<'a>,
 	s: ScopeRecursive<'a>,
 ) -> impl Parser<Token, Spanned<ParserExpr>, Error = Simple<Token, Span>> + 'a {
-	atom(e.clone(), s)
-		.then(angled!(ty(),).or_not().then(parened!(e,)).repeated())
-		.foldl(|lhs, (generics, args)| {
-			let lhs_span = lhs.span;
-			let last_span = args.last().map_or(lhs_span, |x| x.span);
-			Expr::Call {
-				callee: Box::new(lhs),
-				generics,
-				args,
+	let op = choice((
+		jop!(Neg).to(Prefix::Neg),
+		jop!(Amp)
+			.ignore_then(jkeyword!(Mut).or_not())
+			.map(|mutable| Prefix::Ref {
+				mutable: mutable.is_some(),
+			}),
+		jop!(Star).to(Prefix::Deref),
+	));
+	span!(op)
+		.repeated()
+		.then(postfix(e, s))
+		.foldr(|op, value| {
+			let span = op.span + value.span;
+			let value = Box::new(value);
+			match op.value {
+				Prefix::Neg => Expr::UnaryOp(Operator::Neg.add_span(op.span), value),
+				Prefix::Ref { mutable } => Expr::Ref { value, mutable },
+				Prefix::Deref => Expr::Deref(value),
+			}
+			.add_span(span)
+		})
+}
+
+/// Casts bind tighter than any binary operator, but not as tight as prefix
+/// operators, so `-x as u8` casts `-x`.
+fn cast<'a>(
+	e: ExprRecursive<'a>,
+	s: ScopeRecursive<'a>,
+) -> impl Parser<Token, Spanned<ParserExpr>, Error = Simple<Token, Span>> + 'a {
+	prefix(e, s)
+		.then(jkeyword!(As).ignore_then(ty()).repeated())
+		.foldl(|value, ty| {
+			let span = value.span + ty.span;
+			Expr::Cast {
+				value: Box::new(value),
+				ty,
+			}
+			.add_span(span)
+		})
+}
+
+enum Postfix {
+	Call(Option<Vec<Spanned<Type>>>, Vec<Spanned<ParserExpr>>),
+	Field(Spanned<Ident>),
+	TupleField(Spanned<usize>),
+	Index(Box<Spanned<ParserExpr>>),
+	Try,
+}
+
+/// Parses the `.<index>` of a tuple field. The lexer sees something like `.0`
+/// as a float literal, so that's what we get unless there's a space after the
+/// dot.
+fn tuple_index() -> token_parser!(usize) {
+	let digits = |float: bool| {
+		filter(move |token| match token {
+			Token::NumberLiteral(NumberLiteral {
+				value,
+				kind: NumberLiteralKind::Decimal,
+				ty: None,
+			}) => value.starts_with('.') == float && value.matches('.').count() == usize::from(float),
+			_ => false,
+		})
+	};
+	span!(digits(true).or(jpunct!(Dot).ignore_then(digits(false))))
+		.validate(|token, span, emit| {
+			let literal = force_token!(token.value => NumberLiteral);
+			let index = literal.value.trim_start_matches('.').parse().unwrap_or_else(|_| {
+				emit(Simple::custom(span, "invalid tuple index"));
+				0
+			});
+			index.add_span(token.span)
+		})
+}
+
+fn postfix<'a>(
+	e: ExprRecursive<'a>,
+	s: ScopeRecursive<'a>,
+) -> impl Parser<Token, Spanned<ParserExpr>, Error = Simple<Token, Span>> + 'a {
+	let call = angled!(ty(),)
+		.or_not()
+		.then(parened!(e.clone(),))
+		.map(|(generics, args)| Postfix::Call(generics, args));
+	let field = jpunct!(Dot)
+		.ignore_then(ident::nodiscard())
+		.map(Postfix::Field);
+	let tuple_field = tuple_index().map(Postfix::TupleField);
+	let index = bracketed!(e.clone()).map(|x| Postfix::Index(Box::new(x)));
+	let r#try = jop!(Question).map(|_| Postfix::Try);
+	atom(e, s)
+		.then(span!(choice((call, tuple_field, field, index, r#try))).repeated())
+		.foldl(|lhs, postfix| {
+			let span = lhs.span + postfix.span;
+			match postfix.value {
+				Postfix::Call(generics, args) => Expr::Call {
+					callee: Box::new(lhs),
+					generics,
+					args,
+				},
+				Postfix::Field(field) => Expr::Field {
+					value: Box::new(lhs),
+					field,
+				},
+				Postfix::TupleField(index) => Expr::TupleField {
+					value: Box::new(lhs),
+					index,
+				},
+				Postfix::Index(index) => Expr::Index {
+					value: Box::new(lhs),
+					index,
+				},
+				Postfix::Try => Expr::Try(Box::new(lhs)),
 			}
-			.add_span(lhs_span + last_span)
+			.add_span(span)
 		})
 }
 
 /// Atoms:
 /// - `(<expr>)`
+/// - `(<expr>, <expr>, ...)` (tuple)
+/// - `[<expr>, ...]` (array)
 /// - `<number literal>`
-/// - `<ident>` (TODO: should be potentially qualified)
+/// - `"<string>"`, `'<char>'` (with escape sequences like `\n`)
+/// - `none`
+/// - `<ident>` (potentially qualified, like `<ident>::<ident>`, which is also how
+///   enum variants are referred to)
 /// - `<scope>` (ideally with `yield` stmt)
+/// - `<ty> { <ident>: <expr>, ... }` (struct construction)
+/// - `if (<expr>) <expr> [else <expr>]`
+/// - `while (<expr>) <scope> [else <expr>]`
+/// - `for <ident> in <expr>..<expr> <scope> [else <expr>]`
+/// - `match (<expr>) { <pattern> => <expr>, ... }`
+/// - `unsafe <scope>`
+/// - `func [<ty>] (<ty> <ident>, ...) => <expr>` or
+///   `func [<ty>] (<ty> <ident>, ...) <scope>` (closure)
 ///
 /// Parses:
 /// - addition/subtraction (`<expr> +|- <expr>`)
 /// - multiplication/division (`<expr> *|/ <expr>`)
-/// - negation (`-<expr>`)
-/// - function calls (`<expr><<ty>, ...>(<expr>, ...)`)
-///
-/// Want (basic):
-/// - logical operators (`<expr> ||, && <expr>`)
 /// - ord/eq operators (`<expr> ==, !=, <, >, <=, >= <expr>`)
-/// - ref (`&<expr>`)
+/// - logical operators (`<expr> ||, && <expr>`)
+/// - unwrap-or (`<expr> ?? <expr>`)
+/// - casts (`<expr> as <ty>`)
+/// - negation (`-<expr>`)
+/// - ref (`&<expr>`, `&mut <expr>`)
 /// - deref (`*<expr>`)
-/// - dot (`<expr>.<ident>`)
-/// - construct (`<ty> { <ident>: <expr>, <ident>: <expr> }`)
+/// - function calls (`<expr><<ty>, ...>(<expr>, ...)`)
+/// - field access (`<expr>.<ident>`)
+/// - tuple field access (`<expr>.<number>`)
+/// - indexing (`<expr>[<expr>]`)
+/// - optional propagation (`<expr>?`)
 ///
 /// Want (sugar):
 /// - deref dot (`<expr>*.<ident>` (`== (*<expr>.<ident>)`))
@@ -98,8 +315,6 @@ fn call<'a>(
 ///   `<expr1>-><ident>(<expr2>, ...)` (`== <ident>(<expr1>, <expr2>, ...)`))
 ///
 /// Want (thinking about it):
-/// - array literals? (`[<expr>, ...]`)
-/// - tuples? (`(<expr>, ...)`)
 /// - set? (`<ident> = <expr>`, same as doing this outside expr, returning the
 ///   rhs)
 /// - ub producers? (`<ident>++, ++<ident>, <ident>--, --<ident>`, try not to ub
@@ -110,17 +325,18 @@ fn call<'a>(
 ///   functions)
 ///
 /// Want (more scope-y things):
-/// - match/switch
-/// - if (`if (<expr>) <expr> [else <expr>]`, wouldn't even need to make it a
-///   statement, but it would be cool to also allow for `if <expr> { ... } else
-///   <expr>`)
-/// - for? while? how would these return, being loops
+/// - if without parens (`if <expr> { ... } else <expr>`)
 #[allow(clippy::needless_pass_by_value)] // TODO: someday i'll understand how to make this a ref
-pub fn expr(s: ScopeRecursive) -> token_parser!(ParserExpr : '_) {
+pub fn expr(s: ScopeRecursive<'_>) -> token_parser!(ParserExpr : '_) {
 	recursive(|e| {
-		let neg_parser = unop_parser!(Neg => call(e.clone(), s.clone()));
-		let sd_parser = binop_parser!(Star Div => neg_parser);
+		let cast_parser = || cast(e.clone(), s.clone());
+		let sd_parser = binop_parser!(Star Div => cast_parser);
 		let pn_parser = binop_parser!(Plus Neg => sd_parser);
-		pn_parser()
+		let ord_parser = binop_parser!(Lt Gt Le Ge => pn_parser);
+		let eq_parser = binop_parser!(Eq Ne => ord_parser);
+		let and_parser = binop_parser!(And => eq_parser);
+		let or_parser = binop_parser!(Or => and_parser);
+		let unwrap_or_parser = binop_parser!(UnwrapOr => or_parser);
+		unwrap_or_parser()
 	})
 }
@@ -15,10 +15,6 @@ pub fn ident() -> token_parser!(Ident) {
 	})
 }
 
-pub fn nospan() -> token_parser_no_span!(Ident) {
-	ident().map(|x| x.value)
-}
-
 pub fn nodiscard() -> token_parser!(Ident) {
 	ident().validate(|ident, span, emit| {
 		if ident.value.is_discarded() {
@@ -31,4 +27,13 @@ pub fn nodiscard() -> token_parser!(Ident) {
 	})
 }
 
-// TODO: potentially_qualified_ident
+/// Parses an ident that may be qualified with the module it comes from, like
+/// `foo::bar::baz`.
+pub fn potentially_qualified() -> token_parser!(Ident) {
+	ident()
+		.separated_by(jpunct!(ColonColon))
+		.at_least(1)
+		.map_with_span(|segments, span| {
+			Ident::qualify(segments.into_iter().map(|x| x.value).collect()).add_span(span)
+		})
+}
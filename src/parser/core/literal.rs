@@ -0,0 +1,113 @@
+use crate::{
+	common::span::{Add, Span},
+	lexer::Token,
+};
+use chumsky::prelude::*;
+
+/// Decodes the escape sequence right after a backslash, returning the char it
+/// stands for (if it's valid) along with its length in bytes.
+///
+/// Supported sequences are `\n`, `\r`, `\t`, `\0`, `\\`, `\'`, `\"`, `\x<hex>`
+/// (two digits, ASCII only) and `\u{<hex>}` (any unicode scalar value).
+fn escape(rest: &str) -> (Option<char>, usize) {
+	let is_hex = |x: &&str| !x.is_empty() && x.chars().all(|c| c.is_ascii_hexdigit());
+	let Some(c) = rest.chars().next() else {
+		return (None, 0);
+	};
+	match c {
+		'n' => (Some('\n'), 1),
+		'r' => (Some('\r'), 1),
+		't' => (Some('\t'), 1),
+		'0' => (Some('\0'), 1),
+		'\\' | '\'' | '"' => (Some(c), 1),
+		'x' => {
+			let value = rest
+				.get(1..3)
+				.filter(is_hex)
+				.and_then(|x| u8::from_str_radix(x, 16).ok())
+				.filter(u8::is_ascii);
+			let len = 1 + rest[1..].chars().take(2).map(char::len_utf8).sum::<usize>();
+			(value.map(char::from), len)
+		}
+		'u' if rest.starts_with("u{") && rest.contains('}') => {
+			let (hex, _) = rest[2..].split_once('}').unwrap();
+			let value = Some(hex)
+				.filter(is_hex)
+				.and_then(|x| u32::from_str_radix(x, 16).ok())
+				.and_then(char::from_u32);
+			(value, hex.len() + 3)
+		}
+		_ => (None, c.len_utf8()),
+	}
+}
+
+/// Decodes every escape sequence in the literal `token`, whose contents are
+/// surrounded by quotes, reporting the invalid ones.
+fn unescape(token: &str, span: Span, emit: &mut dyn FnMut(Simple<Token, Span>)) -> String {
+	let contents = &token[1..token.len() - 1];
+	let mut out = String::new();
+	let mut chars = contents.char_indices();
+	while let Some((i, c)) = chars.next() {
+		if c != '\\' {
+			out.push(c);
+			continue;
+		}
+		let (value, len) = escape(&contents[i + 1..]);
+		if let Some(value) = value {
+			out.push(value);
+		} else {
+			// skip the opening quote
+			let start = span.start + 1 + i;
+			let escape_span = Span {
+				start,
+				end: start + 1 + len,
+				..span
+			};
+			let sequence = &contents[i..=i + len];
+			emit(Simple::custom(
+				escape_span,
+				format!("invalid escape sequence `{sequence}`"),
+			));
+		}
+		// the chars of the sequence were already taken care of
+		for _ in contents[i + 1..=i + len].chars() {
+			chars.next();
+		}
+	}
+	out
+}
+
+/// Parses a string literal, like `"hello\n"`.
+pub fn string() -> token_parser!(String) {
+	filter(|token| matches!(token, Token::StringLiteral(_))).validate(|token, span, emit| {
+		let token = force_token!(token => StringLiteral);
+		unescape(&token, span, emit).add_span(span)
+	})
+}
+
+/// Parses a char literal, like `'a'` or `'\n'`, which must hold exactly one
+/// char once decoded.
+pub fn char() -> token_parser!(char) {
+	filter(|token| matches!(token, Token::CharLiteral(_))).validate(|token, span, emit| {
+		let token = force_token!(token => CharLiteral);
+		let mut invalid = false;
+		let value = unescape(&token, span, &mut |error| {
+			invalid = true;
+			emit(error);
+		});
+		let mut chars = value.chars();
+		if let (Some(c), None) = (chars.next(), chars.next()) {
+			return c.add_span(span);
+		}
+		// invalid escapes are left out of the value, which would make it look
+		// like it has the wrong length when the escape was already reported
+		if invalid {
+			return '\0'.add_span(span);
+		}
+		emit(Simple::custom(
+			span,
+			"char literals must hold exactly one character",
+		));
+		'\0'.add_span(span)
+	})
+}
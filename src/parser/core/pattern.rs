@@ -0,0 +1,63 @@
+use super::{ident, literal};
+use crate::{
+	common::{
+		ident::Ident,
+		pattern::Pattern,
+		span::{Add, Span, Spanned},
+	},
+	lexer::Token,
+};
+use chumsky::prelude::*;
+
+/// Builds the pattern for `path`, which is a variant if it's qualified with
+/// its enum and a binding otherwise. Only variants can have `fields`.
+fn path_pattern(
+	mut path: Vec<Spanned<Ident>>,
+	fields: Option<Vec<Spanned<Pattern>>>,
+	span: Span,
+	emit: &mut dyn FnMut(Simple<Token, Span>),
+) -> Pattern {
+	let variant = path.pop().unwrap();
+	let Some(last) = path.last() else {
+		if fields.is_some() {
+			emit(Simple::custom(
+				span,
+				"variants must be qualified with their enum, like `Enum::Variant(...)`",
+			));
+		}
+		return Pattern::Binding(variant.value);
+	};
+	let enum_span = path[0].span + last.span;
+	let r#enum = Ident::qualify(path.into_iter().map(|x| x.value).collect());
+	Pattern::Variant {
+		r#enum: r#enum.add_span(enum_span),
+		variant,
+		fields: fields.unwrap_or_default(),
+	}
+}
+
+/// Parses a pattern:
+/// - `_` (matches anything)
+/// - `<ident>` (matches anything, binding it to a new variable)
+/// - `<number literal>`, `'<char>'`
+/// - `<enum>::<variant>` or `<enum>::<variant>(<pattern>, ...)`
+pub fn pattern() -> token_parser!(Pattern) {
+	recursive(|pattern| {
+		let number = filter(|token| matches!(token, Token::NumberLiteral(_)))
+			.map_with_span(|token, span| {
+				Pattern::NumberLiteral(force_token!(token => NumberLiteral)).add_span(span)
+			});
+		let path = ident::ident()
+			.separated_by(jpunct!(ColonColon))
+			.at_least(1)
+			.then(parened!(pattern,).or_not())
+			.validate(|(path, fields), span, emit| {
+				path_pattern(path, fields, span, emit).add_span(span)
+			});
+		choice((
+			number,
+			literal::char().map(|x| x.map(Pattern::CharLiteral)),
+			path,
+		))
+	})
+}
@@ -1,19 +1,105 @@
-use super::ident::ident;
-use crate::common::{
-	r#type::{BuiltIn, Type},
-	span::Add,
+use super::ident::potentially_qualified;
+use crate::{
+	common::{
+		r#type::{ArrayLen, BuiltIn, Type},
+		span::{Add, Span},
+	},
+	lexer::{NumberLiteralType, Token},
 };
 use chumsky::prelude::*;
 
+/// Parses the length of an array type, which must be either an integer literal
+/// or the name of a const.
+fn array_len() -> token_parser!(ArrayLen) {
+	let literal = span!(filter(|token| matches!(token, Token::NumberLiteral(_))))
+		.validate(|token, span, emit| {
+			let literal = force_token!(token.value => NumberLiteral);
+			let len = match literal.ty {
+				Some(NumberLiteralType::Float { .. }) => None,
+				_ => literal.as_u128().and_then(|x| usize::try_from(x).ok()),
+			};
+			ArrayLen::Literal(len.unwrap_or_else(|| {
+				emit(Simple::custom(span, "invalid array length"));
+				0
+			}))
+			.add_span(token.span)
+		});
+	let r#const = potentially_qualified().map(|x| x.map(ArrayLen::Const));
+	literal.or(r#const)
+}
+
 pub fn ty() -> token_parser!(Type) {
-	ident().map_with_span(|x, span| {
-		if let Some(ty) = BuiltIn::from_name(&x.value.to_string()) {
-			Type::BuiltIn(ty)
-		} else if x.value.is_discarded() {
-			Type::Inferred
-		} else {
-			Type::User(x.value)
-		}
-		.add_span(span)
+	recursive(|ty| {
+		let base = potentially_qualified().map_with_span(|x, span| {
+			if let Some(ty) = BuiltIn::from_name(&x.value.to_string()) {
+				Type::BuiltIn(ty)
+			} else if x.value.is_discarded() {
+				Type::Inferred
+			} else {
+				Type::User(x.value)
+			}
+			.add_span(span)
+		});
+		let generic = base
+			.then(angled!(ty.clone(),).or_not())
+			.map_with_span(|(base, generics), span| match generics {
+				Some(generics) => Type::Generic(Box::new(base), generics).add_span(span),
+				None => base,
+			});
+		// `&[mut] <ty>` or `*[mut] <ty>`
+		let pointer = jop!(Amp)
+			.to(true)
+			.or(jop!(Star).to(false))
+			.then(jkeyword!(Mut).or_not())
+			.then(ty.clone())
+			.map_with_span(|((is_ref, mutable), ty), span| {
+				if is_ref {
+					Type::Ref(Box::new(ty), mutable.is_some())
+				} else {
+					Type::Ptr(Box::new(ty), mutable.is_some())
+				}
+				.add_span(span)
+			});
+		// `[unsafe] (<ty>, ...) -> <ty>`
+		let func = jkeyword!(Unsafe)
+			.or_not()
+			.then(parened!(ty.clone(),))
+			.then_ignore(jpunct!(Arrow))
+			.then(ty.clone())
+			.map_with_span(|((is_unsafe, args), return_ty), span| {
+				Type::Func {
+					args,
+					return_ty: Box::new(return_ty),
+					is_unsafe: is_unsafe.is_some(),
+				}
+				.add_span(span)
+			});
+		// `(<ty>, <ty>, ...)`, which is only a tuple if it isn't followed by `->`
+		let tuple = span!(parened!(ty
+			.clone()
+			.separated_by(jpunct!(Comma))
+			.at_least(2)
+			.allow_trailing())
+		.map(Type::Tuple));
+		// `[<ty>; <len>]`
+		let array = span!(bracketed!(ty
+			.then_ignore(jpunct!(Semicolon))
+			.then(array_len()))
+		.map(|(ty, len)| Type::Array(Box::new(ty), len)));
+		// `<ty>?`, where `??` is lexed as a single token but stands for two of them
+		let question = span!(jop!(Question)).map(|x| vec![x.span]);
+		let questions = span!(jop!(UnwrapOr)).map(|x| {
+			let first = Span {
+				end: x.span.start + 1,
+				..x.span
+			};
+			vec![first, x.span]
+		});
+		choice((pointer, func, tuple, array, generic))
+			.then(question.or(questions).repeated().flatten())
+			.foldl(|ty, question| {
+				let span = ty.span + question;
+				Type::Optional(Box::new(ty)).add_span(span)
+			})
 	})
 }
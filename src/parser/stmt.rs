@@ -1,12 +1,18 @@
 use super::types::{ParserStmt, ScopeRecursive};
 use chumsky::prelude::*;
 
+mod r#break;
+mod r#const;
+mod r#continue;
 mod create;
-mod func;
+mod r#enum;
+pub mod func;
+mod import;
 mod r#return;
 mod set;
+mod r#struct;
 
-pub fn stmt(s: ScopeRecursive) -> token_parser!(ParserStmt : '_) {
+pub fn stmt(s: ScopeRecursive<'_>) -> token_parser!(ParserStmt : '_) {
 	macro_rules! semi {
 		(Y $stmt:expr) => {
 			$stmt.then_ignore(jpunct!(Semicolon).repeated().at_least(1))
@@ -17,8 +23,14 @@ pub fn stmt(s: ScopeRecursive) -> token_parser!(ParserStmt : '_) {
 	}
 	span!(choice((
 		semi!(Y r#return::stmt(s.clone())),
+		semi!(Y r#break::stmt(s.clone())),
+		semi!(Y r#continue::stmt()),
+		semi!(Y import::stmt()),
+		semi!(Y r#const::stmt(s.clone())),
 		semi!(Y create::stmt(s.clone())),
 		semi!(Y set::stmt(s.clone())),
+		semi!(N r#struct::stmt()),
+		semi!(N r#enum::stmt()),
 		semi!(N func::stmt(s)),
 	)))
 }
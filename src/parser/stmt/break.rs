@@ -0,0 +1,11 @@
+use crate::parser::{
+	core::expr::expr,
+	types::{ParserStmt, ScopeRecursive},
+};
+use chumsky::prelude::*;
+
+pub fn stmt(s: ScopeRecursive<'_>) -> token_parser_no_span!(ParserStmt : '_) {
+	jkeyword!(Break)
+		.ignore_then(expr(s).or_not())
+		.map(|value| ParserStmt::Break { value })
+}
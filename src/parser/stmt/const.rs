@@ -0,0 +1,16 @@
+use crate::parser::{
+	core::{expr::expr, ident, ty::ty},
+	types::{ParserStmt, ScopeRecursive},
+};
+use chumsky::prelude::*;
+
+/// `const <ident>: <ty> = <expr>`
+pub fn stmt(s: ScopeRecursive<'_>) -> token_parser_no_span!(ParserStmt : '_) {
+	jkeyword!(Const)
+		.ignore_then(ident::nodiscard())
+		.then_ignore(jpunct!(Colon))
+		.then(ty())
+		.then_ignore(jassg_op!(Set))
+		.then(expr(s))
+		.map(|((id, ty), value)| ParserStmt::Const { id, ty, value })
+}
@@ -0,0 +1,6 @@
+use crate::parser::types::ParserStmt;
+use chumsky::prelude::*;
+
+pub fn stmt() -> token_parser_no_span!(ParserStmt) {
+	jkeyword!(Continue).to(ParserStmt::Continue)
+}
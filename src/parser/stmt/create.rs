@@ -1,10 +1,29 @@
 use crate::parser::{
-	core::ty_ident::ty_ident,
+	core::{expr::expr, ident::ident, ty_ident::ty_ident},
 	types::{ParserStmt, ScopeRecursive},
 };
 use chumsky::prelude::*;
 
-fn let_var(s: ScopeRecursive) -> token_parser_no_span!(ParserStmt : '_) {
+/// `let (<ident>, <ident>, ...) = <expr>` or `mut (<ident>, <ident>, ...) =
+/// <expr>`
+fn destructure(s: ScopeRecursive<'_>) -> token_parser_no_span!(ParserStmt : '_) {
+	jkeyword!(Let)
+		.to(false)
+		.or(jkeyword!(Mut).to(true))
+		.then(span!(parened!(ident()
+			.separated_by(jpunct!(Comma))
+			.at_least(2)
+			.allow_trailing())))
+		.then_ignore(jassg_op!(Set))
+		.then(expr(s))
+		.map(|((mutable, ids), value)| ParserStmt::Destructure {
+			ids,
+			mutable,
+			value,
+		})
+}
+
+fn let_var(s: ScopeRecursive<'_>) -> token_parser_no_span!(ParserStmt : '_) {
 	jkeyword!(Let)
 		.ignore_then(assg!(s, optexpr ignore Set))
 		.map(|(ident, expr)| ParserStmt::Create {
@@ -14,7 +33,7 @@ fn let_var(s: ScopeRecursive) -> token_parser_no_span!(ParserStmt : '_) {
 		})
 }
 
-fn mut_var(s: ScopeRecursive) -> token_parser_no_span!(ParserStmt : '_) {
+fn mut_var(s: ScopeRecursive<'_>) -> token_parser_no_span!(ParserStmt : '_) {
 	jkeyword!(Mut)
 		.ignore_then(assg!(s, optexpr ignore Set))
 		.map(|(id, expr)| ParserStmt::Create {
@@ -24,7 +43,7 @@ fn mut_var(s: ScopeRecursive) -> token_parser_no_span!(ParserStmt : '_) {
 		})
 }
 
-fn ty_var(s: ScopeRecursive) -> token_parser_no_span!(ParserStmt : '_) {
+fn ty_var(s: ScopeRecursive<'_>) -> token_parser_no_span!(ParserStmt : '_) {
 	jkeyword!(Mut)
 		.or_not()
 		.then(ty_ident())
@@ -36,6 +55,11 @@ fn ty_var(s: ScopeRecursive) -> token_parser_no_span!(ParserStmt : '_) {
 		})
 }
 
-pub fn stmt(s: ScopeRecursive) -> token_parser_no_span!(ParserStmt : '_) {
-	choice((let_var(s.clone()), mut_var(s.clone()), ty_var(s)))
+pub fn stmt(s: ScopeRecursive<'_>) -> token_parser_no_span!(ParserStmt : '_) {
+	choice((
+		destructure(s.clone()),
+		let_var(s.clone()),
+		mut_var(s.clone()),
+		ty_var(s),
+	))
 }
@@ -0,0 +1,28 @@
+use crate::{
+	common::{r#enum::Variant, span::Spanned},
+	parser::{
+		core::{ident, ty::ty},
+		types::ParserStmt,
+	},
+};
+use chumsky::prelude::*;
+
+fn variant() -> token_parser!(Variant) {
+	span!(ident::nodiscard()
+		.then(parened!(ty(),).or_not())
+		.map(|(id, payload)| Variant {
+			id,
+			payload: payload.unwrap_or_default(),
+		}))
+}
+
+fn variants() -> token_parser!(Vec<Spanned<Variant>>) {
+	span!(braced!(variant(),))
+}
+
+pub fn stmt() -> token_parser_no_span!(ParserStmt) {
+	jkeyword!(Enum)
+		.ignore_then(ident::nodiscard())
+		.then(variants())
+		.map(|(id, variants)| ParserStmt::Enum { id, variants })
+}
@@ -56,7 +56,7 @@ fn func_attribs() -> token_parser!(Attribs) {
 	)
 }
 
-fn func_args() -> token_parser!(Vec<Spanned<TypedIdent>>) {
+pub fn func_args() -> token_parser!(Vec<Spanned<TypedIdent>>) {
 	span!(parened!(choice((
 		ty_ident(),
 		span!(ty().map(Spanned::<Type>::add_discarded_ident))
@@ -67,7 +67,7 @@ fn func_generics() -> token_parser!(Vec<Spanned<Ident>>) {
 	span!(angled!(ident(),).or_not().map(Option::unwrap_or_default))
 }
 
-fn func_body(s: ScopeRecursive) -> token_parser!(ParserScope : '_) {
+fn func_body(s: ScopeRecursive<'_>) -> token_parser!(ParserScope : '_) {
 	choice((
 		jpunct!(FatArrow)
 			.ignore_then(expr(s.clone()))
@@ -86,7 +86,7 @@ fn func_body(s: ScopeRecursive) -> token_parser!(ParserScope : '_) {
 	))
 }
 
-pub fn stmt(s: ScopeRecursive) -> token_parser_no_span!(ParserStmt : '_) {
+pub fn stmt(s: ScopeRecursive<'_>) -> token_parser_no_span!(ParserStmt : '_) {
 	func_linkage()
 		.then(ty_ident::nodiscard())
 		.then(func_generics())
@@ -0,0 +1,8 @@
+use crate::parser::{core::ident::potentially_qualified, types::ParserStmt};
+use chumsky::prelude::*;
+
+pub fn stmt() -> token_parser_no_span!(ParserStmt) {
+	jkeyword!(Import)
+		.ignore_then(potentially_qualified())
+		.map(|path| ParserStmt::Import { path })
+}
@@ -31,8 +31,18 @@ macro_rules! set_stmt {
 	};
 }
 
-pub fn stmt(s: ScopeRecursive) -> token_parser_no_span!(ParserStmt : '_) {
+/// `*<expr> = <expr>`
+fn store_stmt(s: ScopeRecursive<'_>) -> token_parser_no_span!(ParserStmt : '_) {
+	jop!(Star)
+		.ignore_then(expr(s.clone()))
+		.then_ignore(jassg_op!(Set))
+		.then(expr(s))
+		.map(|(ptr, value)| ParserStmt::Store { ptr, value })
+}
+
+pub fn stmt(s: ScopeRecursive<'_>) -> token_parser_no_span!(ParserStmt : '_) {
 	choice((
+		store_stmt(s.clone()),
 		set_stmt!(s.clone(), Set),
 		set_stmt!(s.clone(), operator Neg),
 		set_stmt!(s.clone(), operator Star),
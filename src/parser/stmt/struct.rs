@@ -0,0 +1,30 @@
+use crate::{
+	common::{ident::Ident, span::Spanned, typed_ident::TypedIdent},
+	parser::{
+		core::{ident, ty_ident},
+		types::ParserStmt,
+	},
+};
+use chumsky::prelude::*;
+
+fn struct_generics() -> token_parser!(Vec<Spanned<Ident>>) {
+	span!(angled!(ident::nodiscard(),)
+		.or_not()
+		.map(Option::unwrap_or_default))
+}
+
+fn struct_fields() -> token_parser!(Vec<Spanned<TypedIdent>>) {
+	span!(braced!(ty_ident::nodiscard(),))
+}
+
+pub fn stmt() -> token_parser_no_span!(ParserStmt) {
+	jkeyword!(Struct)
+		.ignore_then(ident::nodiscard())
+		.then(struct_generics())
+		.then(struct_fields())
+		.map(|((id, generics), fields)| ParserStmt::Struct {
+			id,
+			generics,
+			fields,
+		})
+}
@@ -1,4 +1,5 @@
 use self::{
+	captures::captures,
 	mappings::{MapRepr, Mappings},
 	resolve::Resolve,
 	resolve_data::ResolveData,
@@ -6,34 +7,34 @@ use self::{
 };
 use crate::{
 	common::{
+		diagnostics::{
+			captured_by_func, import_conflict, nonexistent_item, not_an_enum, type_mismatch,
+		},
 		expr::Expr,
 		func::Signature,
 		ident::{Id, Ident},
+		pattern::Pattern,
+		r#enum::{Enum, Variant},
+		r#struct::Struct,
 		r#type::Type,
-		span::{Add, Spanned},
+		span::{Add, Span, Spanned},
 		stmt::Stmt,
 		typed_ident::TypedIdent,
 	},
-	hoister::{HoistedExpr, HoistedFunc, HoistedScope, HoistedScopeData, HoistedStmt},
+	hoister::{
+		HoistedConst, HoistedExpr, HoistedFunc, HoistedScope, HoistedScopeData, HoistedStmt, Var,
+	},
+	session::CompilerSession,
 };
-use lazy_static::lazy_static;
-use std::{ops::AddAssign, sync::Mutex, collections::HashMap};
+use indexmap::IndexMap;
+use std::{collections::HashMap, hash::BuildHasher};
 
+pub mod captures;
 pub mod mappings;
 pub mod resolve;
 pub mod resolve_data;
 pub mod resolve_specific;
 
-lazy_static! {
-	static ref COUNTER: Mutex<Id> = Mutex::new(0);
-}
-
-fn count() -> Id {
-	let mut counter = COUNTER.lock().unwrap();
-	counter.add_assign(1);
-	*counter
-}
-
 impl Resolve for Ident {
 	fn resolve(&self, _data: &HoistedScopeData, mappings: &mut Mappings) -> Self {
 		match self {
@@ -45,15 +46,7 @@ impl Resolve for Ident {
 
 impl Resolve for Spanned<Type> {
 	fn resolve(&self, data: &HoistedScopeData, mappings: &mut Mappings) -> Self {
-		match self.value.clone() {
-			Type::User(name) => {
-				let id = name.resolve(data, mappings).id();
-				mappings.ensure_repr(id, MapRepr::Type, self.span);
-				Type::User(Ident::Resolved(id)).add_span(self.span)
-			}
-			Type::Generic(..) => todo!("(generic type parsing is not even implemented yet)"),
-			Type::BuiltIn(..) | Type::Inferred => self.clone(),
-		}
+		self.resolve_must_exist(data, mappings)
 	}
 }
 
@@ -66,20 +59,96 @@ impl Resolve for TypedIdent {
 	}
 }
 
+/// Returns the enum and the variant of it `path` refers to, if it's qualified
+/// with an enum in `data`.
+fn as_variant(
+	path: &Spanned<Ident>,
+	data: &HoistedScopeData,
+	mappings: &Mappings,
+) -> Option<(Spanned<Ident>, Spanned<Ident>)> {
+	let Ident::Qualified(segments) = &path.value else {
+		return None;
+	};
+	let (variant, r#enum) = segments.split_last().unwrap();
+	let id = *mappings.get_by_ident(&Ident::qualify(r#enum.to_vec()))?;
+	let r#enum = Ident::Resolved(id);
+	data.enums
+		.contains_key(&r#enum)
+		.then(|| (r#enum.add_span(path.span), variant.clone().add_span(path.span)))
+}
+
+impl Resolve for Spanned<Pattern> {
+	fn resolve(&self, data: &HoistedScopeData, mappings: &mut Mappings) -> Self {
+		match &self.value {
+			Pattern::Binding(Ident::Discarded)
+			| Pattern::NumberLiteral(_)
+			| Pattern::CharLiteral(_) => self.value.clone(),
+			Pattern::Binding(ident) => {
+				let id = ident.clone().add_span(self.span).resolve_make_new(data, mappings);
+				mappings.ensure_repr(id.value.id(), MapRepr::Var, id.span);
+				Pattern::Binding(id.value)
+			}
+			Pattern::Variant {
+				r#enum,
+				variant,
+				fields,
+			} => {
+				let resolved = r#enum.resolve_must_exist(data, mappings);
+				// a failed resolution was already reported
+				if resolved.value.id() != 0 && !data.enums.contains_key(&resolved.value) {
+					not_an_enum(mappings.session, r#enum.span, &r#enum.value);
+				}
+				Pattern::Variant {
+					r#enum: resolved,
+					// variant names are looked up in the enum during inference instead
+					variant: variant.clone(),
+					fields: fields.resolve(data, mappings),
+				}
+			}
+		}
+		.add_span(self.span)
+	}
+}
+
 impl Resolve for Spanned<HoistedExpr> {
+	// the names held by each kind of expr resolve a little differently, and the
+	// arms share too little to be pulled apart
+	#[allow(clippy::too_many_lines)]
 	fn resolve(&self, data: &HoistedScopeData, mappings: &mut Mappings) -> Self {
 		match self.value.clone() {
 			Expr::NumberLiteral(x) => Expr::NumberLiteral(x),
-			Expr::Identifier(x) => Expr::Identifier(
-				x.add_span(self.span)
-					.resolve_must_exist(data, mappings)
-					.value,
-			),
+			Expr::StringLiteral(x) => Expr::StringLiteral(x),
+			Expr::CharLiteral(x) => Expr::CharLiteral(x),
+			Expr::None => Expr::None,
+			Expr::Identifier(x) => {
+				let x = x.add_span(self.span);
+				if let Some((r#enum, variant)) = as_variant(&x, data, mappings) {
+					return Expr::Variant { r#enum, variant }.add_span(self.span);
+				}
+				let id = x.resolve_must_exist(data, mappings);
+				// functions are values too, but types aren't
+				if let Some(MapRepr::Type) = mappings.get_repr(&id.value.id()) {
+					type_mismatch(mappings.session, id.span, MapRepr::Type, MapRepr::Var);
+				}
+				Expr::Identifier(id.value)
+			}
+			Expr::Variant { r#enum, variant } => Expr::Variant { r#enum, variant },
 			Expr::BinaryOp(lhs, op, rhs) => {
 				Expr::BinaryOp(lhs.resolve(data, mappings), op, rhs.resolve(data, mappings))
 			}
 			Expr::UnaryOp(op, value) => Expr::UnaryOp(op, value.resolve(data, mappings)),
+			Expr::Ref { value, mutable } => Expr::Ref {
+				value: value.resolve(data, mappings),
+				mutable,
+			},
+			Expr::Deref(value) => Expr::Deref(value.resolve(data, mappings)),
+			Expr::Try(value) => Expr::Try(value.resolve(data, mappings)),
+			Expr::Cast { value, ty } => Expr::Cast {
+				value: value.resolve(data, mappings),
+				ty: ty.resolve(data, mappings),
+			},
 			Expr::Scope(scope) => Expr::Scope(scope.resolve(data, mappings)),
+			Expr::Unsafe(scope) => Expr::Unsafe(scope.resolve(data, mappings)),
 			Expr::Call {
 				callee,
 				generics,
@@ -89,6 +158,97 @@ impl Resolve for Spanned<HoistedExpr> {
 				generics: generics.resolve_must_exist(data, mappings),
 				args: args.resolve(data, mappings),
 			},
+			Expr::Construct { ty, fields } => Expr::Construct {
+				ty: ty.resolve(data, mappings),
+				// field names are looked up in the struct during inference instead
+				fields: fields
+					.iter()
+					.map(|(field, value)| (field.clone(), value.resolve(data, mappings)))
+					.collect(),
+			},
+			Expr::If {
+				cond,
+				then,
+				otherwise,
+			} => Expr::If {
+				cond: cond.resolve(data, mappings),
+				then: then.resolve(data, mappings),
+				otherwise: otherwise.resolve(data, mappings),
+			},
+			Expr::While {
+				cond,
+				body,
+				otherwise,
+			} => Expr::While {
+				cond: cond.resolve(data, mappings),
+				body: body.resolve(data, mappings),
+				otherwise: otherwise.resolve(data, mappings),
+			},
+			Expr::For {
+				var,
+				start,
+				end,
+				body,
+				otherwise,
+			} => {
+				let start = start.resolve(data, mappings);
+				let end = end.resolve(data, mappings);
+				// the var only exists inside the body
+				let mut body_mappings = mappings.clone();
+				let var = if var.value.is_discarded() {
+					var
+				} else {
+					let var = var.resolve_make_new(data, &mut body_mappings);
+					body_mappings.ensure_repr(var.value.id(), MapRepr::Var, var.span);
+					var
+				};
+				Expr::For {
+					var,
+					start,
+					end,
+					body: body.resolve(data, &mut body_mappings),
+					otherwise: otherwise.resolve(data, mappings),
+				}
+			}
+			Expr::Match { value, arms } => Expr::Match {
+				value: value.resolve(data, mappings),
+				// the vars a pattern binds only exist in its arm
+				arms: arms
+					.iter()
+					.map(|(pattern, value)| {
+						let mut arm_mappings = mappings.clone();
+						let pattern = pattern.resolve(data, &mut arm_mappings);
+						(pattern, value.resolve(data, &mut arm_mappings))
+					})
+					.collect(),
+			},
+			Expr::Field { value, field } => Expr::Field {
+				value: value.resolve(data, mappings),
+				field,
+			},
+			Expr::Tuple(values) => Expr::Tuple(values.resolve(data, mappings)),
+			Expr::TupleField { value, index } => Expr::TupleField {
+				value: value.resolve(data, mappings),
+				index,
+			},
+			Expr::Array(values) => Expr::Array(values.resolve(data, mappings)),
+			Expr::Index { value, index } => Expr::Index {
+				value: value.resolve(data, mappings),
+				index: index.resolve(data, mappings),
+			},
+			Expr::Closure {
+				signature, body, ..
+			} => {
+				let mut closure_mappings = mappings.clone();
+				let signature = signature.resolve(data, &mut closure_mappings);
+				let body = body.resolve(data, &mut closure_mappings);
+				let captures = captures(&signature, &body.value, &closure_mappings);
+				Expr::Closure {
+					signature,
+					body,
+					captures,
+				}
+			}
 		}
 		.add_span(self.span)
 	}
@@ -99,7 +259,7 @@ impl Resolve for Signature {
 	fn resolve(&self, data: &HoistedScopeData, mappings: &mut Mappings) -> Self {
 		let mut resolved_generics = Vec::new();
 		for generic in &self.generics.value {
-			let id = count();
+			let id = mappings.session.new_id();
 			mappings.insert_ty(id, generic.value.clone());
 			resolved_generics.push(Ident::Resolved(id).add_span(generic.span));
 		}
@@ -108,7 +268,7 @@ impl Resolve for Signature {
 			let new_ident = if arg.ident().is_discarded() {
 				Ident::Discarded
 			} else {
-				let id = count();
+				let id = mappings.session.new_id();
 				mappings.insert_var(id, arg.ident().clone());
 				Ident::Resolved(id)
 			};
@@ -138,10 +298,81 @@ impl Resolve for HoistedFunc {
 		let id = self.id.resolve(data, mappings);
 		mappings.ensure_repr(id.value.id(), MapRepr::Func, self.id.span);
 		let mut mappings = mappings.clone();
+		let signature = self.signature.resolve(data, &mut mappings);
+		let body = self.body.resolve(data, &mut mappings);
+		// a named function has nowhere to keep what it would capture, unlike a closure
+		if let Some(body) = &body {
+			for capture in captures(&signature, &body.value, &mappings) {
+				if let Some(var) = mappings.get_by_id(&capture.value.id) {
+					captured_by_func(mappings.session, capture.span, var);
+				}
+			}
+		}
+		Self {
+			// the resolved id is the key this func is stored under, so we keep the name
+			// around for whoever needs to refer to it outside the compiler (e.g. codegen)
+			id: self.id.clone(),
+			signature,
+			body,
+		}
+	}
+}
+
+impl Resolve for Struct {
+	fn resolve(&self, data: &HoistedScopeData, mappings: &mut Mappings) -> Self {
+		let mut mappings = mappings.clone();
+		let mut resolved_generics = Vec::new();
+		for generic in &self.generics.value {
+			let id = mappings.session.new_id();
+			mappings.insert_ty(id, generic.value.clone());
+			resolved_generics.push(Ident::Resolved(id).add_span(generic.span));
+		}
+		let resolved_fields: Vec<_> = self
+			.fields
+			.value
+			.iter()
+			.map(|field| {
+				TypedIdent {
+					ty: field.value.ty.resolve(data, &mut mappings),
+					ident: field.value.ident.clone(),
+				}
+				.add_span(field.span)
+			})
+			.collect();
+		Self {
+			id: self.id.clone(),
+			generics: resolved_generics.add_span(self.generics.span),
+			fields: resolved_fields.add_span(self.fields.span),
+		}
+	}
+}
+
+impl Resolve for Enum {
+	fn resolve(&self, data: &HoistedScopeData, mappings: &mut Mappings) -> Self {
+		let variants = self.variants.map_ref(|variants| {
+			variants
+				.iter()
+				.map(|variant| {
+					variant.map_ref(|x| Variant {
+						id: x.id.clone(),
+						payload: x.payload.resolve(data, mappings),
+					})
+				})
+				.collect()
+		});
+		Self {
+			id: self.id.clone(),
+			variants,
+		}
+	}
+}
+
+impl Resolve for HoistedConst {
+	fn resolve(&self, data: &HoistedScopeData, mappings: &mut Mappings) -> Self {
 		Self {
-			id,
-			signature: self.signature.resolve(data, &mut mappings),
-			body: self.body.resolve(data, &mut mappings),
+			id: self.id.clone(),
+			ty: self.ty.resolve(data, mappings),
+			value: self.value.resolve(data, mappings),
 		}
 	}
 }
@@ -162,19 +393,59 @@ impl Resolve for HoistedStmt {
 					value: value.resolve(data, mappings),
 				}
 			}
+			Self::Destructure {
+				ids,
+				mutable,
+				value,
+			} => {
+				// the value can't refer to the vars it's destructured into
+				let value = value.resolve(data, mappings);
+				let ids = ids.map_ref(|ids| {
+					ids.iter()
+						.map(|id| {
+							if id.value.is_discarded() {
+								return id.clone();
+							}
+							let id = id.resolve_make_new(data, mappings);
+							mappings.ensure_repr(id.value.id(), MapRepr::Var, id.span);
+							id
+						})
+						.collect()
+				});
+				Self::Destructure {
+					ids,
+					mutable: *mutable,
+					value,
+				}
+			}
 			Self::Set { id, value } => {
 				let id = id.resolve_must_exist(data, mappings);
-				mappings.set_repr(&id.value.id(), MapRepr::Var);
+				// assigning to a const is reported by the mutability checker instead
+				if mappings.get_repr(&id.value.id()) != Some(MapRepr::Const) {
+					mappings.set_repr(&id.value.id(), MapRepr::Var);
+				}
 				Self::Set {
 					id,
 					value: value.resolve(data, mappings),
 				}
 			}
-			Self::Func { .. } => unreachable!(),
+			Self::Store { ptr, value } => Self::Store {
+				ptr: ptr.resolve(data, mappings),
+				value: value.resolve(data, mappings),
+			},
+			Self::Func { .. } | Self::Struct { .. } | Self::Enum { .. } | Self::Const { .. } => {
+				unreachable!()
+			}
 			Self::Return { value, is_yield } => Self::Return {
 				value: value.resolve(data, mappings),
 				is_yield: *is_yield,
 			},
+			Self::Break { value } => Self::Break {
+				value: value.resolve(data, mappings),
+			},
+			Self::Continue => Self::Continue,
+			// imports are handled before anything else in the module
+			Self::Import { path } => Self::Import { path: path.clone() },
 		}
 	}
 }
@@ -187,7 +458,45 @@ impl Resolve for HoistedScope {
 		let mut data = data.clone();
 		// add hoisted funcs from scope (we only need to add vars in top level, vars in
 		// non-top-level contexts are actually inaccurate due to shadowing)
+		self.data.make_all_structs(&mut data, &mut mappings);
+		self.data.make_all_enums(&mut data, &mut mappings);
 		self.data.make_all_funcs(&mut data, &mut mappings);
+		self.data.make_all_consts(&mut data, &mut mappings);
+		// structs and enums are resolved before the stmts, as vars declared in them
+		// could shadow the type names
+		let structs = self
+			.data
+			.structs
+			.iter()
+			.map(|(ident, r#struct)| {
+				(
+					ident.resolve(&data, &mut mappings),
+					r#struct.resolve(&data, &mut mappings),
+				)
+			})
+			.collect();
+		let enums = self
+			.data
+			.enums
+			.iter()
+			.map(|(ident, r#enum)| {
+				(
+					ident.resolve(&data, &mut mappings),
+					r#enum.resolve(&data, &mut mappings),
+				)
+			})
+			.collect();
+		let consts = self
+			.data
+			.consts
+			.iter()
+			.map(|(ident, r#const)| {
+				(
+					ident.resolve(&data, &mut mappings),
+					r#const.resolve(&data, &mut mappings),
+				)
+			})
+			.collect();
 		let mut new_scope = Self {
 			stmts: self
 				.stmts
@@ -197,32 +506,156 @@ impl Resolve for HoistedScope {
 			data: HoistedScopeData::default(),
 		};
 		new_scope.data = HoistedScopeData {
-			// FIXME: this is inaccurate in case of shadowing
-			vars: self
-				.data
-				.vars
+			// the hoisted vars are keyed by name, which loses shadowed vars, so we
+			// rebuild them from the resolved stmts instead
+			vars: new_scope
+				.stmts
 				.iter()
-				.map(|(ident, var)| {
-					(
-						Ident::Resolved(*mappings.get_by_ident(ident).unwrap()),
-						var.clone(),
-					)
+				.flat_map(|stmt| match &stmt.value {
+					Stmt::Create { ty_id, mutable, .. } => vec![(
+						ty_id.ident().clone(),
+						Var {
+							ty: ty_id.ty().clone(),
+							mutable: *mutable,
+						}
+						.add_span(stmt.span),
+					)],
+					Stmt::Destructure { ids, mutable, .. } => ids
+						.value
+						.iter()
+						.filter(|id| !id.value.is_discarded())
+						.map(|id| {
+							let var = Var {
+								ty: Type::Inferred,
+								mutable: *mutable,
+							};
+							(id.value.clone(), var.add_span(id.span))
+						})
+						.collect(),
+					_ => Vec::new(),
 				})
 				.collect(),
-			funcs: HashMap::default(),
+			funcs: IndexMap::default(),
+			structs,
+			enums,
+			consts,
+		};
+		// variants of imported enums can be used in funcs too, even though the enums
+		// aren't part of the scope
+		let func_data = HoistedScopeData {
+			enums: data
+				.enums
+				.clone()
+				.into_iter()
+				.chain(new_scope.data.enums.clone())
+				.collect(),
+			..new_scope.data.clone()
 		};
+		// only the funcs declared in this scope belong to it. `data` also holds the
+		// ones of every enclosing scope, still unresolved, which are already reachable
+		// through `mappings` and would otherwise be inferred and generated once per
+		// nested scope
 		new_scope.data.funcs = self
 			.data
 			.funcs
 			.iter()
-			.map(|(ident, func)| (ident.resolve(&new_scope.data, &mut mappings), func.resolve(&new_scope.data, &mut mappings)))
-			.collect::<HashMap<Ident, Spanned<HoistedFunc>>>();
-		new_scope.data.funcs.extend(data.funcs);
+			.map(|(ident, func)| (ident.resolve(&func_data, &mut mappings), func.resolve(&func_data, &mut mappings)))
+			.collect::<IndexMap<Ident, Spanned<HoistedFunc>>>();
 		new_scope
 	}
 }
 
+/// Returns every item `exports` holds, along with its name and what it is.
+fn exported_items(
+	exports: &HoistedScopeData,
+) -> impl Iterator<Item = (Id, Ident, Span, MapRepr)> + '_ {
+	let funcs = exports.funcs.iter().map(|(ident, func)| {
+		(ident.id(), func.value.id.value.clone(), func.span, MapRepr::Func)
+	});
+	let structs = exports.structs.iter().map(|(ident, r#struct)| {
+		(ident.id(), r#struct.value.id.value.clone(), r#struct.span, MapRepr::Type)
+	});
+	let enums = exports.enums.iter().map(|(ident, r#enum)| {
+		(ident.id(), r#enum.value.id.value.clone(), r#enum.span, MapRepr::Type)
+	});
+	let consts = exports.consts.iter().map(|(ident, r#const)| {
+		(ident.id(), r#const.value.id.value.clone(), r#const.span, MapRepr::Const)
+	});
+	funcs.chain(structs).chain(enums).chain(consts)
+}
+
+/// Makes what `path` refers to available in `mappings`. Importing a module
+/// makes its items available qualified with the last segment of its path,
+/// whereas importing a single item makes it available by its own name.
+fn import<S: BuildHasher>(
+	path: &Spanned<Ident>,
+	modules: &HashMap<Ident, HoistedScopeData, S>,
+	imported: &mut HoistedScopeData,
+	mappings: &mut Mappings,
+) {
+	let segments = path.value.segments();
+	if let Some(exports) = modules.get(&path.value) {
+		let alias = segments.last().unwrap();
+		for (id, name, _, repr) in exported_items(exports) {
+			mappings.insert_qualified(id, Ident::Qualified(vec![alias.clone(), name]), repr);
+		}
+		*imported = imported.clone() + exports.clone();
+		return;
+	}
+	let (name, module) = segments.split_last().unwrap();
+	let item = (!module.is_empty())
+		.then(|| modules.get(&Ident::qualify(module.to_vec())))
+		.flatten()
+		.and_then(|exports| {
+			exported_items(exports)
+				.find(|(_, item_name, ..)| item_name == name)
+				.map(|item| (item, exports))
+		});
+	let Some(((id, name, _, repr), exports)) = item else {
+		nonexistent_item(mappings.session, path.span, &path.value);
+		return;
+	};
+	mappings.insert(id, name, repr);
+	let ident = Ident::Resolved(id);
+	if let Some(func) = exports.funcs.get(&ident) {
+		imported.funcs.insert(ident, func.clone());
+	} else if let Some(r#enum) = exports.enums.get(&ident) {
+		imported.enums.insert(ident, r#enum.clone());
+	} else if let Some(r#const) = exports.consts.get(&ident) {
+		imported.consts.insert(ident, r#const.clone());
+	} else {
+		imported.structs.insert(ident.clone(), exports.structs[&ident].clone());
+	}
+}
+
+/// Resolves a module, whose imports are looked up in `modules`: the items
+/// exported by every module resolved so far, keyed by the path of the module.
 #[must_use]
-pub fn resolve(scope: &HoistedScope, imported_data: &HoistedScopeData) -> HoistedScope {
-	scope.resolve(imported_data, &mut Mappings::default())
+pub fn resolve<S: BuildHasher>(
+	session: &CompilerSession,
+	scope: &HoistedScope,
+	modules: &HashMap<Ident, HoistedScopeData, S>,
+) -> HoistedScope {
+	let mut mappings = Mappings::new(session);
+	let mut imported = HoistedScopeData::default();
+	for stmt in &scope.stmts {
+		if let Stmt::Import { path } = &stmt.value {
+			import(path, modules, &mut imported, &mut mappings);
+			// the items declared in the module would silently shadow imported ones
+			let segments = path.value.segments();
+			let name = segments.last().unwrap();
+			let local = scope
+				.data
+				.funcs
+				.get(name)
+				.map(|x| x.span)
+				.or_else(|| scope.data.structs.get(name).map(|x| x.span))
+				.or_else(|| scope.data.enums.get(name).map(|x| x.span))
+				.or_else(|| scope.data.consts.get(name).map(|x| x.span));
+			if let (Some(local), Some(_)) = (local, mappings.get_by_ident(name)) {
+				import_conflict(mappings.session, path.span, local, name);
+			}
+		}
+	}
+	scope.resolve(&imported, &mut mappings)
 }
@@ -0,0 +1,122 @@
+use super::mappings::{MapRepr, Mappings};
+use crate::{
+	common::{
+		expr::{Capture, Expr},
+		func::Signature,
+		ident::{Id, Ident},
+		pattern::Pattern,
+		span::{Add, Span, Spanned},
+		stmt::Stmt,
+		visit::{walk_expr, walk_scope, walk_stmt, Visit},
+	},
+	hoister::{HoistedExpr, HoistedScope, HoistedStmt},
+};
+use std::collections::HashSet;
+
+/// Everything a function body declares, and everything it uses in order of
+/// first use.
+#[derive(Default)]
+struct Uses {
+	declared: HashSet<Id>,
+	used: Vec<Spanned<Capture>>,
+}
+
+impl Uses {
+	fn declare(&mut self, ident: &Ident) {
+		if let Ident::Resolved(x) = ident {
+			self.declared.insert(*x);
+		}
+	}
+
+	fn add_use(&mut self, ident: &Ident, span: Span, mutated: bool) {
+		let Ident::Resolved(id) = *ident else {
+			return;
+		};
+		let capture = Capture { id, mutated }.add_span(span);
+		match self.used.iter_mut().find(|x| x.value.id == id) {
+			// the first mutation is more interesting than the first read
+			Some(used) if mutated && !used.value.mutated => *used = capture,
+			Some(_) => {}
+			None => self.used.push(capture),
+		}
+	}
+
+	fn signature(&mut self, signature: &Signature) {
+		for arg in &signature.args.value {
+			self.declare(arg.ident());
+		}
+	}
+}
+
+impl<'a> Visit<'a, HoistedScope> for Uses {
+	fn scope(&mut self, scope: &'a HoistedScope) {
+		for (ident, func) in &scope.data.funcs {
+			self.declare(ident);
+			self.signature(&func.value.signature);
+			if let Some(body) = &func.value.body {
+				self.scope(&body.value);
+			}
+		}
+		walk_scope(self, scope);
+	}
+
+	fn stmt(&mut self, stmt: &'a Spanned<HoistedStmt>) {
+		match &stmt.value {
+			Stmt::Create { ty_id, .. } => self.declare(ty_id.ident()),
+			Stmt::Destructure { ids, .. } => {
+				for id in &ids.value {
+					self.declare(&id.value);
+				}
+			}
+			Stmt::Set { id, .. } => self.add_use(&id.value, id.span, true),
+			_ => {}
+		}
+		walk_stmt(self, stmt);
+	}
+
+	fn expr(&mut self, expr: &'a Spanned<HoistedExpr>) {
+		match &expr.value {
+			Expr::Identifier(ident) => self.add_use(ident, expr.span, false),
+			Expr::Ref { value, mutable } => {
+				if let Some(place) = value.place() {
+					self.add_use(&place.value, place.span, *mutable);
+				}
+			}
+			Expr::Closure { signature, .. } => self.signature(signature),
+			_ => {}
+		}
+		walk_expr(self, expr);
+	}
+
+	fn loop_var(&mut self, var: &'a Spanned<Ident>) {
+		self.declare(&var.value);
+	}
+
+	fn pattern(&mut self, pattern: &'a Spanned<Pattern>) {
+		for binding in pattern.bindings() {
+			self.declare(&binding.value);
+		}
+	}
+}
+
+/// Finds the variables a resolved function body uses that were declared
+/// outside of it. Ids are unique, so anything used but not declared inside
+/// that `mappings` knows as a variable comes from the surroundings; functions
+/// and types are never captured.
+#[must_use]
+pub fn captures(
+	signature: &Signature,
+	body: &HoistedScope,
+	mappings: &Mappings,
+) -> Vec<Spanned<Capture>> {
+	let mut uses = Uses::default();
+	uses.signature(signature);
+	uses.scope(body);
+	let Uses { declared, used } = uses;
+	used.into_iter()
+		.filter(|x| {
+			!declared.contains(&x.value.id)
+				&& matches!(mappings.get_repr(&x.value.id), Some(MapRepr::Var))
+		})
+		.collect()
+}
@@ -1,8 +1,10 @@
-use super::count;
-use crate::common::{
-	diagnostics::type_mismatch,
-	ident::{Id, Ident},
-	span::Span,
+use crate::{
+	common::{
+		diagnostics::type_mismatch,
+		ident::{Id, Ident},
+		span::Span,
+	},
+	session::CompilerSession,
 };
 use bimap::BiMap;
 use derive_more::Display;
@@ -16,21 +18,38 @@ pub enum MapRepr {
 	Func,
 	#[display(fmt = "type")]
 	Type,
+	#[display(fmt = "const")]
+	Const,
 }
 
-#[derive(Clone, Default)]
-pub struct Mappings {
+#[derive(Clone)]
+pub struct Mappings<'a> {
+	pub session: &'a CompilerSession,
 	pub mappings: BiMap<Id, Ident>,
 	pub reprs: HashMap<Id, MapRepr>,
+	/// Items from imported modules, which are referred to by a qualified ident.
+	/// They live apart from the other mappings since an item may be imported
+	/// both on its own and through its module, under two different names.
+	pub qualified: HashMap<Ident, Id>,
 }
 
-impl Mappings {
+impl<'a> Mappings<'a> {
+	#[must_use]
+	pub fn new(session: &'a CompilerSession) -> Self {
+		Self {
+			session,
+			mappings: BiMap::new(),
+			reprs: HashMap::new(),
+			qualified: HashMap::new(),
+		}
+	}
+
 	#[must_use]
 	pub fn get_or_add_id(&mut self, id: &Ident) -> Id {
 		if let Some(x) = self.mappings.get_by_right(id) {
 			*x
 		} else {
-			let new_id = count();
+			let new_id = self.session.new_id();
 			self.mappings.insert(new_id, id.clone());
 			new_id
 		}
@@ -43,7 +62,9 @@ impl Mappings {
 
 	#[must_use]
 	pub fn get_by_ident(&self, ident: &Ident) -> Option<&Id> {
-		self.mappings.get_by_right(ident)
+		self.mappings
+			.get_by_right(ident)
+			.or_else(|| self.qualified.get(ident))
 	}
 
 	#[must_use]
@@ -60,6 +81,11 @@ impl Mappings {
 		self.reprs.insert(id, repr);
 	}
 
+	pub fn insert_qualified(&mut self, id: Id, ident: Ident, repr: MapRepr) {
+		self.qualified.insert(ident, id);
+		self.reprs.insert(id, repr);
+	}
+
 	pub fn insert_var(&mut self, id: Id, ident: Ident) {
 		self.insert(id, ident, MapRepr::Var);
 	}
@@ -72,11 +98,15 @@ impl Mappings {
 		self.insert(id, ident, MapRepr::Type);
 	}
 
+	pub fn insert_const(&mut self, id: Id, ident: Ident) {
+		self.insert(id, ident, MapRepr::Const);
+	}
+
 	pub fn ensure_repr(&mut self, id: Id, want: MapRepr, span: Span) {
 		match self.get_repr(&id) {
 			Some(x) => {
 				if x != want {
-					type_mismatch(span, x, want);
+					type_mismatch(self.session, span, x, want);
 				}
 			}
 			None => {
@@ -1,18 +1,26 @@
-use super::{count, mappings::Mappings};
-use crate::{common::ident::Ident, hoister::HoistedScopeData};
+use super::mappings::Mappings;
+use crate::{common::ident::Ident, hoister::HoistedScopeData, session::CompilerSession};
 
 pub trait ResolveData {
 	fn make_all_funcs(&self, data: &mut HoistedScopeData, mappings: &mut Mappings);
 	fn make_all_vars(&self, data: &mut HoistedScopeData, mappings: &mut Mappings);
-	// TODO: fn make_all_tys(&self) -> Self;
-	fn just_make_all_funcs(&self) -> (HoistedScopeData, Mappings);
-	fn just_make_all_funcs_and_vars(&self) -> (HoistedScopeData, Mappings);
+	fn make_all_structs(&self, data: &mut HoistedScopeData, mappings: &mut Mappings);
+	fn make_all_enums(&self, data: &mut HoistedScopeData, mappings: &mut Mappings);
+	fn make_all_consts(&self, data: &mut HoistedScopeData, mappings: &mut Mappings);
+	fn just_make_all_funcs<'a>(
+		&self,
+		session: &'a CompilerSession,
+	) -> (HoistedScopeData, Mappings<'a>);
+	fn just_make_all_funcs_and_vars<'a>(
+		&self,
+		session: &'a CompilerSession,
+	) -> (HoistedScopeData, Mappings<'a>);
 }
 
 impl ResolveData for HoistedScopeData {
 	fn make_all_funcs(&self, data: &mut HoistedScopeData, mappings: &mut Mappings) {
 		for (ident, func) in self.funcs.clone() {
-			let id = count();
+			let id = mappings.session.new_id();
 			mappings.insert_func(id, ident);
 			data.funcs.insert(Ident::Resolved(id), func);
 		}
@@ -20,22 +28,52 @@ impl ResolveData for HoistedScopeData {
 
 	fn make_all_vars(&self, data: &mut HoistedScopeData, mappings: &mut Mappings) {
 		for (ident, var) in self.vars.clone() {
-			let id = count();
+			let id = mappings.session.new_id();
 			mappings.insert_var(id, ident);
 			data.vars.insert(Ident::Resolved(id), var);
 		}
 	}
 
-	fn just_make_all_funcs(&self) -> (HoistedScopeData, Mappings) {
+	fn make_all_structs(&self, data: &mut HoistedScopeData, mappings: &mut Mappings) {
+		for (ident, r#struct) in self.structs.clone() {
+			let id = mappings.session.new_id();
+			mappings.insert_ty(id, ident);
+			data.structs.insert(Ident::Resolved(id), r#struct);
+		}
+	}
+
+	fn make_all_enums(&self, data: &mut HoistedScopeData, mappings: &mut Mappings) {
+		for (ident, r#enum) in self.enums.clone() {
+			let id = mappings.session.new_id();
+			mappings.insert_ty(id, ident);
+			data.enums.insert(Ident::Resolved(id), r#enum);
+		}
+	}
+
+	fn make_all_consts(&self, data: &mut HoistedScopeData, mappings: &mut Mappings) {
+		for (ident, r#const) in self.consts.clone() {
+			let id = mappings.session.new_id();
+			mappings.insert_const(id, ident);
+			data.consts.insert(Ident::Resolved(id), r#const);
+		}
+	}
+
+	fn just_make_all_funcs<'a>(
+		&self,
+		session: &'a CompilerSession,
+	) -> (HoistedScopeData, Mappings<'a>) {
 		let mut data = HoistedScopeData::default();
-		let mut mappings = Mappings::default();
+		let mut mappings = Mappings::new(session);
 		self.make_all_funcs(&mut data, &mut mappings);
 		(data, mappings)
 	}
 
-	fn just_make_all_funcs_and_vars(&self) -> (HoistedScopeData, Mappings) {
+	fn just_make_all_funcs_and_vars<'a>(
+		&self,
+		session: &'a CompilerSession,
+	) -> (HoistedScopeData, Mappings<'a>) {
 		let mut data = HoistedScopeData::default();
-		let mut mappings = Mappings::default();
+		let mut mappings = Mappings::new(session);
 		self.make_all_funcs(&mut data, &mut mappings);
 		self.make_all_vars(&mut data, &mut mappings);
 		(data, mappings)
@@ -1,9 +1,9 @@
-use super::{count, mappings::Mappings};
+use super::mappings::{MapRepr, Mappings};
 use crate::{
 	common::{
 		diagnostics::{discarded_ident, nonexistent_item},
 		ident::Ident,
-		r#type::Type,
+		r#type::{ArrayLen, Type},
 		span::{Add, Spanned},
 		typed_ident::TypedIdent,
 	},
@@ -67,19 +67,19 @@ fn fail_ident() -> Ident {
 
 impl ResolveSpecific for Spanned<Ident> {
 	fn resolve_make_new(&self, _data: &HoistedScopeData, mappings: &mut Mappings) -> Self {
-		let id = count();
+		let id = mappings.session.new_id();
 		mappings.insert_var(id, self.value.clone());
 		Ident::Resolved(id).add_span(self.span)
 	}
 
 	fn resolve_must_exist(&self, _data: &HoistedScopeData, mappings: &mut Mappings) -> Self {
 		if let Ident::Discarded = self.value {
-			discarded_ident(self.span);
+			discarded_ident(mappings.session, self.span);
 			fail_ident()
 		} else if let Some(id) = mappings.get_by_ident(&self.value) {
 			Ident::Resolved(*id)
 		} else {
-			nonexistent_item(self.span, &self.value);
+			nonexistent_item(mappings.session, self.span, &self.value);
 			fail_ident()
 		}
 		.add_span(self.span)
@@ -90,25 +90,76 @@ impl ResolveSpecific for Spanned<Type> {
 	fn resolve_make_new(&self, _data: &HoistedScopeData, mappings: &mut Mappings) -> Self {
 		match &self.value {
 			Type::User(name) => {
-				let id = count();
+				let id = mappings.session.new_id();
 				mappings.insert_ty(id, name.clone());
 				Type::User(Ident::Resolved(id)).add_span(self.span)
 			}
 			Type::Generic(..) => todo!("(generic type parsing is not even implemented yet)"),
+			Type::Ref(..)
+			| Type::Ptr(..)
+			| Type::Optional(..)
+			| Type::Func { .. }
+			| Type::Tuple(..)
+			| Type::Array(..) => {
+				unreachable!("only names can declare new types")
+			}
 			Type::BuiltIn(..) | Type::Inferred => self.clone(),
 		}
 	}
 
-	fn resolve_must_exist(&self, _data: &HoistedScopeData, mappings: &mut Mappings) -> Self {
+	fn resolve_must_exist(&self, data: &HoistedScopeData, mappings: &mut Mappings) -> Self {
 		match &self.value {
 			Type::User(name) => Type::User(if let Some(id) = mappings.get_by_ident(name) {
-				Ident::Resolved(*id)
+				let id = *id;
+				mappings.ensure_repr(id, MapRepr::Type, self.span);
+				Ident::Resolved(id)
 			} else {
-				nonexistent_item(self.span, name);
+				nonexistent_item(mappings.session, self.span, name);
 				fail_ident()
 			})
 			.add_span(self.span),
-			Type::Generic(..) => todo!("(generic type parsing is not even implemented yet)"),
+			Type::Generic(base, generics) => Type::Generic(
+				base.resolve_must_exist(data, mappings),
+				generics.resolve_must_exist(data, mappings),
+			)
+			.add_span(self.span),
+			Type::Ref(ty, mutable) => {
+				Type::Ref(ty.resolve_must_exist(data, mappings), *mutable).add_span(self.span)
+			}
+			Type::Ptr(ty, mutable) => {
+				Type::Ptr(ty.resolve_must_exist(data, mappings), *mutable).add_span(self.span)
+			}
+			Type::Optional(ty) => {
+				Type::Optional(ty.resolve_must_exist(data, mappings)).add_span(self.span)
+			}
+			Type::Func {
+				args,
+				return_ty,
+				is_unsafe,
+			} => Type::Func {
+				args: args.resolve_must_exist(data, mappings),
+				return_ty: return_ty.resolve_must_exist(data, mappings),
+				is_unsafe: *is_unsafe,
+			}
+			.add_span(self.span),
+			Type::Tuple(tys) => Type::Tuple(tys.resolve_must_exist(data, mappings)).add_span(self.span),
+			Type::Array(ty, len) => {
+				let span = len.span;
+				let len = len.map_ref(|len| match len {
+					ArrayLen::Literal(x) => ArrayLen::Literal(*x),
+					ArrayLen::Const(name) => ArrayLen::Const(
+						if let Some(id) = mappings.get_by_ident(name) {
+							let id = *id;
+							mappings.ensure_repr(id, MapRepr::Const, span);
+							Ident::Resolved(id)
+						} else {
+							nonexistent_item(mappings.session, span, name);
+							fail_ident()
+						},
+					),
+				});
+				Type::Array(ty.resolve_must_exist(data, mappings), len).add_span(self.span)
+			}
 			Type::BuiltIn(..) | Type::Inferred => self.clone(),
 		}
 	}
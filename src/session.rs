@@ -0,0 +1,53 @@
+use crate::{common::ident::Id, infer::engine::Engine};
+use codespan_reporting::diagnostic::{Diagnostic, Severity};
+use std::cell::{Cell, RefCell, RefMut};
+
+/// The state shared by every step of a single compilation: the diagnostics
+/// reported so far, the ids handed out to resolved idents and the inference
+/// engine. Steps only ever get a shared reference to it, so that it can be
+/// kept around by the passes walking the program.
+#[derive(Default)]
+pub struct CompilerSession {
+	diagnostics: RefCell<Vec<Diagnostic<usize>>>,
+	last_id: Cell<Id>,
+	engine: RefCell<Engine>,
+}
+
+impl CompilerSession {
+	pub fn add_diagnostic(&self, diagnostic: Diagnostic<usize>) {
+		self.diagnostics.borrow_mut().push(diagnostic);
+	}
+
+	pub fn add_diagnostics(&self, diagnostics: &mut Vec<Diagnostic<usize>>) {
+		self.diagnostics.borrow_mut().append(diagnostics);
+	}
+
+	/// Takes every diagnostic reported so far out of the session.
+	#[must_use]
+	pub fn take_diagnostics(&self) -> Vec<Diagnostic<usize>> {
+		self.diagnostics.take()
+	}
+
+	#[must_use]
+	pub fn error_count(&self) -> usize {
+		self.diagnostics
+			.borrow()
+			.iter()
+			.filter(|x| x.severity >= Severity::Error)
+			.count()
+	}
+
+	/// Hands out an id no ident of this compilation has been given yet.
+	#[must_use]
+	pub fn new_id(&self) -> Id {
+		self.last_id.set(self.last_id.get() + 1);
+		self.last_id.get()
+	}
+
+	/// Gives access to the inference engine, which can't be accessed again
+	/// before the returned guard is dropped.
+	#[must_use]
+	pub fn engine(&self) -> RefMut<'_, Engine> {
+		self.engine.borrow_mut()
+	}
+}
@@ -0,0 +1,236 @@
+//! Golden-file tests for every step of the compiler. Each `.lang` program in
+//! `tests/golden` is compiled as far as it gets, then run and built if it
+//! compiled, and what every step made of it is compared against the file next
+//! to it named after the step. A directory of `.lang` files is a program made
+//! of several modules, whose files are named after the directory. Every step
+//! has its own test, so a failure names the step whose output changed. Running
+//! the tests with `BLESS=1` writes what the steps made into those files
+//! instead, so that changes to the output can be reviewed as a diff.
+
+use codespan_reporting::{
+	diagnostic::{Diagnostic, Severity},
+	files::SimpleFiles,
+	term::{self, termcolor::NoColor},
+};
+use lang::{codegen, interp, lexer, session::CompilerSession, Source};
+use std::{
+	env,
+	fmt::Write,
+	fs,
+	path::{Path, PathBuf},
+	sync::OnceLock,
+	thread,
+};
+
+const STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// A program of the suite along with what each step that got to run made of
+/// it, keyed by the extension of the step's file.
+struct Program {
+	path: PathBuf,
+	outputs: Vec<(&'static str, String)>,
+}
+
+/// Compiles every program of the suite, which is only done once for all the
+/// tests.
+fn programs() -> &'static [Program] {
+	static PROGRAMS: OnceLock<Vec<Program>> = OnceLock::new();
+	PROGRAMS.get_or_init(|| {
+		let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+		let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+			.unwrap()
+			.map(|x| x.unwrap().path())
+			.filter(|x| x.is_dir() || x.extension().is_some_and(|x| x == "lang"))
+			.collect();
+		paths.sort();
+		assert!(!paths.is_empty(), "no programs in {}", dir.display());
+		paths
+			.into_iter()
+			.map(|path| {
+				let files = files(&path);
+				let sources: Vec<_> = files
+					.iter()
+					.map(|(name, code)| Source { name, code })
+					.collect();
+				let outputs = thread::scope(|scope| {
+					// the parser recurses deeply enough in debug builds to overflow
+					// the default stack of test threads, which is smaller than the
+					// main one
+					thread::Builder::new()
+						.stack_size(STACK_SIZE)
+						.spawn_scoped(scope, || steps(&sources))
+						.unwrap()
+						.join()
+						.unwrap()
+				});
+				Program {
+					path: path.with_extension("lang"),
+					outputs,
+				}
+			})
+			.collect()
+	})
+}
+
+/// Reads the name and code of every file of the program at `path`. The files
+/// of a directory are sorted by name, so the first one is the root module the
+/// IR is named after.
+fn files(path: &Path) -> Vec<(String, String)> {
+	let name = |x: &Path| x.file_name().unwrap().to_str().unwrap().to_owned();
+	if !path.is_dir() {
+		return vec![(name(path), fs::read_to_string(path).unwrap())];
+	}
+	let mut files: Vec<_> = fs::read_dir(path)
+		.unwrap()
+		.map(|x| x.unwrap().path())
+		.filter(|x| x.extension().is_some_and(|x| x == "lang"))
+		.map(|x| (name(&x), fs::read_to_string(&x).unwrap()))
+		.collect();
+	files.sort();
+	files
+}
+
+/// Joins what a step made of every file of a program, under the name of each
+/// file when there are several.
+fn join(sources: &[Source], outputs: impl IntoIterator<Item = String>) -> String {
+	if let [_] = sources {
+		return outputs.into_iter().collect();
+	}
+	sources
+		.iter()
+		.zip(outputs)
+		.map(|(source, output)| format!("// {}\n{output}", source.name))
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+/// Runs every step on the program made of `sources`.
+fn steps(sources: &[Source]) -> Vec<(&'static str, String)> {
+	// lexing again as part of parsing would report every error twice
+	let tokens = sources.iter().enumerate().map(|(file_id, source)| {
+		lexer::lex(&CompilerSession::default(), source.code, file_id)
+			.into_iter()
+			.fold(String::new(), |mut acc, (token, span)| {
+				writeln!(acc, "{}..{}: {token:?}", span.start, span.end).unwrap();
+				acc
+			})
+	});
+	let mut outputs = vec![("tokens", join(sources, tokens))];
+
+	let session = CompilerSession::default();
+	let mut compilation = lang::compile(&session, sources);
+	if !compilation.parsed.is_empty() {
+		let ast = compilation.parsed.iter().map(ToString::to_string);
+		outputs.push(("ast", join(sources, ast)));
+	}
+	if let Some(resolved) = &compilation.resolved {
+		let resolved = resolved.iter().map(ToString::to_string);
+		outputs.push(("resolved", join(sources, resolved)));
+	}
+	if let Some(typed) = &compilation.program {
+		outputs.push(("types", typed.to_string()));
+		let failed = compilation
+			.diagnostics
+			.iter()
+			.any(|x| x.severity >= Severity::Error);
+		if !failed {
+			let value = interp::run(&session, typed);
+			outputs.extend(value.map(|x| ("run", format!("{x}\n"))));
+			// like the driver, only keep IR that codegen found nothing wrong with
+			let errors = session.error_count();
+			let ir = codegen::codegen(&session, sources[0].name, typed);
+			if session.error_count() == errors {
+				outputs.push(("ir", ir));
+			}
+		}
+	}
+
+	compilation.diagnostics.append(&mut session.take_diagnostics());
+	if !compilation.diagnostics.is_empty() {
+		outputs.push(("diagnostics", render(sources, &compilation.diagnostics)));
+	}
+	outputs
+}
+
+fn render(sources: &[Source], diagnostics: &[Diagnostic<usize>]) -> String {
+	let mut files = SimpleFiles::new();
+	for source in sources {
+		files.add(source.name, source.code);
+	}
+	let mut writer = NoColor::new(Vec::new());
+	let config = term::Config::default();
+	for diagnostic in diagnostics {
+		term::emit(&mut writer, &config, &files, diagnostic).unwrap();
+	}
+	String::from_utf8(writer.into_inner()).unwrap()
+}
+
+/// Compares what `step` made of every program against its golden file, or
+/// overwrites the file with it when blessing.
+fn check(step: &str) {
+	let bless = env::var_os("BLESS").is_some_and(|x| x != "0");
+	let mut mismatches = Vec::new();
+	for program in programs() {
+		let path = program.path.with_extension(step);
+		let actual = program.outputs.iter().find(|x| x.0 == step).map(|x| &x.1);
+		let expected = fs::read_to_string(&path).ok();
+		if actual == expected.as_ref() {
+			continue;
+		}
+		if bless {
+			match actual {
+				Some(actual) => fs::write(&path, actual).unwrap(),
+				None => fs::remove_file(&path).unwrap(),
+			}
+			continue;
+		}
+		let show = |x: Option<&String>| x.map_or("<step did not run>\n".into(), Clone::clone);
+		mismatches.push(format!(
+			"{}\n--- expected\n{}--- actual\n{}",
+			path.display(),
+			show(expected.as_ref()),
+			show(actual),
+		));
+	}
+	assert!(
+		mismatches.is_empty(),
+		"{} {step} output(s) differ from their golden files, run with BLESS=1 to update them:\n\n{}",
+		mismatches.len(),
+		mismatches.join("\n"),
+	);
+}
+
+#[test]
+fn tokens() {
+	check("tokens");
+}
+
+#[test]
+fn ast() {
+	check("ast");
+}
+
+#[test]
+fn resolved() {
+	check("resolved");
+}
+
+#[test]
+fn types() {
+	check("types");
+}
+
+#[test]
+fn run() {
+	check("run");
+}
+
+#[test]
+fn ir() {
+	check("ir");
+}
+
+#[test]
+fn diagnostics() {
+	check("diagnostics");
+}